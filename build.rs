@@ -0,0 +1,22 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Record the commit the CLI is built from, for `stoffel version` and bug reports
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=STOFFEL_GIT_COMMIT={}", commit);
+    // Outside a checkout (e.g. a crates.io tarball) there is nothing to watch
+    for path in [".git/HEAD", ".git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
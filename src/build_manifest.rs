@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the build manifest inside `target/<profile>/`
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// Artifact metadata recorded by `stoffel build`
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Manifest {
+    /// Optimization level the artifacts were compiled with
+    pub opt_level: u8,
+    pub protocol: String,
+    /// Number of parties the artifacts were compiled for
+    pub parties: u8,
+    pub threshold: u8,
+    pub field: String,
+    pub artifacts: Vec<Artifact>,
+}
+
+/// One compiled source and the binary it produced
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Artifact {
+    pub source: String,
+    pub output: String,
+    /// SHA-256 of the output, so deploy tooling can tell when it changed
+    pub sha256: String,
+}
+
+impl Artifact {
+    /// Record `output`, hashing its current contents
+    pub fn new(source: &str, output: &str) -> Result<Artifact, String> {
+        let content = fs::read(output).map_err(|e| format!("Failed to read {}: {}", output, e))?;
+        Ok(Artifact {
+            source: source.to_string(),
+            output: output.to_string(),
+            sha256: Sha256::digest(&content).iter().map(|byte| format!("{:02x}", byte)).collect(),
+        })
+    }
+}
+
+/// Location of the build manifest for a build profile directory
+pub fn manifest_path(profile_dir: &Path) -> PathBuf {
+    profile_dir.join(MANIFEST_NAME)
+}
+
+/// Load the build manifest, or `None` if nothing has been built yet
+pub fn load(path: &Path) -> Result<Option<Manifest>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Write the build manifest, replacing any from an earlier build
+pub fn save(path: &Path, manifest: &Manifest) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Delete the build manifest, if any, so a failed build can't leave a stale one behind
+pub fn remove(path: &Path) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to remove {}: {}", path.display(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_manifest_loads_back_with_artifact_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("main.bin");
+        fs::write(&output, "bin").unwrap();
+
+        let manifest = Manifest {
+            opt_level: 2,
+            protocol: "honeybadger".to_string(),
+            parties: 5,
+            threshold: 1,
+            field: "bls12-381".to_string(),
+            artifacts: vec![Artifact::new("src/main.stfl", &output.to_string_lossy()).unwrap()],
+        };
+        let path = manifest_path(dir.path());
+        save(&path, &manifest).unwrap();
+
+        assert_eq!(load(&path).unwrap(), Some(manifest));
+        assert_eq!(
+            load(&path).unwrap().unwrap().artifacts[0].sha256,
+            "51a1f05af85e342e3c849b47d387086476282d5f50dc240c19216d6edfb1eb5a"
+        );
+        assert_eq!(load(&dir.path().join("missing.json")).unwrap(), None);
+
+        remove(&path).unwrap();
+        assert_eq!(load(&path).unwrap(), None);
+        remove(&path).unwrap();
+    }
+}
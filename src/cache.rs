@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Global caches under `~/.stoffel/cache/`, as (description, directory name)
+pub const GLOBAL_CACHES: &[(&str, &str)] = &[
+    ("git dependencies", "git"),
+    ("registry downloads", "registry"),
+    ("compiled templates", "templates"),
+];
+
+/// Root directory of the global caches
+pub fn cache_root() -> Option<PathBuf> {
+    crate::home::stoffel_home().map(|home| home.join("cache"))
+}
+
+/// Total size in bytes of all files below `path`
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(_) => entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Remove a directory tree, returning the number of bytes reclaimed
+///
+/// A directory that does not exist reclaims nothing.
+pub fn remove_dir(path: &Path) -> Result<u64, String> {
+    if !path.exists() {
+        return Ok(0);
+    }
+
+    let size = dir_size(path);
+    fs::remove_dir_all(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    Ok(size)
+}
+
+/// Human-readable byte count, e.g. "1.5 MiB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
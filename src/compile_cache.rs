@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::style::{self, Status};
+
+/// Project-local directory holding the compile cache index
+pub const CACHE_DIR: &str = ".stoffel-cache";
+
+const INDEX_NAME: &str = "index.json";
+
+/// What a source file was last compiled from and into
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CacheEntry {
+    /// SHA-256 of the source contents and any other compiler inputs
+    pub hash: String,
+    pub opt_level: u8,
+    pub binary: bool,
+    pub output: String,
+}
+
+/// Index of previously compiled sources, keyed by source path
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CompileCache {
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+impl CompileCache {
+    /// Load the index under `root`; a missing or unreadable index starts empty
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CACHE_DIR).join(INDEX_NAME);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("{}  Ignoring corrupt compile cache {}: {}", style::prefix(Status::Warning), path.display(), e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, root: &Path) -> Result<(), String> {
+        let dir = root.join(CACHE_DIR);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize compile cache: {}", e))?;
+        let path = dir.join(INDEX_NAME);
+        fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Whether `source` was last compiled with exactly `entry` and its output is still there
+    pub fn is_fresh(&self, source: &str, entry: &CacheEntry) -> bool {
+        self.entries.get(source) == Some(entry) && Path::new(&entry.output).exists()
+    }
+
+    pub fn record(&mut self, source: &str, entry: CacheEntry) {
+        self.entries.insert(source.to_string(), entry);
+    }
+}
+
+/// Hash a source file together with extra inputs that change its compiled output
+pub fn fingerprint(source: &Path, extra: &str) -> Result<String, String> {
+    let content = fs::read(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    let mut hasher = Sha256::new();
+    hasher.update(&content);
+    hasher.update([0]);
+    hasher.update(extra.as_bytes());
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Hash every .stfl file below `dir` together with its path
+///
+/// Folded into cache keys so editing a module the program imports makes it
+/// stale too. A missing directory hashes like an empty one.
+pub fn sources_digest(dir: &Path) -> Result<String, String> {
+    digest(dir, |path| path.extension().is_some_and(|extension| extension == "stfl"))
+}
+
+/// Hash every file below `path`, or `path` itself when it's a file
+///
+/// Used for the standard library, which is compiled in wherever it lives.
+pub fn tree_digest(path: &Path) -> Result<String, String> {
+    digest(path, |_| true)
+}
+
+fn digest(path: &Path, keep: fn(&Path) -> bool) -> Result<String, String> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        collect_files(path, keep, &mut HashSet::new(), &mut files)?;
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+    }
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for file in &files {
+        let content = fs::read(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        hasher.update(file.strip_prefix(path).unwrap_or(file).to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(&content);
+        hasher.update([0]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn collect_files(dir: &Path, keep: fn(&Path) -> bool, visited: &mut HashSet<PathBuf>, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    // A symlink back to a directory already hashed would otherwise recurse forever
+    let canonical = dir.canonicalize()
+        .map_err(|e| format!("Failed to resolve directory {}: {}", dir.display(), e))?;
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?.path();
+        if path.is_dir() {
+            collect_files(&path, keep, visited, files)?;
+        } else if keep(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_is_stale_when_inputs_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("main.stfl");
+        let output = dir.path().join("main.bin");
+        fs::write(&source, "main() {}").unwrap();
+        fs::write(&output, "").unwrap();
+
+        let entry = CacheEntry {
+            hash: fingerprint(&source, "").unwrap(),
+            opt_level: 0,
+            binary: true,
+            output: output.to_string_lossy().to_string(),
+        };
+        let mut cache = CompileCache::default();
+        cache.record("main.stfl", entry.clone());
+        cache.save(dir.path()).unwrap();
+
+        let cache = CompileCache::load(dir.path());
+        assert!(cache.is_fresh("main.stfl", &entry));
+        assert!(!cache.is_fresh("main.stfl", &CacheEntry { opt_level: 2, ..entry.clone() }));
+        assert!(!cache.is_fresh("main.stfl", &CacheEntry { binary: false, ..entry.clone() }));
+
+        fs::write(&source, "main() { 1 }").unwrap();
+        let edited = CacheEntry { hash: fingerprint(&source, "").unwrap(), ..entry.clone() };
+        assert!(!cache.is_fresh("main.stfl", &edited));
+
+        fs::remove_file(&output).unwrap();
+        assert!(!cache.is_fresh("main.stfl", &entry));
+    }
+
+    #[test]
+    fn editing_any_module_changes_the_sources_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("lib")).unwrap();
+        fs::write(src.join("main.stfl"), "main() {}").unwrap();
+        fs::write(src.join("lib/util.stfl"), "util() {}").unwrap();
+        let digest = sources_digest(&src).unwrap();
+
+        fs::write(src.join("notes.md"), "not a module").unwrap();
+        assert_eq!(sources_digest(&src).unwrap(), digest);
+
+        fs::write(src.join("lib/util.stfl"), "util() { 1 }").unwrap();
+        assert_ne!(sources_digest(&src).unwrap(), digest);
+        assert!(sources_digest(&dir.path().join("missing")).is_ok());
+
+        let digest = tree_digest(&src).unwrap();
+        fs::write(src.join("notes.md"), "edited").unwrap();
+        assert_ne!(tree_digest(&src).unwrap(), digest);
+    }
+}
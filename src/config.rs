@@ -0,0 +1,416 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::init::StoffelConfig;
+use crate::{calculate_threshold, manifest_profile_layer, merge_mpc_layers, validate_mpc_params};
+use crate::{ConfigSource, MpcLayer};
+
+/// File name of the project manifest
+pub const MANIFEST_NAME: &str = "Stoffel.toml";
+
+/// Parse a Stoffel.toml
+///
+/// Unknown keys are rejected; the parse error names the key and its line.
+pub fn load(path: &Path) -> Result<StoffelConfig, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Write a manifest back to disk
+pub fn save(path: &Path, config: &StoffelConfig) -> Result<(), String> {
+    let content = toml::to_string(config)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Declare a dependency in `[dependencies]`, or `[dev_dependencies]` when `dev` is set
+///
+/// A package may only be declared once across both tables.
+pub fn add_dependency(config: &mut StoffelConfig, name: &str, version: &str, dev: bool) -> Result<(), String> {
+    for (table, dependencies) in [("dependencies", &config.dependencies), ("dev_dependencies", &config.dev_dependencies)] {
+        if let Some(existing) = dependencies.as_ref().and_then(|deps| deps.get(name)) {
+            return Err(format!(
+                "'{}' is already in [{}] ({} = \"{}\"). Use `stoffel update {}` to change it.",
+                name, table, name, existing, name
+            ));
+        }
+    }
+
+    let dependencies = if dev { &mut config.dev_dependencies } else { &mut config.dependencies };
+    dependencies.get_or_insert_with(Default::default).insert(name.to_string(), version.to_string());
+    Ok(())
+}
+
+/// Load and validate the manifest of the project in `dir`
+pub fn load_project(dir: &Path) -> Result<StoffelConfig, String> {
+    let path = dir.join(MANIFEST_NAME);
+    let config = load(&path)?;
+    validate(&config).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    Ok(config)
+}
+
+/// Reject protocol, field, party and threshold settings that can't be run
+pub fn validate(config: &StoffelConfig) -> Result<(), String> {
+    let base = base_layer(config)?;
+    let settings = merge_mpc_layers(&[base]).map_err(|e| format!("mpc.parties = {}: {}", config.mpc.parties, e))?;
+
+    if let Err(e) = validate_mpc_params(settings.parties, settings.threshold, &settings.protocol) {
+        // Blame the threshold only if the party count allows some threshold
+        let key = if config.mpc.threshold.is_some() && calculate_threshold(settings.parties, &settings.protocol).is_ok() {
+            format!("mpc.threshold = {}", settings.threshold)
+        } else {
+            format!("mpc.parties = {}", settings.parties)
+        };
+        return Err(format!("{}: {}", key, e));
+    }
+
+    validate_profiles(config)
+}
+
+/// Check that every [mpc.profiles.<name>] merged over [mpc] forms valid parameters
+pub fn validate_profiles(config: &StoffelConfig) -> Result<(), String> {
+    let base = base_layer(config)?;
+
+    let mut names: Vec<&String> = config.mpc.profiles.iter().flatten().map(|(name, _)| name).collect();
+    names.sort();
+
+    for name in names {
+        merge_mpc_layers(&[manifest_profile_layer(config, name)?, base.clone()])
+            .and_then(|settings| validate_mpc_params(settings.parties, settings.threshold, &settings.protocol))
+            .map_err(|e| format!("mpc.profiles.{}: {}", name, e))?;
+    }
+
+    Ok(())
+}
+
+/// The base [mpc] section as a settings layer
+pub fn base_layer(config: &StoffelConfig) -> Result<MpcLayer, String> {
+    MpcLayer::from_manifest(
+        Some(&config.mpc.protocol),
+        Some(config.mpc.parties),
+        config.mpc.threshold,
+        Some(&config.mpc.field),
+        ConfigSource::Manifest,
+    )
+    .map_err(|e| format!("[mpc] {}", e))
+}
+
+/// `[workspace]` in a Stoffel.toml
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    /// Member directories relative to the workspace root; globs like `libs/*` are allowed
+    pub members: Vec<String>,
+}
+
+/// `[tools]` in a Stoffel.toml or ~/.stoffel/config.toml
+///
+/// Pins executables instead of searching the environment for them. Relative
+/// paths are relative to the file that sets them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ToolsConfig {
+    /// Stoffel-Lang compiler binary, or a checkout of its repository
+    pub compiler: Option<String>,
+}
+
+/// The part of a manifest `project_tools` reads
+#[derive(Deserialize)]
+struct ToolsSection {
+    tools: Option<ToolsConfig>,
+}
+
+/// `[tools]` of the Stoffel.toml in `dir`, if there is one
+///
+/// The rest of the manifest isn't validated, so a project with an unrelated
+/// mistake can still find its compiler to report it.
+pub fn project_tools(dir: &Path) -> Result<Option<ToolsConfig>, String> {
+    let path = dir.join(MANIFEST_NAME);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let section: ToolsSection = toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(section.tools)
+}
+
+/// `[tools]` of the workspace root above `dir`, when `dir` is one of its members
+///
+/// Returns the root directory too, since relative paths are relative to it.
+/// Only the nearest Stoffel.toml with `[workspace]` is considered.
+pub fn workspace_tools(dir: &Path) -> Result<Option<(PathBuf, ToolsConfig)>, String> {
+    let dir = dir.canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", dir.display(), e))?;
+    for root in dir.ancestors().skip(1) {
+        let Some(workspace) = find_workspace(root)? else {
+            continue;
+        };
+        let is_member = workspace.members.iter()
+            .any(|member| root.join(member).canonicalize().is_ok_and(|member| member == dir));
+        if !is_member {
+            return Ok(None);
+        }
+        return Ok(project_tools(root)?.map(|tools| (root.to_path_buf(), tools)));
+    }
+    Ok(None)
+}
+
+/// A workspace root and the projects it contains
+#[derive(Debug)]
+pub struct Workspace {
+    /// Member directories relative to the root, in declaration order; `.` when the root is a project too
+    pub members: Vec<PathBuf>,
+}
+
+/// The parts of a root manifest that decide whether it's a workspace
+///
+/// A root may be virtual, with `[workspace]` and nothing else, so this
+/// doesn't require `[package]` like a project manifest does.
+#[derive(Deserialize)]
+struct WorkspaceRoot {
+    package: Option<toml::Value>,
+    workspace: Option<WorkspaceConfig>,
+}
+
+/// Detect a workspace rooted at `dir` and resolve its members
+///
+/// Returns `None` when `dir` has no Stoffel.toml or it doesn't declare `[workspace]`.
+/// Every member must contain a Stoffel.toml; glob matches without one are skipped.
+pub fn find_workspace(dir: &Path) -> Result<Option<Workspace>, String> {
+    let path = dir.join(MANIFEST_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let root: WorkspaceRoot = toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    let Some(workspace) = root.workspace else {
+        return Ok(None);
+    };
+
+    let mut members = Vec::new();
+    if root.package.is_some() {
+        members.push(PathBuf::from("."));
+    }
+    for pattern in &workspace.members {
+        for member in expand_member(dir, pattern)? {
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+    }
+    if members.is_empty() {
+        return Err(format!("{}: [workspace] has no members", path.display()));
+    }
+    Ok(Some(Workspace { members }))
+}
+
+/// Member directories named by one `members` entry
+fn expand_member(root: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    if !pattern.contains(['*', '?', '[']) {
+        let member = PathBuf::from(pattern);
+        if !root.join(&member).join(MANIFEST_NAME).is_file() {
+            return Err(format!("Workspace member '{}' has no {}", pattern, MANIFEST_NAME));
+        }
+        return Ok(vec![member]);
+    }
+
+    let full_pattern = root.join(pattern);
+    let matches = glob::glob(&full_pattern.to_string_lossy())
+        .map_err(|e| format!("Invalid workspace member pattern '{}': {}", pattern, e))?;
+    let mut members: Vec<PathBuf> = matches
+        .flatten()
+        .filter(|path| path.join(MANIFEST_NAME).is_file())
+        .filter_map(|path| path.strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect();
+    members.sort();
+    Ok(members)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{value_name, MpcField, MpcProtocol};
+    use clap::ValueEnum;
+
+    fn parse(mpc: &str) -> Result<StoffelConfig, toml::de::Error> {
+        toml::from_str(&format!("[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[mpc]\n{}", mpc))
+    }
+
+    #[test]
+    fn workspace_members_are_discovered_from_names_and_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let project = "[package]\nname = \"member\"\nversion = \"0.1.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n";
+        for member in ["app", "libs/b", "libs/a"] {
+            std::fs::create_dir_all(root.join(member)).unwrap();
+            std::fs::write(root.join(member).join(MANIFEST_NAME), project).unwrap();
+        }
+        std::fs::create_dir_all(root.join("libs/notes")).unwrap();
+
+        assert!(find_workspace(root).unwrap().is_none());
+
+        std::fs::write(root.join(MANIFEST_NAME), "[workspace]\nmembers = [\"app\", \"libs/*\", \"libs/a\"]\n").unwrap();
+        let members = find_workspace(root).unwrap().unwrap().members;
+        assert_eq!(members, [PathBuf::from("app"), PathBuf::from("libs/a"), PathBuf::from("libs/b")]);
+
+        // A root that is also a project is its own first member, and still loads as one
+        std::fs::write(root.join(MANIFEST_NAME), format!("{}\n[workspace]\nmembers = [\"app\"]\n", project)).unwrap();
+        assert_eq!(find_workspace(root).unwrap().unwrap().members, [PathBuf::from("."), PathBuf::from("app")]);
+        assert!(load_project(root).is_ok());
+
+        std::fs::write(root.join(MANIFEST_NAME), "[workspace]\nmembers = [\"missing\"]\n").unwrap();
+        assert_eq!(find_workspace(root).unwrap_err(), "Workspace member 'missing' has no Stoffel.toml");
+    }
+
+    #[test]
+    fn members_fall_back_to_the_workspace_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join(MANIFEST_NAME), "[workspace]\nmembers = [\"app\"]\n\n[tools]\ncompiler = \"bin/stoffellang\"\n").unwrap();
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::write(root.join("app").join(MANIFEST_NAME), "[package]\nname = \"app\"\n").unwrap();
+        std::fs::create_dir_all(root.join("other")).unwrap();
+
+        let (tools_root, tools) = workspace_tools(&root.join("app")).unwrap().unwrap();
+        assert_eq!(tools_root, root.canonicalize().unwrap());
+        assert_eq!(tools.compiler.as_deref(), Some("bin/stoffellang"));
+        assert!(workspace_tools(&root.join("other")).unwrap().is_none());
+    }
+
+    #[test]
+    fn flags_override_stoffel_toml_which_overrides_defaults() {
+        let manifest = parse("protocol = \"shamir\"\nparties = 7\nfield = \"bn254\"\n").unwrap();
+        let flags = crate::MpcLayer { parties: Some(9), source: crate::ConfigSource::Flag, ..Default::default() };
+        let defaults = crate::MpcLayer {
+            parties: Some(4),
+            field: Some(MpcField::Bls12_381),
+            source: crate::ConfigSource::UserGlobal("config.toml".into()),
+            ..Default::default()
+        };
+
+        let settings = crate::merge_mpc_layers(&[flags, base_layer(&manifest).unwrap(), defaults]).unwrap();
+        assert_eq!(settings.parties, 9);
+        assert_eq!(settings.protocol, MpcProtocol::Shamir);
+        assert_eq!(settings.field, MpcField::Bn254);
+        assert_eq!(settings.threshold, calculate_threshold(9, &MpcProtocol::Shamir).unwrap());
+        assert_eq!(settings.origins.parties, "--parties flag");
+        assert_eq!(settings.origins.field, "Stoffel.toml [mpc] field");
+    }
+
+    #[test]
+    fn threshold_too_large_names_threshold() {
+        let config = parse("protocol = \"honeybadger\"\nparties = 7\nthreshold = 3\nfield = \"bls12-381\"\n").unwrap();
+        let error = validate(&config).unwrap_err();
+        assert!(error.starts_with("mpc.threshold = 3"), "{}", error);
+    }
+
+    #[test]
+    fn too_few_parties_names_parties() {
+        let config = parse("protocol = \"honeybadger\"\nparties = 3\nthreshold = 1\nfield = \"bls12-381\"\n").unwrap();
+        let error = validate(&config).unwrap_err();
+        assert!(error.starts_with("mpc.parties = 3"), "{}", error);
+    }
+
+    #[test]
+    fn each_protocol_calculates_its_largest_valid_threshold() {
+        for (protocol, parties, threshold) in [
+            (MpcProtocol::Honeybadger, 5, 1),
+            (MpcProtocol::Honeybadger, 7, 2),
+            (MpcProtocol::Honeybadger, 10, 3),
+            (MpcProtocol::Shamir, 3, 1),
+            (MpcProtocol::Shamir, 6, 2),
+            (MpcProtocol::Shamir, 7, 3),
+            (MpcProtocol::Bgw, 4, 1),
+            (MpcProtocol::Bgw, 7, 2),
+        ] {
+            assert_eq!(calculate_threshold(parties, &protocol), Ok(threshold), "{:?} with {} parties", protocol, parties);
+            assert!(validate_mpc_params(parties, threshold, &protocol).is_ok());
+            assert!(validate_mpc_params(parties, threshold + 1, &protocol).is_err());
+        }
+    }
+
+    #[test]
+    fn each_protocol_enforces_its_minimum_party_count() {
+        for (protocol, min_parties) in [(MpcProtocol::Honeybadger, 5), (MpcProtocol::Shamir, 3), (MpcProtocol::Bgw, 4)] {
+            assert!(validate_mpc_params(min_parties, 1, &protocol).is_ok());
+            let error = validate_mpc_params(min_parties - 1, 1, &protocol).unwrap_err();
+            assert!(error.contains(&format!("at least {} parties", min_parties)), "{}", error);
+            // No threshold is calculated for a party count the protocol can't run with
+            assert_eq!(calculate_threshold(min_parties - 1, &protocol), Err(error));
+        }
+
+        let config = parse("protocol = \"shamir\"\nparties = 3\nthreshold = 1\nfield = \"bls12-381\"\n").unwrap();
+        assert_eq!(validate(&config), Ok(()));
+        let config = parse("protocol = \"shamir\"\nparties = 2\nfield = \"bls12-381\"\n").unwrap();
+        assert!(validate(&config).unwrap_err().starts_with("mpc.parties = 2"));
+    }
+
+    #[test]
+    fn threshold_errors_quote_the_protocol_and_its_bound() {
+        let spec = MpcProtocol::Honeybadger.spec();
+        assert_eq!(spec.max_threshold(7), 2);
+        assert!(validate_mpc_params(7, 2, &MpcProtocol::Honeybadger).is_ok());
+
+        let error = validate_mpc_params(7, 3, &MpcProtocol::Honeybadger).unwrap_err();
+        assert_eq!(error, "HoneyBadger protocol requires threshold t < n/3. For 7 parties, max threshold is 2 (got 3)");
+        let error = validate_mpc_params(2, 1, &MpcProtocol::Shamir).unwrap_err();
+        assert_eq!(error, "Shamir protocol requires at least 3 parties, got 2");
+    }
+
+    #[test]
+    fn protocol_names_round_trip_through_the_manifest() {
+        for protocol in MpcProtocol::value_variants() {
+            let name = value_name(protocol);
+            assert_eq!(MpcProtocol::from_str(&name, true).as_ref(), Ok(protocol));
+        }
+    }
+
+    #[test]
+    fn field_names_round_trip_through_the_field_table() {
+        for field in MpcField::value_variants() {
+            let spec = field.spec();
+            assert_eq!(value_name(field), spec.name);
+            assert_eq!(MpcField::from_str(spec.name, true).as_ref(), Ok(field));
+            assert!(spec.modulus.starts_with("0x") && spec.bits > 0);
+        }
+        assert_eq!(MpcField::Prime61.spec().security_bits, None);
+    }
+
+    #[test]
+    fn add_dependency_keeps_mpc_and_rejects_duplicates() {
+        let mut config = parse("protocol = \"honeybadger\"\nparties = 7\nthreshold = 2\nfield = \"bn254\"\n").unwrap();
+        add_dependency(&mut config, "stdlib", "1.0", false).unwrap();
+        add_dependency(&mut config, "testkit", "*", true).unwrap();
+
+        let reloaded: StoffelConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reloaded.mpc.parties, 7);
+        assert_eq!(reloaded.mpc.field, "bn254");
+        assert_eq!(reloaded.dependencies.unwrap()["stdlib"], "1.0");
+        assert_eq!(reloaded.dev_dependencies.unwrap()["testkit"], "*");
+
+        let error = add_dependency(&mut config, "stdlib", "2.0", true).unwrap_err();
+        assert!(error.contains("stoffel update stdlib"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_key_is_rejected() {
+        let error = parse("protocol = \"honeybadger\"\npartys = 5\nfield = \"bls12-381\"\n").unwrap_err();
+        assert!(error.to_string().contains("unknown field `partys`"), "{}", error);
+    }
+
+    #[test]
+    fn package_entry_is_optional() {
+        let config: StoffelConfig = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nentry = \"src/app.stfl\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.package.entry.as_deref(), Some("src/app.stfl"));
+        assert_eq!(parse("protocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n").unwrap().package.entry, None);
+    }
+}
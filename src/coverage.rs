@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Where `stoffel test --coverage-format lcov` writes the merged tracefile, under target/
+pub const LCOV_PATH: &str = "coverage/lcov.info";
+
+/// Proc and line execution counts collected from instrumented test runs, per source file
+#[derive(Debug, Default, PartialEq)]
+pub struct Coverage {
+    files: BTreeMap<String, FileCoverage>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct FileCoverage {
+    /// Proc name to the line it starts on and how often it was called
+    procs: BTreeMap<String, (u32, u64)>,
+    /// Line number to how often it was executed
+    lines: BTreeMap<u32, u64>,
+}
+
+/// Totals for the coverage summary
+#[derive(Debug, PartialEq)]
+pub struct Summary {
+    pub procs: usize,
+    pub procs_hit: usize,
+    pub lines: usize,
+    pub lines_hit: usize,
+    /// `file: proc` for every proc no test called
+    pub uncovered: Vec<String>,
+}
+
+impl Summary {
+    /// Percentage of `hit` out of `total`; nothing to cover counts as fully covered
+    pub fn percent(hit: usize, total: usize) -> f64 {
+        if total == 0 {
+            100.0
+        } else {
+            hit as f64 * 100.0 / total as f64
+        }
+    }
+}
+
+impl Coverage {
+    /// Add the counts from an lcov tracefile written by the VM
+    ///
+    /// Only the records that carry counts are read (`SF`, `FN`, `FNDA`, `DA`
+    /// and `end_of_record`); totals such as `LF`/`LH` are recomputed when writing.
+    /// Counts for a proc or line seen before are summed.
+    pub fn merge_lcov(&mut self, tracefile: &str) -> Result<(), String> {
+        let mut current: Option<String> = None;
+        for (number, line) in tracefile.lines().enumerate() {
+            let line = line.trim();
+            let invalid = || format!("line {}: invalid lcov record '{}'", number + 1, line);
+            if line == "end_of_record" {
+                current = None;
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            if key == "SF" {
+                current = Some(value.to_string());
+                continue;
+            }
+
+            let Some(file) = current.as_ref() else {
+                if matches!(key, "FN" | "FNDA" | "DA") {
+                    return Err(format!("line {}: '{}' outside of an SF record", number + 1, key));
+                }
+                continue;
+            };
+            let file = self.files.entry(file.clone()).or_default();
+            match key {
+                "FN" => {
+                    let (start, name) = value.split_once(',').ok_or_else(invalid)?;
+                    let start = start.parse().map_err(|_| invalid())?;
+                    file.procs.entry(name.to_string()).or_insert((start, 0)).0 = start;
+                }
+                "FNDA" => {
+                    let (hits, name) = value.split_once(',').ok_or_else(invalid)?;
+                    let hits: u64 = hits.parse().map_err(|_| invalid())?;
+                    file.procs.entry(name.to_string()).or_insert((0, 0)).1 += hits;
+                }
+                "DA" => {
+                    let mut fields = value.split(',');
+                    let line = fields.next().and_then(|line| line.parse().ok()).ok_or_else(invalid)?;
+                    let hits: u64 = fields.next().and_then(|hits| hits.parse().ok()).ok_or_else(invalid)?;
+                    *file.lines.entry(line).or_insert(0) += hits;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep only the source files `keep` accepts, e.g. to leave the tests themselves out
+    pub fn retain_files(&mut self, keep: impl Fn(&str) -> bool) {
+        self.files.retain(|file, _| keep(file));
+    }
+
+    pub fn summary(&self) -> Summary {
+        let mut summary = Summary { procs: 0, procs_hit: 0, lines: 0, lines_hit: 0, uncovered: Vec::new() };
+        for (name, file) in &self.files {
+            summary.procs += file.procs.len();
+            summary.lines += file.lines.len();
+            summary.lines_hit += file.lines.values().filter(|hits| **hits > 0).count();
+            for (proc_name, (_, hits)) in &file.procs {
+                if *hits > 0 {
+                    summary.procs_hit += 1;
+                } else {
+                    summary.uncovered.push(format!("{}: {}", name, proc_name));
+                }
+            }
+        }
+        summary
+    }
+
+    /// Render the merged counts as an lcov tracefile
+    pub fn to_lcov(&self) -> String {
+        let mut lcov = String::new();
+        for (name, file) in &self.files {
+            let _ = writeln!(lcov, "SF:{}", name);
+            let mut procs: Vec<_> = file.procs.iter().collect();
+            procs.sort_by_key(|(proc_name, (start, _))| (*start, *proc_name));
+            for (proc_name, (start, _)) in &procs {
+                let _ = writeln!(lcov, "FN:{},{}", start, proc_name);
+            }
+            for (proc_name, (_, hits)) in &procs {
+                let _ = writeln!(lcov, "FNDA:{},{}", hits, proc_name);
+            }
+            let _ = writeln!(lcov, "FNF:{}", procs.len());
+            let _ = writeln!(lcov, "FNH:{}", procs.iter().filter(|(_, (_, hits))| *hits > 0).count());
+            for (line, hits) in &file.lines {
+                let _ = writeln!(lcov, "DA:{},{}", line, hits);
+            }
+            let _ = writeln!(lcov, "LF:{}", file.lines.len());
+            let _ = writeln!(lcov, "LH:{}", file.lines.values().filter(|hits| **hits > 0).count());
+            lcov.push_str("end_of_record\n");
+        }
+        lcov
+    }
+
+    pub fn write_lcov(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(path, self.to_lcov())
+            .map_err(|e| format!("Failed to write lcov tracefile {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUN: &str = "TN:\nSF:src/math.stfl\nFN:1,add\nFN:5,sub\nFNDA:2,add\nFNDA:0,sub\nFNF:2\nFNH:1\nDA:2,2\nDA:6,0\nLF:2\nLH:1\nend_of_record\n";
+
+    #[test]
+    fn runs_are_merged_and_summarized() {
+        let mut coverage = Coverage::default();
+        coverage.merge_lcov(RUN).unwrap();
+        let summary = coverage.summary();
+        assert_eq!((summary.procs_hit, summary.procs, summary.lines_hit, summary.lines), (1, 2, 1, 2));
+        assert_eq!(summary.uncovered, ["src/math.stfl: sub"]);
+
+        coverage.merge_lcov("SF:src/math.stfl\nFNDA:1,sub\nDA:6,1\nend_of_record\nSF:tests/math.stfl\nDA:1,1\nend_of_record\n").unwrap();
+        coverage.retain_files(|file| !file.starts_with("tests/"));
+        let summary = coverage.summary();
+        assert_eq!((summary.procs_hit, summary.lines_hit), (2, 2));
+        assert!(summary.uncovered.is_empty());
+        assert_eq!(Summary::percent(1, 4), 25.0);
+    }
+
+    #[test]
+    fn lcov_output_round_trips() {
+        let mut coverage = Coverage::default();
+        coverage.merge_lcov(RUN).unwrap();
+        let lcov = coverage.to_lcov();
+        assert_eq!(lcov, RUN.trim_start_matches("TN:\n"));
+
+        let mut reloaded = Coverage::default();
+        reloaded.merge_lcov(&lcov).unwrap();
+        assert_eq!(reloaded, coverage);
+    }
+
+    #[test]
+    fn malformed_records_are_rejected() {
+        let mut coverage = Coverage::default();
+        assert_eq!(coverage.merge_lcov("DA:1,1\n").unwrap_err(), "line 1: 'DA' outside of an SF record");
+        assert!(coverage.merge_lcov("SF:a.stfl\nDA:x,1\n").unwrap_err().starts_with("line 2: invalid lcov record"));
+    }
+}
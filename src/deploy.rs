@@ -0,0 +1,511 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Directory generated deployment files are written to by default
+pub const DEPLOY_DIR: &str = "deploy";
+
+/// Default file name of the Kubernetes manifest inside `DEPLOY_DIR`
+pub const K8S_MANIFEST_NAME: &str = "k8s.yaml";
+
+/// Port every party listens on inside its pod or container
+pub const PARTY_PORT: u16 = 9001;
+
+/// Where the compiled program lives inside the deployment image
+pub const PROGRAM_PATH: &str = "/app/main.bin";
+
+/// Everything the deployment generators need to know about the project
+pub struct Deployment {
+    /// Package name from Stoffel.toml
+    pub name: String,
+    /// Package version from Stoffel.toml
+    pub version: String,
+    pub parties: u8,
+    pub protocol: String,
+    pub field: String,
+    pub threshold: u8,
+}
+
+impl Deployment {
+    /// The package name made safe for Kubernetes resource and DNS names
+    pub fn resource_name(&self) -> String {
+        let name: String = self
+            .name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let name = name.trim_matches('-');
+        if name.is_empty() {
+            "stoffel".to_string()
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Image the deployment expects the compiled program to be packaged in
+    pub fn image(&self) -> String {
+        format!("{}:{}", self.resource_name(), self.version)
+    }
+}
+
+/// Render a Kubernetes manifest running one pod per party
+///
+/// Parties run in a StatefulSet behind a headless Service, so party `i`
+/// is reachable at `<name>-<i>.<name>-parties`. Each pod reads its index
+/// from the StatefulSet pod-index label into `STOFFEL_PARTY_ID`, and the
+/// MPC settings come from a shared ConfigMap.
+pub fn kubernetes(deployment: &Deployment) -> String {
+    let name = deployment.resource_name();
+    let service = format!("{}-parties", name);
+    let peers: Vec<String> = (0..deployment.parties)
+        .map(|i| format!("{}={}-{}.{}:{}", i, name, i, service, PARTY_PORT))
+        .collect();
+
+    let mut yaml = String::new();
+    let _ = write!(
+        yaml,
+        r#"# Generated by `stoffel deploy --k8s`
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: {name}-mpc
+  labels:
+    app: {name}
+data:
+  STOFFEL_PARTIES: "{parties}"
+  STOFFEL_PROTOCOL: "{protocol}"
+  STOFFEL_FIELD: "{field}"
+  STOFFEL_THRESHOLD: "{threshold}"
+  STOFFEL_PEERS: "{peers}"
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: {service}
+  labels:
+    app: {name}
+spec:
+  clusterIP: None
+  selector:
+    app: {name}
+  ports:
+    - name: mpc
+      port: {port}
+---
+apiVersion: apps/v1
+kind: StatefulSet
+metadata:
+  name: {name}
+  labels:
+    app: {name}
+spec:
+  serviceName: {service}
+  replicas: {parties}
+  podManagementPolicy: Parallel
+  selector:
+    matchLabels:
+      app: {name}
+  template:
+    metadata:
+      labels:
+        app: {name}
+    spec:
+      containers:
+        - name: party
+          image: {image}
+          envFrom:
+            - configMapRef:
+                name: {name}-mpc
+          env:
+            - name: STOFFEL_PARTY_ID
+              valueFrom:
+                fieldRef:
+                  fieldPath: metadata.labels['apps.kubernetes.io/pod-index']
+          command: ["stoffel-vm", "{program}"]
+          args:
+            - "--party-id"
+            - "$(STOFFEL_PARTY_ID)"
+            - "--parties"
+            - "$(STOFFEL_PARTIES)"
+            - "--threshold"
+            - "$(STOFFEL_THRESHOLD)"
+            - "--protocol"
+            - "$(STOFFEL_PROTOCOL)"
+            - "--field"
+            - "$(STOFFEL_FIELD)"
+"#,
+        name = name,
+        service = service,
+        parties = deployment.parties,
+        protocol = deployment.protocol,
+        field = deployment.field,
+        threshold = deployment.threshold,
+        peers = peers.join(","),
+        port = PARTY_PORT,
+        image = deployment.image(),
+        program = PROGRAM_PATH,
+    );
+    for peer in &peers {
+        let _ = writeln!(yaml, "            - \"--party\"\n            - \"{}\"", peer);
+    }
+    let _ = write!(
+        yaml,
+        r#"          ports:
+            - name: mpc
+              containerPort: {}
+"#,
+        PARTY_PORT
+    );
+    yaml
+}
+
+/// File names of the local Docker deployment inside the output directory
+pub const DOCKERFILE_NAME: &str = "Dockerfile";
+pub const COMPOSE_NAME: &str = "docker-compose.yml";
+
+/// Render a Dockerfile packaging the compiled program on top of the VM image
+///
+/// `artifact` is relative to the project root, which is the build context.
+pub fn dockerfile(artifact: &str) -> String {
+    format!(
+        r#"# Generated by `stoffel deploy`
+ARG VM_IMAGE=stoffel-vm:latest
+FROM ${{VM_IMAGE}}
+COPY {artifact} {program}
+EXPOSE {port}
+ENTRYPOINT ["stoffel-vm", "{program}"]
+"#,
+        artifact = artifact,
+        program = PROGRAM_PATH,
+        port = PARTY_PORT,
+    )
+}
+
+/// Render a compose file running one container per party on a private network
+///
+/// `context` is the project root relative to the compose file, `dockerfile`
+/// is relative to that root, and party `i` is published on `host_ports[i]`.
+pub fn compose(deployment: &Deployment, context: &str, dockerfile: &str, host_ports: &[u16]) -> String {
+    let name = deployment.resource_name();
+    let peers: Vec<String> = (0..deployment.parties)
+        .map(|i| format!("{}=party-{}:{}", i, i, PARTY_PORT))
+        .collect();
+
+    let mut yaml = String::from("# Generated by `stoffel deploy`\nservices:\n");
+    for (party, host_port) in host_ports.iter().enumerate() {
+        let _ = write!(
+            yaml,
+            r#"  party-{party}:
+    image: {image}
+    build:
+      context: {context}
+      dockerfile: {dockerfile}
+    command:
+      - "--party-id"
+      - "{party}"
+      - "--parties"
+      - "{parties}"
+      - "--threshold"
+      - "{threshold}"
+      - "--protocol"
+      - "{protocol}"
+      - "--field"
+      - "{field}"
+"#,
+            party = party,
+            image = deployment.image(),
+            context = context,
+            dockerfile = dockerfile,
+            parties = deployment.parties,
+            threshold = deployment.threshold,
+            protocol = deployment.protocol,
+            field = deployment.field,
+        );
+        for peer in &peers {
+            let _ = writeln!(yaml, "      - \"--party\"\n      - \"{}\"", peer);
+        }
+        let _ = write!(
+            yaml,
+            r#"    ports:
+      - "{}:{}"
+    networks:
+      - {}
+"#,
+            host_port, PARTY_PORT, name
+        );
+    }
+    let _ = write!(yaml, "networks:\n  {}:\n    driver: bridge\n", name);
+    yaml
+}
+
+/// The project root as seen from a file generated into `out_dir`
+///
+/// Relative output directories give a relative path, so the generated files
+/// keep working when the project is checked out elsewhere.
+pub fn project_context(out_dir: &Path) -> Result<String, String> {
+    let mut depth = 0;
+    for component in out_dir.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            _ => {
+                let root = std::env::current_dir()
+                    .map_err(|e| format!("Failed to resolve current directory: {}", e))?;
+                return Ok(root.display().to_string());
+            }
+        }
+    }
+
+    Ok(if depth == 0 { ".".to_string() } else { vec![".."; depth].join("/") })
+}
+
+/// File names inside a TEE deployment bundle
+pub const TEE_PROGRAM_NAME: &str = "main.bin";
+pub const TEE_MANIFEST_NAME: &str = "manifest.json";
+pub const TEE_README_NAME: &str = "README.md";
+
+/// Description of a TEE bundle that attestation is checked against
+#[derive(Serialize, Debug)]
+struct TeeManifest<'a> {
+    package: &'a str,
+    version: &'a str,
+    program: TeeProgram,
+    mpc: TeeMpc<'a>,
+    enclave: TeeEnclave,
+}
+
+#[derive(Serialize, Debug)]
+struct TeeProgram {
+    file: &'static str,
+    sha256: String,
+}
+
+#[derive(Serialize, Debug)]
+struct TeeMpc<'a> {
+    protocol: &'a str,
+    field: &'a str,
+    parties: u8,
+    threshold: u8,
+}
+
+/// Enclave settings and the measurements each party's enclave must report
+///
+/// `mrenclave` and `mrsigner` are only known once the enclave is built and
+/// signed, so they are left empty for the operator to fill in.
+#[derive(Serialize, Debug)]
+struct TeeEnclave {
+    platform: &'static str,
+    debug: bool,
+    mrenclave: Option<String>,
+    mrsigner: Option<String>,
+    party_ids: Vec<u8>,
+}
+
+/// Directory name of the TEE bundle for a deployment
+pub fn tee_bundle_name(deployment: &Deployment) -> String {
+    format!("{}-{}-tee", deployment.resource_name(), deployment.version)
+}
+
+/// Assemble a TEE deployment bundle from a compiled program
+///
+/// The bundle holds a copy of the program, a manifest with its hash and the
+/// MPC configuration, and a README walking through attestation. An existing
+/// bundle is only replaced with `force`.
+pub fn write_tee_bundle(deployment: &Deployment, program: &Path, out_dir: &Path, force: bool) -> Result<PathBuf, String> {
+    let program_bytes = fs::read(program).map_err(|e| {
+        format!("Failed to read {}: {}. Run `stoffel build` before packaging a TEE bundle.", program.display(), e)
+    })?;
+
+    let bundle = out_dir.join(tee_bundle_name(deployment));
+    if bundle.exists() {
+        if !force {
+            return Err(format!("{} already exists. Pass --force to replace it.", bundle.display()));
+        }
+        fs::remove_dir_all(&bundle).map_err(|e| format!("Failed to remove {}: {}", bundle.display(), e))?;
+    }
+    fs::create_dir_all(&bundle).map_err(|e| format!("Failed to create {}: {}", bundle.display(), e))?;
+
+    let sha256: String = Sha256::digest(&program_bytes).iter().map(|byte| format!("{:02x}", byte)).collect();
+    let manifest = TeeManifest {
+        package: &deployment.name,
+        version: &deployment.version,
+        program: TeeProgram { file: TEE_PROGRAM_NAME, sha256 },
+        mpc: TeeMpc {
+            protocol: &deployment.protocol,
+            field: &deployment.field,
+            parties: deployment.parties,
+            threshold: deployment.threshold,
+        },
+        enclave: TeeEnclave {
+            platform: "sgx",
+            debug: false,
+            mrenclave: None,
+            mrsigner: None,
+            party_ids: (0..deployment.parties).collect(),
+        },
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize TEE manifest: {}", e))?;
+
+    let program_copy = bundle.join(TEE_PROGRAM_NAME);
+    fs::write(&program_copy, &program_bytes)
+        .map_err(|e| format!("Failed to write {}: {}", program_copy.display(), e))?;
+    write_file(&bundle.join(TEE_MANIFEST_NAME), &(manifest_json + "\n"))?;
+    write_file(&bundle.join(TEE_README_NAME), &tee_readme(deployment, &manifest.program.sha256))?;
+    Ok(bundle)
+}
+
+fn tee_readme(deployment: &Deployment, sha256: &str) -> String {
+    format!(
+        r#"# {name} {version} — TEE deployment bundle
+
+Generated by `stoffel deploy --tee`.
+
+| File | Contents |
+|------|----------|
+| `{program}` | Compiled StoffelLang program |
+| `{manifest}` | Program hash, MPC configuration and expected enclave measurements |
+
+The network runs {parties} parties using {protocol} over {field} with threshold {threshold}.
+Every party must run inside an enclave before it is admitted to the network.
+
+## Attestation
+
+1. Check that `{program}` hashes to the value recorded in `{manifest}`:
+
+       sha256sum {program}   # expect {sha256}
+
+2. Build and sign the enclave that loads `{program}`, then record its
+   `mrenclave` and `mrsigner` in the `enclave` section of `{manifest}`.
+3. Start each party (ids 0 to {last}) in its enclave and collect its attestation quote.
+4. Verify every quote with your platform's attestation service and compare the
+   reported measurements against `{manifest}`.
+5. Only admit parties whose measurements match; reject the rest.
+"#,
+        name = deployment.name,
+        version = deployment.version,
+        program = TEE_PROGRAM_NAME,
+        manifest = TEE_MANIFEST_NAME,
+        parties = deployment.parties,
+        protocol = deployment.protocol,
+        field = deployment.field,
+        threshold = deployment.threshold,
+        last = deployment.parties.saturating_sub(1),
+        sha256 = sha256,
+    )
+}
+
+/// Write a generated file, refusing to replace an existing one unless `force`
+///
+/// Returns whether the file was written, so callers can report skipped files.
+pub fn write_generated(path: &Path, content: &str, force: bool) -> Result<bool, String> {
+    if path.exists() && !force {
+        return Ok(false);
+    }
+    write_file(path, content)?;
+    Ok(true)
+}
+
+/// Write a generated file, creating its parent directory
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kubernetes_manifest_has_a_pod_per_party() {
+        let deployment = Deployment {
+            name: "My_App".to_string(),
+            version: "0.1.0".to_string(),
+            parties: 5,
+            protocol: "honeybadger".to_string(),
+            field: "bls12-381".to_string(),
+            threshold: 1,
+        };
+        let yaml = kubernetes(&deployment);
+
+        assert!(yaml.contains("kind: StatefulSet"));
+        assert!(yaml.contains("replicas: 5"));
+        assert!(yaml.contains("clusterIP: None"));
+        assert!(yaml.contains("image: my-app:0.1.0"));
+        assert!(yaml.contains("STOFFEL_THRESHOLD: \"1\""));
+        assert!(yaml.contains("\"4=my-app-4.my-app-parties:9001\""));
+        assert!(!yaml.contains("my-app-5."));
+    }
+
+    #[test]
+    fn compose_publishes_each_party_on_its_own_port() {
+        let deployment = Deployment {
+            name: "demo".to_string(),
+            version: "1.0.0".to_string(),
+            parties: 2,
+            protocol: "honeybadger".to_string(),
+            field: "bls12-381".to_string(),
+            threshold: 0,
+        };
+        let yaml = compose(&deployment, "..", "deploy/Dockerfile", &[9001, 9002]);
+
+        assert!(yaml.contains("  party-0:\n"));
+        assert!(yaml.contains("  party-1:\n"));
+        assert!(yaml.contains("\"9002:9001\""));
+        assert!(yaml.contains("context: ..\n      dockerfile: deploy/Dockerfile\n"));
+        assert!(yaml.contains("\"1=party-1:9001\""));
+    }
+
+    #[test]
+    fn context_climbs_out_of_relative_output_dirs() {
+        assert_eq!(project_context(Path::new("deploy")).unwrap(), "..");
+        assert_eq!(project_context(Path::new("./ops/local")).unwrap(), "../..");
+        assert_eq!(project_context(Path::new(".")).unwrap(), ".");
+    }
+
+    #[test]
+    fn tee_bundle_is_named_after_the_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("main.bin");
+        fs::write(&program, b"program").unwrap();
+        let deployment = Deployment {
+            name: "demo".to_string(),
+            version: "0.2.0".to_string(),
+            parties: 5,
+            protocol: "honeybadger".to_string(),
+            field: "bls12-381".to_string(),
+            threshold: 1,
+        };
+
+        let bundle = write_tee_bundle(&deployment, &program, dir.path(), false).unwrap();
+        assert_eq!(bundle, dir.path().join("demo-0.2.0-tee"));
+        assert_eq!(fs::read(bundle.join(TEE_PROGRAM_NAME)).unwrap(), b"program");
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(bundle.join(TEE_MANIFEST_NAME)).unwrap()).unwrap();
+        assert_eq!(manifest["mpc"]["parties"], 5);
+        assert_eq!(manifest["program"]["sha256"].as_str().unwrap().len(), 64);
+
+        assert!(write_tee_bundle(&deployment, &program, dir.path(), false).is_err());
+        assert!(write_tee_bundle(&deployment, &program, dir.path(), true).is_ok());
+    }
+
+    #[test]
+    fn generated_files_are_kept_without_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Dockerfile");
+        fs::write(&path, "hand edited").unwrap();
+
+        assert!(!write_generated(&path, "generated", false).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hand edited");
+        assert!(write_generated(&path, "generated", true).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "generated");
+    }
+}
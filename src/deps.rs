@@ -0,0 +1,279 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::init::StoffelConfig;
+use crate::lockfile::{LockChange, Lockfile, LOCKFILE_NAME};
+
+/// A project's declared dependencies and their locked versions, edited together
+///
+/// Everything else in Stoffel.toml is carried through a rewrite unchanged.
+pub struct Dependencies {
+    dir: PathBuf,
+    pub manifest: StoffelConfig,
+    pub lock: Lockfile,
+}
+
+/// A single change made while adding or updating dependencies
+#[derive(Debug, PartialEq)]
+pub enum DependencyChange {
+    Locked { name: String, version: String },
+    Updated { name: String, from: String, to: String },
+    /// A `"*"` constraint in Stoffel.toml was replaced by the locked version
+    Pinned { name: String, version: String },
+    Removed { name: String, version: String },
+}
+
+impl fmt::Display for DependencyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyChange::Locked { name, version } => write!(f, "Locked {} {}", name, version),
+            DependencyChange::Updated { name, from, to } => write!(f, "Updated {}: {} -> {}", name, from, to),
+            DependencyChange::Pinned { name, version } => write!(f, "Pinned {} to {} in {}", name, version, config::MANIFEST_NAME),
+            DependencyChange::Removed { name, version } => write!(f, "Removed {} {}", name, version),
+        }
+    }
+}
+
+impl Dependencies {
+    /// Load Stoffel.toml and Stoffel.lock from a project directory
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        Ok(Dependencies {
+            dir: dir.to_path_buf(),
+            manifest: config::load(&dir.join(config::MANIFEST_NAME))?,
+            lock: Lockfile::load(&dir.join(LOCKFILE_NAME))?,
+        })
+    }
+
+    /// Write both files back
+    pub fn save(&self) -> Result<(), String> {
+        config::save(&self.dir.join(config::MANIFEST_NAME), &self.manifest)?;
+        self.lock.save(&self.dir.join(LOCKFILE_NAME))
+    }
+
+    /// Declare a new dependency and lock its resolved version
+    pub fn add(&mut self, name: &str, version: &str, dev: bool) -> Result<(), String> {
+        let resolved = resolve(&self.dir, version)?;
+        config::add_dependency(&mut self.manifest, name, version, dev)?;
+        self.lock.lock(name, &resolved);
+        Ok(())
+    }
+
+    /// Remove a dependency from its table and the lockfile, returning its declared version
+    ///
+    /// A table left empty is dropped so Stoffel.toml doesn't keep a bare header.
+    pub fn remove(&mut self, name: &str, dev: bool) -> Result<String, String> {
+        let (table, other) = if dev {
+            ("dev_dependencies", "dependencies")
+        } else {
+            ("dependencies", "dev_dependencies")
+        };
+        let (dependencies, others) = if dev {
+            (&mut self.manifest.dev_dependencies, &self.manifest.dependencies)
+        } else {
+            (&mut self.manifest.dependencies, &self.manifest.dev_dependencies)
+        };
+
+        let Some(version) = dependencies.as_mut().and_then(|deps| deps.remove(name)) else {
+            if others.as_ref().is_some_and(|deps| deps.contains_key(name)) {
+                let flag = if dev { "without" } else { "with" };
+                return Err(format!(
+                    "'{}' is not in [{}] but is in [{}]. Run the command {} --dev.",
+                    name, table, other, flag
+                ));
+            }
+            return Err(format!("'{}' is not in [{}] of {}", name, table, config::MANIFEST_NAME));
+        };
+
+        if dependencies.as_ref().is_some_and(|deps| deps.is_empty()) {
+            *dependencies = None;
+        }
+        self.lock.remove(name);
+        Ok(version)
+    }
+
+    /// Re-resolve one dependency, or all of them, and sync the lockfile
+    ///
+    /// Path dependencies lock the version from their own Stoffel.toml, and
+    /// `"*"` constraints are pinned to an already locked concrete version.
+    /// Updating everything also drops lock entries nobody declares anymore.
+    pub fn update(&mut self, package: Option<&str>) -> Result<Vec<DependencyChange>, String> {
+        let mut changes = Vec::new();
+
+        if let Some(package) = package {
+            if !self.is_declared(package) {
+                return Err(format!("Package '{}' is not a dependency in {}", package, config::MANIFEST_NAME));
+            }
+        }
+
+        let dir = self.dir.clone();
+        let lock = &mut self.lock;
+        let tables = [&mut self.manifest.dependencies, &mut self.manifest.dev_dependencies];
+        for (name, spec) in tables.into_iter().flatten().flat_map(|table| table.iter_mut()) {
+            if package.is_some_and(|package| package != name) {
+                continue;
+            }
+
+            let locked = lock.packages.iter().find(|locked| &locked.name == name).map(|locked| locked.version.clone());
+            if spec == "*" {
+                if let Some(version) = locked.filter(|version| version != "*") {
+                    *spec = version.clone();
+                    changes.push(DependencyChange::Pinned { name: name.clone(), version });
+                }
+            }
+
+            let resolved = resolve(&dir, spec)?;
+            match lock.lock(name, &resolved) {
+                LockChange::Added => changes.push(DependencyChange::Locked { name: name.clone(), version: resolved }),
+                LockChange::Updated(from) => changes.push(DependencyChange::Updated { name: name.clone(), from, to: resolved }),
+                LockChange::Unchanged => {}
+            }
+        }
+
+        if package.is_none() {
+            let manifest = &self.manifest;
+            let is_declared = |name: &str| {
+                [&manifest.dependencies, &manifest.dev_dependencies]
+                    .into_iter()
+                    .flatten()
+                    .any(|table| table.contains_key(name))
+            };
+            for removed in self.lock.retain_declared(is_declared) {
+                changes.push(DependencyChange::Removed { name: removed.name, version: removed.version });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    fn is_declared(&self, name: &str) -> bool {
+        [&self.manifest.dependencies, &self.manifest.dev_dependencies]
+            .into_iter()
+            .flatten()
+            .any(|table| table.contains_key(name))
+    }
+}
+
+/// Whether a dependency spec refers to a local directory rather than a version
+pub fn is_path_dependency(spec: &str) -> bool {
+    spec.starts_with('.') || spec.starts_with('/') || spec.contains('/') || spec.contains('\\')
+}
+
+/// The concrete version to lock for a dependency spec
+///
+/// Path dependencies resolve to the version in their own Stoffel.toml; any
+/// other spec is locked as written until a registry exists.
+fn resolve(dir: &Path, spec: &str) -> Result<String, String> {
+    if !is_path_dependency(spec) {
+        return Ok(spec.to_string());
+    }
+
+    let manifest = config::load(&dir.join(spec).join(config::MANIFEST_NAME))?;
+    Ok(manifest.package.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const MANIFEST: &str = r#"[package]
+name = "demo"
+version = "0.1.0"
+description = "Keeps its metadata"
+license = "MIT"
+
+[mpc]
+protocol = "honeybadger"
+parties = 7
+threshold = 2
+field = "bn254"
+
+[mpc.profiles.ci]
+parties = 10
+
+[dependencies]
+stdlib = "*"
+local = "./local"
+
+[dev_dependencies]
+testkit = "0.3"
+"#;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(config::MANIFEST_NAME), MANIFEST).unwrap();
+        fs::create_dir(dir.path().join("local")).unwrap();
+        fs::write(
+            dir.path().join("local").join(config::MANIFEST_NAME),
+            "[package]\nname = \"local\"\nversion = \"1.4.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn update_keeps_unrelated_sections() {
+        let dir = project();
+        let mut deps = Dependencies::load(dir.path()).unwrap();
+        deps.update(None).unwrap();
+        deps.save().unwrap();
+
+        let manifest = config::load(&dir.path().join(config::MANIFEST_NAME)).unwrap();
+        assert_eq!(manifest.package.description.as_deref(), Some("Keeps its metadata"));
+        assert_eq!(manifest.package.license.as_deref(), Some("MIT"));
+        assert_eq!(manifest.mpc.parties, 7);
+        assert_eq!(manifest.mpc.threshold, Some(2));
+        assert_eq!(manifest.mpc.field, "bn254");
+        assert_eq!(manifest.mpc.profiles.unwrap()["ci"].parties, Some(10));
+        assert_eq!(manifest.dev_dependencies.unwrap()["testkit"], "0.3");
+    }
+
+    #[test]
+    fn update_resolves_path_dependencies_and_pins_wildcards() {
+        let dir = project();
+        fs::write(
+            dir.path().join(LOCKFILE_NAME),
+            "version = 1\n\n[[package]]\nname = \"stdlib\"\nversion = \"2.1.0\"\n\n[[package]]\nname = \"stale\"\nversion = \"0.1\"\n",
+        )
+        .unwrap();
+
+        let mut deps = Dependencies::load(dir.path()).unwrap();
+        let changes = deps.update(None).unwrap();
+        deps.save().unwrap();
+
+        assert!(changes.contains(&DependencyChange::Pinned { name: "stdlib".to_string(), version: "2.1.0".to_string() }));
+        assert!(changes.contains(&DependencyChange::Locked { name: "local".to_string(), version: "1.4.0".to_string() }));
+        assert!(changes.contains(&DependencyChange::Removed { name: "stale".to_string(), version: "0.1".to_string() }));
+
+        let reloaded = Dependencies::load(dir.path()).unwrap();
+        assert_eq!(reloaded.manifest.dependencies.as_ref().unwrap()["stdlib"], "2.1.0");
+        assert_eq!(reloaded.manifest.dependencies.as_ref().unwrap()["local"], "./local");
+        let names: Vec<&str> = reloaded.lock.packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["local", "stdlib", "testkit"]);
+    }
+
+    #[test]
+    fn remove_last_dependency_drops_table() {
+        let dir = project();
+        let mut deps = Dependencies::load(dir.path()).unwrap();
+        deps.update(None).unwrap();
+        assert_eq!(deps.remove("testkit", true).unwrap(), "0.3");
+        assert!(deps.remove("stdlib", true).is_err());
+        deps.save().unwrap();
+
+        let content = fs::read_to_string(dir.path().join(config::MANIFEST_NAME)).unwrap();
+        assert!(!content.contains("dev_dependencies"));
+        let reloaded = Dependencies::load(dir.path()).unwrap();
+        assert!(reloaded.manifest.dev_dependencies.is_none());
+        assert!(reloaded.lock.packages.iter().all(|p| p.name != "testkit"));
+        assert_eq!(reloaded.manifest.mpc.parties, 7);
+    }
+
+    #[test]
+    fn update_unknown_package_fails() {
+        let dir = project();
+        let mut deps = Dependencies::load(dir.path()).unwrap();
+        assert!(deps.update(Some("missing")).is_err());
+    }
+}
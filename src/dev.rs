@@ -0,0 +1,212 @@
+use serde::Serialize;
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sim::Simulation;
+use crate::style::{self, Status};
+use crate::watch::{self, SourceWatcher};
+
+/// How long to wait for source changes between checks on the running network
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Live state of a dev session, served by the status endpoint
+pub struct DevStatus {
+    pub parties: u8,
+    pub protocol: String,
+    pub threshold: u8,
+    pub field: String,
+    /// Number of the current reload generation, 0 before the first start
+    pub generation: AtomicU64,
+}
+
+#[derive(Serialize)]
+struct Health<'a> {
+    status: &'static str,
+    parties: u8,
+    protocol: &'a str,
+    threshold: u8,
+    field: &'a str,
+    generation: u64,
+}
+
+/// First port that can be bound without elevated privileges
+const FIRST_UNPRIVILEGED_PORT: u16 = 1024;
+
+/// Check the dev server's port up front by binding it
+///
+/// Port 0 and, unless `allow_privileged` is set, ports below 1024 are
+/// rejected. The bound listener is returned for `serve_status`, so nothing
+/// else can take the port in between.
+pub fn ensure_port_available(address: SocketAddr, allow_privileged: bool) -> Result<TcpListener, String> {
+    let port = address.port();
+    if port == 0 {
+        return Err("Port 0 is not a valid dev server port; pick one with --port".to_string());
+    }
+    if port < FIRST_UNPRIVILEGED_PORT && !allow_privileged {
+        return Err(format!(
+            "Port {} is privileged (below {}). Pick another port with --port, or pass --allow-privileged.",
+            port, FIRST_UNPRIVILEGED_PORT
+        ));
+    }
+
+    TcpListener::bind(address).map_err(|e| match e.kind() {
+        io::ErrorKind::AddrInUse => format!(
+            "Port {} already in use on {}. Stop the other process or pick another port with --port.",
+            port,
+            address.ip()
+        ),
+        _ => format!("Failed to bind dev server to {}: {}", address, e),
+    })
+}
+
+/// Answer status requests on a background thread
+///
+/// `GET /health` returns the session's configuration and reload generation
+/// as JSON; every other path is a 404.
+pub fn serve_status(listener: TcpListener, status: Arc<DevStatus>) -> Result<(), String> {
+    let address = listener.local_addr().map_err(|e| format!("Failed to read dev server address: {}", e))?;
+    let server = tiny_http::Server::from_listener(listener, None)
+        .map_err(|e| format!("Failed to start dev server on {}: {}", address, e))?;
+
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let (code, body) = route(request.url(), &status);
+            let header = tiny_http::Header::from_bytes("Content-Type", "application/json")
+                .expect("static header is valid");
+            let response = tiny_http::Response::from_string(body)
+                .with_status_code(code)
+                .with_header(header);
+            let _ = request.respond(response);
+        }
+    });
+    Ok(())
+}
+
+fn route(url: &str, status: &DevStatus) -> (u16, String) {
+    let path = url.split('?').next().unwrap_or(url);
+    match path {
+        "/health" => {
+            let health = Health {
+                status: "ok",
+                parties: status.parties,
+                protocol: &status.protocol,
+                threshold: status.threshold,
+                field: &status.field,
+                generation: status.generation.load(Ordering::SeqCst),
+            };
+            (200, serde_json::to_string(&health).unwrap_or_default())
+        }
+        _ => (404, r#"{"error":"not found"}"#.to_string()),
+    }
+}
+
+/// Rebuild and restart the simulated network every time a source or Stoffel.toml changes
+///
+/// Sources are watched under src/ and next to `entry`, which may live
+/// elsewhere when Stoffel.toml sets `package.entry`.
+/// Each restart is a new generation, numbered from 1. A failed build or
+/// party is reported and the loop waits for the next change. Runs until the
+/// process is interrupted.
+pub fn reload_loop(
+    simulation: &Simulation,
+    entry: &Path,
+    status: &DevStatus,
+    mut rebuild: impl FnMut() -> Result<(), String>,
+) -> Result<(), String> {
+    let src = Path::new("src");
+    let mut watcher = SourceWatcher::new(src)?;
+    watcher.watch_file(Path::new(crate::config::MANIFEST_NAME))?;
+    match entry.parent().filter(|dir| !dir.as_os_str().is_empty() && *dir != Path::new(".")) {
+        Some(dir) if dir.starts_with(src) => {}
+        Some(dir) => watcher.watch_dir(dir, true)?,
+        // Recursing from the project root would watch target/ too
+        None => watcher.watch_dir(Path::new("."), false)?,
+    }
+
+    for generation in 1u64.. {
+        status.generation.store(generation, Ordering::SeqCst);
+        println!();
+        println!("[{}] 🔁 Generation {}", watch::timestamp(), generation);
+
+        let mut network = match rebuild().and_then(|()| simulation.start()) {
+            Ok(network) => Some(network),
+            Err(e) => {
+                eprintln!("[{}] {} {}", watch::timestamp(), style::prefix(Status::Failure), e);
+                None
+            }
+        };
+
+        loop {
+            if let Some(result) = network.as_mut().and_then(|network| network.poll()) {
+                match result {
+                    Ok(output) => {
+                        println!("[{}] {} All parties finished", watch::timestamp(), style::prefix(Status::Success));
+                        for line in output.lines() {
+                            println!("   {}", line);
+                        }
+                    }
+                    Err(e) => eprintln!("[{}] {} {}", watch::timestamp(), style::prefix(Status::Failure), e),
+                }
+                network = None;
+            }
+
+            let changed = watcher.changes(Some(POLL_INTERVAL))?;
+            if !changed.is_empty() {
+                for path in &changed {
+                    println!("[{}] 📝 Changed: {}", watch::timestamp(), path.display());
+                }
+                break;
+            }
+        }
+        // Dropping the network stops any parties still running from this generation
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> Arc<DevStatus> {
+        Arc::new(DevStatus {
+            parties: 5,
+            protocol: "honeybadger".to_string(),
+            threshold: 1,
+            field: "bls12-381".to_string(),
+            generation: AtomicU64::new(3),
+        })
+    }
+
+    #[test]
+    fn health_reports_configuration_and_generation() {
+        let (code, body) = route("/health?verbose=1", &status());
+        assert_eq!(code, 200);
+        let health: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(health["parties"], 5);
+        assert_eq!(health["protocol"], "honeybadger");
+        assert_eq!(health["threshold"], 1);
+        assert_eq!(health["field"], "bls12-381");
+        assert_eq!(health["generation"], 3);
+
+        assert_eq!(route("/other", &status()).0, 404);
+    }
+
+    #[test]
+    fn port_in_use_is_a_clear_error() {
+        let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+        let err = ensure_port_available(taken.local_addr().unwrap(), true).unwrap_err();
+        assert!(err.contains("already in use"), "{}", err);
+    }
+
+    #[test]
+    fn privileged_ports_need_an_explicit_opt_in() {
+        let address = |port| SocketAddr::from(([127, 0, 0, 1], port));
+        let err = ensure_port_available(address(80), false).unwrap_err();
+        assert!(err.contains("--allow-privileged"), "{}", err);
+        assert!(ensure_port_available(address(0), true).is_err());
+    }
+}
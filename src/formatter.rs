@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Spaces a tab expands to in the built-in normalizer
+const TAB_WIDTH: usize = 4;
+
+/// How `stoffel fmt` rewrites a source file
+pub enum Formatter {
+    /// The compiler's `--format` mode, which prints the formatted source
+    Compiler(PathBuf),
+    /// Whitespace-only cleanup for compilers without a formatter
+    Builtin,
+}
+
+impl Formatter {
+    /// Use the compiler's formatter if its `--help` advertises one
+    pub fn detect(compiler: Option<PathBuf>) -> Formatter {
+        let Some(compiler) = compiler else {
+            return Formatter::Builtin;
+        };
+        let supported = Command::new(&compiler)
+            .arg("--help")
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("--format"));
+        if supported {
+            Formatter::Compiler(compiler)
+        } else {
+            Formatter::Builtin
+        }
+    }
+
+    /// Name shown in `stoffel fmt` output
+    pub fn describe(&self) -> String {
+        match self {
+            Formatter::Compiler(path) => format!("compiler formatter ({})", path.display()),
+            Formatter::Builtin => "built-in whitespace normalizer".to_string(),
+        }
+    }
+
+    /// Formatted contents of `file`; the file itself is left untouched
+    pub fn format(&self, file: &Path) -> Result<String, String> {
+        match self {
+            Formatter::Compiler(compiler) => {
+                let output = Command::new(compiler)
+                    .arg(file)
+                    .arg("--format")
+                    .output()
+                    .map_err(|e| format!("Failed to execute compiler: {}", e))?;
+                if !output.status.success() {
+                    return Err(format!(
+                        "Failed to format {}:\n{}",
+                        file.display(),
+                        String::from_utf8_lossy(&output.stderr).trim_end()
+                    ));
+                }
+                String::from_utf8(output.stdout)
+                    .map_err(|_| format!("Compiler produced non-UTF-8 output for {}", file.display()))
+            }
+            Formatter::Builtin => {
+                let source = std::fs::read_to_string(file)
+                    .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+                Ok(normalize(&source))
+            }
+        }
+    }
+}
+
+/// Expand tabs, strip trailing whitespace, and end with exactly one newline
+pub fn normalize(source: &str) -> String {
+    let mut normalized: String = source
+        .lines()
+        .map(|line| line.replace('\t', &" ".repeat(TAB_WIDTH)).trim_end().to_string() + "\n")
+        .collect();
+    let content_len = normalized.trim_end_matches('\n').len();
+    normalized.truncate(content_len);
+    if !normalized.is_empty() {
+        normalized.push('\n');
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizer_only_touches_whitespace() {
+        assert_eq!(normalize("main() {\n\treturn 1;   \n}"), "main() {\n    return 1;\n}\n");
+        assert_eq!(normalize("x\r\n\n\n"), "x\n");
+        assert_eq!(normalize(""), "");
+
+        let formatted = "main() {\n    return 1;\n}\n";
+        assert_eq!(normalize(formatted), formatted);
+    }
+}
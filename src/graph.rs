@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path};
+
+/// Modules under `src/` and the imports between them
+///
+/// Modules are named by their path below `src/` without the `.stfl`
+/// extension, e.g. `util/math`. Imports that don't resolve to a project file
+/// (standard library, dependencies) are kept as external modules.
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    pub modules: BTreeSet<String>,
+    pub external: BTreeSet<String>,
+    /// Importing module to the modules it imports
+    pub imports: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Scan the `import`/`export` statements of `files`, which live below `src_dir`
+pub fn scan(src_dir: &Path, files: &[String]) -> Result<ModuleGraph, String> {
+    let mut sources = Vec::new();
+    for file in files {
+        let source = std::fs::read_to_string(file).map_err(|e| format!("Failed to read {}: {}", file, e))?;
+        sources.push((module_name(src_dir, Path::new(file)), source));
+    }
+
+    let mut graph = ModuleGraph {
+        modules: sources.iter().map(|(module, _)| module.clone()).collect(),
+        ..Default::default()
+    };
+    for (module, source) in &sources {
+        let mut imports = BTreeSet::new();
+        for imported in imported_modules(source) {
+            let resolved = resolve(&graph.modules, module, &imported);
+            if !graph.modules.contains(&resolved) {
+                graph.external.insert(resolved.clone());
+            }
+            imports.insert(resolved);
+        }
+        graph.imports.insert(module.clone(), imports);
+    }
+    Ok(graph)
+}
+
+/// Module name of `file`: its path below `src_dir`, without the extension
+fn module_name(src_dir: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(src_dir).unwrap_or(file).with_extension("");
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Modules named by the import statements in `source`
+///
+/// Understands `import a, b/c`, `from a import x` and the
+/// `import { x } from "a"` / `export { x } from "a"` forms. Everything after
+/// a `#` is a comment.
+fn imported_modules(source: &str) -> Vec<String> {
+    let mut modules = Vec::new();
+    for line in source.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if !(line.starts_with("import ") || line.starts_with("export ") || line.starts_with("from ")) {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("from ") {
+            if let Some((module, _)) = rest.split_once(" import ") {
+                modules.push(unquote(module));
+            }
+        } else if let Some((_, module)) = line.rsplit_once(" from ") {
+            modules.push(unquote(module));
+        } else if let Some(rest) = line.strip_prefix("import ") {
+            modules.extend(rest.split(',').map(unquote));
+        }
+    }
+    modules.retain(|module| !module.is_empty());
+    modules
+}
+
+fn unquote(module: &str) -> String {
+    let module = module.trim().trim_end_matches(';').trim_matches(|c| c == '"' || c == '\'');
+    module.strip_suffix(".stfl").unwrap_or(module).to_string()
+}
+
+/// Project module an import refers to, or the import itself if it's external
+///
+/// `./` and `../` paths are relative to the importing module; other names are
+/// looked up next to the importer first and then from the top of `src/`.
+fn resolve(modules: &BTreeSet<String>, importer: &str, imported: &str) -> String {
+    let importer_dir = importer.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+    let sibling = normalize(&format!("{}/{}", importer_dir, imported));
+    if imported.starts_with("./") || imported.starts_with("../") || modules.contains(&sibling) {
+        return sibling;
+    }
+    normalize(imported)
+}
+
+/// Collapse `.` and `..` segments of a module path
+fn normalize(path: &str) -> String {
+    let mut segments: Vec<String> = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::ParentDir => {
+                segments.pop();
+            }
+            Component::Normal(segment) => segments.push(segment.to_string_lossy().to_string()),
+            _ => {}
+        }
+    }
+    segments.join("/")
+}
+
+impl ModuleGraph {
+    /// Groups of project modules that import each other, directly or through others
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let reachable: BTreeMap<&String, BTreeSet<&String>> =
+            self.modules.iter().map(|module| (module, self.reachable_from(module))).collect();
+
+        let mut cycles = Vec::new();
+        let mut seen = BTreeSet::new();
+        for module in &self.modules {
+            if !reachable[module].contains(module) || seen.contains(module) {
+                continue;
+            }
+            let cycle: Vec<String> = self.modules.iter()
+                .filter(|other| reachable[module].contains(other) && reachable[other].contains(module))
+                .cloned()
+                .collect();
+            seen.extend(cycle.iter().cloned());
+            cycles.push(cycle);
+        }
+        cycles
+    }
+
+    /// Every module reachable from `start` by following at least one import
+    fn reachable_from<'a>(&'a self, start: &'a String) -> BTreeSet<&'a String> {
+        let mut reached = BTreeSet::new();
+        let mut pending = vec![start];
+        while let Some(module) = pending.pop() {
+            for imported in self.imports.get(module).into_iter().flatten() {
+                if reached.insert(imported) {
+                    pending.push(imported);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Graphviz DOT source, with external modules dashed
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph modules {\n    rankdir=LR;\n");
+        for module in &self.modules {
+            dot.push_str(&format!("    \"{}\";\n", module));
+        }
+        for module in &self.external {
+            dot.push_str(&format!("    \"{}\" [style=dashed];\n", module));
+        }
+        for (module, imports) in &self.imports {
+            for imported in imports {
+                dot.push_str(&format!("    \"{}\" -> \"{}\";\n", module, imported));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Mermaid flowchart source, with external modules drawn as rounded nodes
+    pub fn to_mermaid(&self) -> String {
+        // Mermaid ids can't contain `/`, so nodes are numbered and labelled instead
+        let ids: BTreeMap<&String, usize> = self.modules.iter()
+            .chain(&self.external)
+            .enumerate()
+            .map(|(index, module)| (module, index))
+            .collect();
+
+        let mut mermaid = String::from("graph LR\n");
+        for module in &self.modules {
+            mermaid.push_str(&format!("    m{}[\"{}\"]\n", ids[module], module));
+        }
+        for module in &self.external {
+            mermaid.push_str(&format!("    m{}([\"{}\"])\n", ids[module], module));
+        }
+        for (module, imports) in &self.imports {
+            for imported in imports {
+                mermaid.push_str(&format!("    m{} --> m{}\n", ids[module], ids[imported]));
+            }
+        }
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_resolve_to_project_modules_and_cycles_are_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("util")).unwrap();
+        let files = [
+            ("main.stfl", "import util/math, std/io  # comment\nfrom auth import check\n"),
+            ("util/math.stfl", "import { check } from \"../auth\"\n"),
+            ("auth.stfl", "export { add } from \"util/math.stfl\";\n"),
+        ];
+        for (name, source) in files {
+            std::fs::write(src.join(name), source).unwrap();
+        }
+        let paths: Vec<String> = files.iter().map(|(name, _)| src.join(name).to_string_lossy().to_string()).collect();
+
+        let graph = scan(&src, &paths).unwrap();
+        assert_eq!(graph.imports["main"], BTreeSet::from(["util/math".to_string(), "std/io".to_string(), "auth".to_string()]));
+        assert_eq!(graph.imports["util/math"], BTreeSet::from(["auth".to_string()]));
+        assert_eq!(graph.external, BTreeSet::from(["std/io".to_string()]));
+        assert_eq!(graph.cycles(), [vec!["auth".to_string(), "util/math".to_string()]]);
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("    \"main\" -> \"util/math\";\n"), "{}", dot);
+        assert!(dot.contains("    \"std/io\" [style=dashed];\n"), "{}", dot);
+        assert!(graph.to_mermaid().contains("    m1 --> m0\n"));
+    }
+}
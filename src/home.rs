@@ -0,0 +1,9 @@
+use std::path::PathBuf;
+
+/// Per-user Stoffel directory (`~/.stoffel`), overridable with `STOFFEL_HOME`
+pub fn stoffel_home() -> Option<PathBuf> {
+    if let Some(home) = std::env::var_os("STOFFEL_HOME") {
+        return Some(PathBuf::from(home));
+    }
+    dirs::home_dir().map(|home| home.join(".stoffel"))
+}
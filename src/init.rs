@@ -1,32 +1,123 @@
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
 use std::path::{Path, PathBuf};
 
+use crate::style::{self, Status};
+use crate::{calculate_threshold, MpcField, MpcProtocol};
+
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct StoffelConfig {
     pub package: PackageConfig,
     pub mpc: MpcConfig,
-    pub dependencies: Option<HashMap<String, String>>,
-    pub dev_dependencies: Option<HashMap<String, String>>,
+    pub dependencies: Option<BTreeMap<String, String>>,
+    pub dev_dependencies: Option<BTreeMap<String, String>>,
+    pub workspace: Option<crate::config::WorkspaceConfig>,
+    pub tools: Option<crate::config::ToolsConfig>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct PackageConfig {
     pub name: String,
     pub version: String,
     pub description: Option<String>,
     pub authors: Option<Vec<String>>,
     pub license: Option<String>,
+    /// Program that run, dev, bench and deploy use [default: src/main.stfl]
+    pub entry: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct MpcConfig {
     pub protocol: String,
     pub parties: u8,
     pub threshold: Option<u8>,
     pub field: String,
+    pub profiles: Option<BTreeMap<String, MpcProfile>>,
+}
+
+/// Named MPC settings in `[mpc.profiles.<name>]`; unset values fall back to `[mpc]`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MpcProfile {
+    pub protocol: Option<String>,
+    pub parties: Option<u8>,
+    pub threshold: Option<u8>,
+    pub field: Option<String>,
+}
+
+/// Answers to the interactive prompts, read from `stoffel init --answers`
+///
+/// Keys left out fall back to the same defaults the prompts offer.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct InitAnswers {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub parties: Option<u8>,
+    pub field: Option<String>,
+    pub template: Option<String>,
+}
+
+impl InitAnswers {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+}
+
+/// User-global defaults from `~/.stoffel/config.toml`
+///
+/// Applied when neither a CLI flag nor a prompt answer provides a value.
+#[derive(Deserialize, Debug, Default)]
+pub struct UserDefaults {
+    pub author: Option<String>,
+    pub license: Option<String>,
+    pub field: Option<String>,
+    pub template: Option<String>,
+    pub tools: Option<crate::config::ToolsConfig>,
+}
+
+impl UserDefaults {
+    pub fn path() -> Option<PathBuf> {
+        crate::home::stoffel_home().map(|home| home.join("config.toml"))
+    }
+
+    /// Load the defaults file; a missing file yields empty defaults
+    pub fn load() -> Result<Self, String> {
+        match Self::path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    fn load_from(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+
+    fn author(&self) -> String {
+        self.author
+            .clone()
+            .or_else(get_git_user)
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+
+    fn license(&self) -> String {
+        self.license.clone().unwrap_or_else(|| "MIT".to_string())
+    }
+
+    fn field(&self) -> String {
+        self.field.clone().unwrap_or_else(|| "bls12-381".to_string())
+    }
 }
 
 pub struct InitOptions {
@@ -35,23 +126,243 @@ pub struct InitOptions {
     pub path: Option<String>,
     pub interactive: bool,
     pub template: Option<String>,
+    /// Git repository (`url` or `url#ref`) whose files make up the project template
+    pub template_git: Option<String>,
+    /// TOML file answering the interactive prompts, for scripted setup
+    pub answers: Option<String>,
+    /// Stream the generated files to stdout as a tar archive instead of writing them
+    pub print_only: bool,
+    /// List the files that would be generated without writing anything
+    pub dry_run: bool,
+    /// Overwrite an existing Stoffel.toml instead of refusing
+    pub force: bool,
+    /// Initialize a git repository and commit the generated files
+    pub git: bool,
+}
+
+/// A file to generate, relative to the project root
+pub struct PlannedFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Characters of each file's first line shown by `--dry-run`
+const DRY_RUN_PREVIEW_CHARS: usize = 60;
+
+/// Files and directories that make up a new project, collected before anything is written
+#[derive(Default)]
+pub struct ProjectFiles {
+    files: Vec<PlannedFile>,
+    dirs: Vec<PathBuf>,
+}
+
+impl ProjectFiles {
+    fn add(&mut self, path: impl Into<PathBuf>, contents: impl Into<String>) {
+        self.files.push(PlannedFile {
+            path: path.into(),
+            contents: contents.into(),
+        });
+    }
+
+    /// Record a directory that should exist even if no file is generated inside it
+    fn add_dir(&mut self, path: impl Into<PathBuf>) {
+        self.dirs.push(path.into());
+    }
+
+    pub fn files(&self) -> &[PlannedFile] {
+        &self.files
+    }
+
+    fn write_to(&self, root: &Path) -> Result<(), String> {
+        fs::create_dir_all(root)
+            .map_err(|e| format!("Failed to create project directory: {}", e))?;
+
+        for dir in &self.dirs {
+            fs::create_dir_all(root.join(dir))
+                .map_err(|e| format!("Failed to create {} directory: {}", dir.display(), e))?;
+        }
+
+        for file in &self.files {
+            let target = root.join(&file.path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {} directory: {}", parent.display(), e))?;
+            }
+            fs::write(&target, &file.contents)
+                .map_err(|e| format!("Failed to write {}: {}", file.path.display(), e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Tree of the planned directories and files, with each file's size and first line
+    fn dry_run_listing(&self) -> Vec<String> {
+        let mut entries: BTreeMap<&Path, Option<&PlannedFile>> = BTreeMap::new();
+        let parents = self.files.iter().filter_map(|file| file.path.parent());
+        for dir in self.dirs.iter().map(PathBuf::as_path).chain(parents) {
+            for ancestor in dir.ancestors().filter(|ancestor| !ancestor.as_os_str().is_empty()) {
+                entries.entry(ancestor).or_insert(None);
+            }
+        }
+        for file in &self.files {
+            entries.insert(&file.path, Some(file));
+        }
+
+        let label = |path: &Path, file: Option<&PlannedFile>| {
+            let indent = "  ".repeat(path.components().count() - 1);
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let slash = if file.is_none() { "/" } else { "" };
+            format!("{}{}{}", indent, name, slash)
+        };
+        let width = entries.iter().map(|(path, file)| label(path, *file).chars().count()).max().unwrap_or(0);
+
+        entries
+            .iter()
+            .map(|(path, file)| {
+                let label = label(path, *file);
+                let Some(file) = file else {
+                    return label;
+                };
+                let first_line = file.contents.lines().find(|line| !line.trim().is_empty()).unwrap_or("").trim();
+                let first_line = match first_line.char_indices().nth(DRY_RUN_PREVIEW_CHARS) {
+                    Some((end, _)) => format!("{}…", &first_line[..end]),
+                    None => first_line.to_string(),
+                };
+                format!("{:<width$}  {:>7} B  {}", label, file.contents.len(), first_line, width = width)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn print_dry_run(&self, root: &Path) {
+        println!("🔍 Dry run: would create {} file(s) in {}", self.files.len(), root.display());
+        for line in self.dry_run_listing() {
+            println!("   {}", line);
+        }
+        println!("   Nothing was written.");
+    }
+
+    /// Serialize the planned files as a tar stream with paths relative to the project root
+    fn write_archive<W: Write>(&self, out: W) -> Result<(), String> {
+        let mtime = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut archive = tar::Builder::new(out);
+
+        for dir in &self.dirs {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_mode(0o755);
+            header.set_size(0);
+            header.set_mtime(mtime);
+            archive
+                .append_data(&mut header, dir, io::empty())
+                .map_err(|e| format!("Failed to archive {}: {}", dir.display(), e))?;
+        }
+
+        for file in &self.files {
+            let mut header = tar::Header::new_gnu();
+            header.set_mode(0o644);
+            header.set_size(file.contents.len() as u64);
+            header.set_mtime(mtime);
+            archive
+                .append_data(&mut header, &file.path, file.contents.as_bytes())
+                .map_err(|e| format!("Failed to archive {}: {}", file.path.display(), e))?;
+        }
+
+        archive
+            .into_inner()
+            .and_then(|mut out| out.flush())
+            .map_err(|e| format!("Failed to write archive: {}", e))
+    }
 }
 
 pub fn initialize_project(options: InitOptions) -> Result<(), String> {
     let project_path = determine_project_path(&options)?;
     let project_name = determine_project_name(&options, &project_path)?;
+    validate_project_name(&project_name).map_err(|e| {
+        if options.name.is_none() {
+            format!("{} (derived from the directory name; pass a name explicitly)", e)
+        } else {
+            e
+        }
+    })?;
+
+    if !options.force && !options.print_only {
+        ensure_directory_available(&project_path)?;
+    }
+    let defaults = UserDefaults::load()?;
 
     if options.interactive {
-        initialize_interactive(project_name, project_path, options.lib)?;
-    } else if let Some(template) = &options.template {
-        initialize_from_template(project_name, project_path, template, options.lib)?;
+        initialize_interactive(&mut Prompter::stdin(), project_name, project_path, &options, &defaults)?;
+    } else if let Some(answers) = &options.answers {
+        let answers = InitAnswers::load(Path::new(answers))?;
+        initialize_from_answers(project_name, project_path, answers, &options, &defaults)?;
+    } else if let Some(spec) = &options.template_git {
+        initialize_from_git(project_name, project_path, spec, &options, &defaults)?;
+    } else if let Some(template) = options.template.as_ref().or(defaults.template.as_ref().filter(|template| {
+        // A default without a library layout shouldn't stop `--lib` from working
+        !options.lib || find_template(template).is_some_and(|template| template.library.is_some())
+    })) {
+        initialize_from_template(project_name, project_path, template, &options, &defaults)?;
     } else {
-        initialize_default(project_name, project_path, options.lib)?;
+        initialize_default(project_name, project_path, &options, &defaults)?;
     }
 
     Ok(())
 }
 
+/// `stoffel new`: initialize into a fresh `<name>/` directory, which must not exist yet
+pub fn new_project(options: InitOptions) -> Result<(), String> {
+    let project_path = determine_project_path(&options)?;
+    if project_path.exists() {
+        return Err(format!(
+            "destination {} already exists. Use `stoffel init` to set up an existing directory.",
+            project_path.display()
+        ));
+    }
+    initialize_project(options)
+}
+
+/// Refuse to initialize over an existing project or into a non-empty directory
+fn ensure_directory_available(path: &Path) -> Result<(), String> {
+    let manifest_path = path.join("Stoffel.toml");
+    if manifest_path.exists() {
+        return Err(format!(
+            "{} already exists. Use --force to overwrite the existing project.",
+            manifest_path.display()
+        ));
+    }
+
+    let Ok(entries) = fs::read_dir(path) else {
+        return Ok(());
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    if names.is_empty() {
+        return Ok(());
+    }
+    names.sort();
+
+    const SHOWN: usize = 5;
+    eprintln!("{}  {} is not empty:", style::prefix(Status::Warning), path.display());
+    for name in names.iter().take(SHOWN) {
+        eprintln!("     - {}", name);
+    }
+    if names.len() > SHOWN {
+        eprintln!("     ... and {} more", names.len() - SHOWN);
+    }
+
+    Err(format!(
+        "{} is not empty. Use --force to initialize anyway; existing files with generated names will be overwritten.",
+        path.display()
+    ))
+}
+
 fn determine_project_path(options: &InitOptions) -> Result<PathBuf, String> {
     let base_path = if let Some(path) = &options.path {
         PathBuf::from(path)
@@ -78,224 +389,667 @@ fn determine_project_name(options: &InitOptions, project_path: &Path) -> Result<
     }
 }
 
-fn initialize_interactive(name: String, path: PathBuf, is_lib: bool) -> Result<(), String> {
+/// Check that a project name is a valid package identifier: lowercase letters,
+/// digits and hyphens, starting with a letter
+pub fn validate_project_name(name: &str) -> Result<(), String> {
+    let reason = if name.is_empty() {
+        Some("name must not be empty")
+    } else if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        Some("use lowercase letters, digits, and hyphens")
+    } else if !name.starts_with(|c: char| c.is_ascii_lowercase()) {
+        Some("must start with a lowercase letter")
+    } else {
+        None
+    };
+
+    match reason {
+        Some(reason) => Err(format!("invalid project name '{}': {}", name, reason)),
+        None => Ok(()),
+    }
+}
+
+/// What a new project's Stoffel.toml is generated from
+struct ProjectSettings {
+    name: String,
+    description: Option<String>,
+    author: String,
+    license: String,
+    parties: u8,
+    field: String,
+}
+
+impl ProjectSettings {
+    /// Settings for a non-interactive init: 5 parties and the user's defaults
+    fn from_defaults(name: String, description: String, defaults: &UserDefaults) -> Self {
+        ProjectSettings {
+            name,
+            description: Some(description),
+            author: defaults.author(),
+            license: defaults.license(),
+            parties: 5,
+            field: defaults.field(),
+        }
+    }
+
+    /// The manifest for these settings, with the HoneyBadger threshold for the party count
+    ///
+    /// Every init mode goes through here, so prompts, answers files and
+    /// defaults are held to the same rules.
+    fn manifest(self) -> Result<StoffelConfig, String> {
+        validate_project_name(&self.name)?;
+        if MpcField::from_str(&self.field, true).is_err() {
+            return Err(format!(
+                "unsupported field '{}' (expected one of: {})",
+                self.field,
+                crate::possible_values::<MpcField>()
+            ));
+        }
+        let threshold = calculate_threshold(self.parties, &MpcProtocol::Honeybadger)?;
+        Ok(StoffelConfig {
+            package: PackageConfig {
+                name: self.name,
+                version: "0.1.0".to_string(),
+                description: self.description,
+                authors: Some(vec![self.author]),
+                license: Some(self.license),
+                entry: None,
+            },
+            mpc: MpcConfig {
+                protocol: "honeybadger".to_string(),
+                parties: self.parties,
+                threshold: Some(threshold),
+                field: self.field,
+                profiles: None,
+            },
+            dependencies: None,
+            dev_dependencies: None,
+            workspace: None,
+            tools: None,
+        })
+    }
+}
+
+fn initialize_interactive<R: BufRead>(
+    input: &mut Prompter<R>,
+    name: String,
+    path: PathBuf,
+    options: &InitOptions,
+    defaults: &UserDefaults,
+) -> Result<(), String> {
+    let is_lib = options.lib;
     println!("🚀 Interactive Stoffel project setup");
     println!("Press Enter to use default values shown in [brackets]");
     println!();
 
+    let (config, template) = prompt_project(input, name, options, defaults)?;
+    let files = plan_project_files(&config, is_lib, Some(template))?;
+
+    println!("\n📋 Summary:");
+    println!("   Name: {}", config.package.name);
+    println!("   Path: {}", path.display());
+    println!("   Kind: {}", if is_lib { "library" } else { "application" });
+    println!("   Template: {}", template);
+    println!("   Protocol: {}", config.mpc.protocol);
+    println!("   Parties: {}", config.mpc.parties);
+    if let Some(threshold) = config.mpc.threshold {
+        println!("   Threshold: {}", threshold);
+    }
+    println!("   Field: {}", config.mpc.field);
+    println!("   Files to create:");
+    for file in files.files() {
+        println!("     - {}", file.path.display());
+    }
+
+    // A dry run's listing stands in for the confirmation
+    if options.dry_run {
+        println!();
+    } else if input.confirm("\nCreate project?", true)? {
+        println!("\n📁 Creating project structure...");
+    } else {
+        println!("Aborted. No files were written.");
+        return Ok(());
+    }
+    emit_files(&path, &config, &files, options)
+}
+
+/// Ask for the project details, MPC settings and template, offering `defaults`
+///
+/// A template given with `--template` is used without asking.
+fn prompt_project<R: BufRead>(
+    input: &mut Prompter<R>,
+    name: String,
+    options: &InitOptions,
+    defaults: &UserDefaults,
+) -> Result<(StoffelConfig, &'static str), String> {
     // Project details
-    let project_name = prompt_with_default("Project name", &name)?;
-    let description = prompt_optional("Description")?;
-    let author = prompt_with_default("Author", &get_git_user().unwrap_or_else(|| "Unknown".to_string()))?;
+    let project_name = input.with_default("Project name", &name)?;
+    validate_project_name(&project_name)?;
+    let description = input.optional("Description")?;
+    let author = input.with_default("Author", &defaults.author())?;
+    let author = if author.contains('<') {
+        author
+    } else {
+        let email = input.author_email(get_git_email())?;
+        format_author(&author, email.as_deref())
+    };
 
     // MPC Configuration
     println!("\n🔒 MPC Configuration:");
-    let parties = prompt_with_default_parsed("Number of parties", 5u8)?;
-    let field = prompt_with_default("Field type", "bls12-381")?;
-
-    // Validate parties for HoneyBadger
-    if parties < 5 {
-        return Err("HoneyBadger protocol requires at least 5 parties".to_string());
-    }
-
-    let threshold = (parties - 1) / 3;
-    println!("   Calculated threshold: {} (max corrupted parties)", threshold);
-
-    // Template selection based on programming language ecosystem
-    let template = if !is_lib {
-        println!("\n📋 Available language ecosystems:");
-        println!("   1. python - Python SDK integration (fully implemented)");
-        println!("   2. rust - Rust FFI integration (skeleton)");
-        println!("   3. typescript - TypeScript/Node.js integration (skeleton)");
-        println!("   4. solidity - Solidity smart contract integration (skeleton)");
-        println!("   5. stoffel - Pure StoffelLang (default)");
-
-        let choice = prompt_with_default("Choose ecosystem (1-5)", "5")?;
-        match choice.as_str() {
-            "1" => Some("python"),
-            "2" => Some("rust"),
-            "3" => Some("typescript"),
-            "4" => Some("solidity"),
-            _ => Some("stoffel"),
-        }
-    } else {
-        None
+    let parties = input.parsed("Number of parties", 5u8)?;
+    let field = input.with_default("Field type", &defaults.field())?;
+
+    let settings = ProjectSettings {
+        name: project_name,
+        description: if description.is_empty() { None } else { Some(description) },
+        author,
+        license: defaults.license(),
+        parties,
+        field,
     };
+    let config = settings.manifest()?;
+    if let Some(threshold) = config.mpc.threshold {
+        println!("   Calculated threshold: {} (max corrupted parties)", threshold);
+    }
 
-    println!("\n📁 Creating project structure...");
+    let template = match &options.template {
+        Some(template) => validate_template(template)?.name,
+        None => prompt_template(input, options.lib, defaults)?,
+    };
+    Ok((config, template))
+}
 
-    let config = StoffelConfig {
-        package: PackageConfig {
-            name: project_name,
-            version: "0.1.0".to_string(),
-            description: if description.is_empty() { None } else { Some(description) },
-            authors: Some(vec![author]),
-            license: Some("MIT".to_string()),
-        },
-        mpc: MpcConfig {
-            protocol: "honeybadger".to_string(),
-            parties,
-            threshold: Some(threshold),
-            field,
-        },
-        dependencies: None,
-        dev_dependencies: None,
+/// Initialize from an answers file as if its values had been typed at the prompts
+///
+/// `--template` overrides the file's template; keys the file leaves out take
+/// the defaults the prompts would have offered.
+fn initialize_from_answers(
+    name: String,
+    path: PathBuf,
+    answers: InitAnswers,
+    options: &InitOptions,
+    defaults: &UserDefaults,
+) -> Result<(), String> {
+    let source = options.answers.as_deref().unwrap_or_default();
+    status(options.print_only, &format!("🚀 Initializing from answers in {}", source));
+
+    let template = options.template.clone().or(answers.template).or_else(|| {
+        // As in the prompt, a default without a library layout isn't offered for `--lib`
+        defaults.template.clone().filter(|template| {
+            !options.lib || find_template(template).is_some_and(|template| template.library.is_some())
+        })
+    });
+    let template = match template {
+        Some(template) => validate_template(&template)?.name,
+        None => DEFAULT_TEMPLATE,
     };
 
-    create_project_structure(&path, &config, is_lib, template)?;
-    println!("✅ Project initialized successfully at {}", path.display());
-    Ok(())
+    let settings = ProjectSettings {
+        name: answers.name.unwrap_or(name),
+        description: answers.description.filter(|description| !description.is_empty()),
+        author: answers.author.unwrap_or_else(|| defaults.author()),
+        license: defaults.license(),
+        parties: answers.parties.unwrap_or(5),
+        field: answers.field.unwrap_or_else(|| defaults.field()),
+    };
+    let config = settings.manifest().map_err(|e| format!("{}: {}", source, e))?;
+
+    emit_project(&path, &config, Some(template), options)
 }
 
-fn initialize_from_template(name: String, path: PathBuf, template: &str, is_lib: bool) -> Result<(), String> {
-    println!("🚀 Initializing from template: {}", template);
+/// Offer the language ecosystems by number; libraries only get the templates
+/// that have a library layout
+fn prompt_template<R: BufRead>(input: &mut Prompter<R>, is_lib: bool, defaults: &UserDefaults) -> Result<&'static str, String> {
+    let choices: Vec<&Template> = TEMPLATES
+        .iter()
+        .filter(|template| !is_lib || template.library.is_some())
+        .collect();
+    println!("\n📋 Available language ecosystems:");
+    for (number, template) in choices.iter().enumerate() {
+        let default = if template.name == DEFAULT_TEMPLATE { ", default" } else { "" };
+        println!("   {}. {} - {} ({}{})", number + 1, template.name, template.description, template.status, default);
+    }
 
-    let config = StoffelConfig {
-        package: PackageConfig {
-            name,
-            version: "0.1.0".to_string(),
-            description: Some(get_template_description(template)),
-            authors: Some(vec![get_git_user().unwrap_or_else(|| "Unknown".to_string())]),
-            license: Some("MIT".to_string()),
-        },
-        mpc: MpcConfig {
-            protocol: "honeybadger".to_string(),
-            parties: 5,
-            threshold: Some(1),
-            field: "bls12-381".to_string(),
-        },
-        dependencies: None,
-        dev_dependencies: None,
+    let preferred = defaults.template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+    let default_choice = choices
+        .iter()
+        .position(|template| template.name == preferred)
+        .or_else(|| choices.iter().position(|template| template.name == DEFAULT_TEMPLATE))
+        .map_or(1, |index| index + 1);
+    let choice = input.with_default(&format!("Choose ecosystem (1-{})", choices.len()), &default_choice.to_string())?;
+    Ok(choice
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| choices.get(number.checked_sub(1)?))
+        .map_or(DEFAULT_TEMPLATE, |template| template.name))
+}
+
+fn initialize_from_template(
+    name: String,
+    path: PathBuf,
+    template: &str,
+    options: &InitOptions,
+    defaults: &UserDefaults,
+) -> Result<(), String> {
+    validate_template(template)?;
+    status(options.print_only, &format!("🚀 Initializing from template: {}", template));
+
+    let config = ProjectSettings::from_defaults(name, get_template_description(template), defaults).manifest()?;
+
+    emit_project(&path, &config, Some(template), options)
+}
+
+/// Initialize from a template stored in a git repository (`url` or `url#ref`)
+fn initialize_from_git(
+    name: String,
+    path: PathBuf,
+    spec: &str,
+    options: &InitOptions,
+    defaults: &UserDefaults,
+) -> Result<(), String> {
+    status(options.print_only, &format!("🚀 Initializing from git template: {}", spec));
+
+    let config = ProjectSettings::from_defaults(name, "A Stoffel MPC application".to_string(), defaults).manifest()?;
+
+    let clone_dir = std::env::temp_dir().join(format!("stoffel-template-{}", std::process::id()));
+    if clone_dir.exists() {
+        fs::remove_dir_all(&clone_dir).map_err(|e| format!("Failed to remove {}: {}", clone_dir.display(), e))?;
+    }
+    let result = clone_template(spec, &clone_dir).and_then(|()| plan_git_template_files(&config, &clone_dir));
+    if clone_dir.exists() {
+        let _ = fs::remove_dir_all(&clone_dir);
+    }
+
+    emit_files(&path, &config, &result?, options)
+}
+
+/// Split a `url#ref` template spec into the repository URL and the optional ref
+fn split_git_ref(spec: &str) -> (&str, Option<&str>) {
+    match spec.rsplit_once('#') {
+        Some((url, reference)) if !reference.is_empty() => (url, Some(reference)),
+        Some((url, _)) => (url, None),
+        None => (spec, None),
+    }
+}
+
+/// Clone the repository named by `spec` into `dir`, checking out the pinned ref if any
+fn clone_template(spec: &str, dir: &Path) -> Result<(), String> {
+    let (url, reference) = split_git_ref(spec);
+    let run = |command: &mut std::process::Command| -> Result<(), String> {
+        let output = command.output().map_err(|e| format!("Failed to run git: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
     };
 
-    create_project_structure(&path, &config, is_lib, Some(template))?;
-    println!("✅ Project initialized successfully at {}", path.display());
+    let mut clone = std::process::Command::new("git");
+    clone.args(["clone", "--quiet"]);
+    // A pinned commit may not be reachable from a shallow clone of the default branch
+    if reference.is_none() {
+        clone.args(["--depth", "1"]);
+    }
+    run(clone.arg(url).arg(dir)).map_err(|e| format!("git clone of {} failed: {}", url, e))?;
+
+    if let Some(reference) = reference {
+        run(std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["checkout", "--quiet", reference]))
+        .map_err(|e| format!("Failed to check out '{}' in {}: {}", reference, url, e))?;
+    }
     Ok(())
 }
 
-fn initialize_default(name: String, path: PathBuf, is_lib: bool) -> Result<(), String> {
-    println!("🚀 Initializing default Stoffel project");
+/// Collect the files of a cloned template, substituting variables in their paths and contents
+///
+/// Stoffel.toml is always generated from the init settings, so a template's own copy is ignored.
+fn plan_git_template_files(config: &StoffelConfig, template_dir: &Path) -> Result<ProjectFiles, String> {
+    let mut files = ProjectFiles::default();
+    let toml_content = toml::to_string(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    files.add("Stoffel.toml", toml_content);
 
-    let config = StoffelConfig {
-        package: PackageConfig {
-            name,
-            version: "0.1.0".to_string(),
-            description: Some("A Stoffel MPC application".to_string()),
-            authors: Some(vec![get_git_user().unwrap_or_else(|| "Unknown".to_string())]),
-            license: Some("MIT".to_string()),
-        },
-        mpc: MpcConfig {
-            protocol: "honeybadger".to_string(),
-            parties: 5,
-            threshold: Some(1),
-            field: "bls12-381".to_string(),
-        },
-        dependencies: None,
-        dev_dependencies: None,
-    };
+    let mut template_files = Vec::new();
+    collect_template_files(template_dir, Path::new(""), &mut template_files)?;
+    for relative in template_files {
+        if relative == Path::new("Stoffel.toml") {
+            continue;
+        }
+        let full = template_dir.join(&relative);
+        let content = fs::read(&full).map_err(|e| format!("Failed to read {}: {}", full.display(), e))?;
+        let content = String::from_utf8(content)
+            .map_err(|_| format!("Template file {} is not UTF-8 text; binary template files aren't supported", relative.display()))?;
+        let path = substitute_template_vars(&relative.to_string_lossy(), config)?;
+        files.add(path, substitute_template_vars(&content, config)?);
+    }
 
-    create_project_structure(&path, &config, is_lib, Some("basic"))?;
-    println!("✅ Project initialized successfully at {}", path.display());
+    if !files.files().iter().any(|file| file.path == Path::new(".gitignore")) {
+        create_gitignore(&mut files, None)?;
+    }
+    Ok(files)
+}
+
+/// Recursively list the files under `dir` (relative to `root`) in a stable order, skipping .git
+fn collect_template_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let full = root.join(dir);
+    let mut entries: Vec<_> = fs::read_dir(&full)
+        .map_err(|e| format!("Failed to read {}: {}", full.display(), e))?
+        .filter_map(|entry| entry.ok())
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let relative = dir.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .map_err(|e| format!("Failed to read {}: {}", relative.display(), e))?;
+        if file_type.is_dir() {
+            if entry.file_name() != ".git" {
+                collect_template_files(root, &relative, files)?;
+            }
+        } else if file_type.is_file() {
+            files.push(relative);
+        }
+    }
     Ok(())
 }
 
-fn create_project_structure(
-    path: &Path,
-    config: &StoffelConfig,
-    is_lib: bool,
-    template: Option<&str>,
-) -> Result<(), String> {
-    // Create main directory
-    fs::create_dir_all(path)
-        .map_err(|e| format!("Failed to create project directory: {}", e))?;
+fn initialize_default(name: String, path: PathBuf, options: &InitOptions, defaults: &UserDefaults) -> Result<(), String> {
+    status(options.print_only, "🚀 Initializing default Stoffel project");
+
+    let config = ProjectSettings::from_defaults(name, "A Stoffel MPC application".to_string(), defaults).manifest()?;
+
+    emit_project(&path, &config, None, options)
+}
+
+/// Progress output goes to stderr when stdout carries the project archive
+fn status(print_only: bool, message: &str) {
+    if print_only {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// Write the project to disk, or to stdout as a tar archive with `--print-only`
+fn emit_project(path: &Path, config: &StoffelConfig, template: Option<&str>, options: &InitOptions) -> Result<(), String> {
+    let files = plan_project_files(config, options.lib, template)?;
+    emit_files(path, config, &files, options)
+}
+
+fn emit_files(path: &Path, config: &StoffelConfig, files: &ProjectFiles, options: &InitOptions) -> Result<(), String> {
+    if options.print_only {
+        files.write_archive(io::stdout().lock())?;
+        eprintln!("{} Wrote project archive for {} to stdout", style::prefix(Status::Success), config.package.name);
+    } else if options.dry_run {
+        files.print_dry_run(path);
+    } else {
+        files.write_to(path)?;
+        if options.git {
+            init_git_repository(path, files);
+        }
+        println!("{} Project initialized successfully at {}", style::prefix(Status::Success), path.display());
+    }
+    Ok(())
+}
+
+/// Put a freshly written project under version control and commit the generated files
+///
+/// Problems only produce warnings: a missing git shouldn't fail the whole init.
+fn init_git_repository(path: &Path, files: &ProjectFiles) {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(path)
+            .output()
+    };
+
+    match git(&["rev-parse", "--is-inside-work-tree"]) {
+        Err(_) => {
+            println!("{}  git not found; skipping repository initialization", style::prefix(Status::Warning));
+            return;
+        }
+        Ok(output) if output.status.success() => {
+            println!("   Skipping git init: {} is already inside a git repository", path.display());
+            return;
+        }
+        Ok(_) => {}
+    }
+
+    if !git(&["init", "--quiet"]).is_ok_and(|output| output.status.success()) {
+        println!("{}  git init failed; skipping repository initialization", style::prefix(Status::Warning));
+        return;
+    }
+
+    let paths: Vec<String> = files.files().iter().map(|file| file.path.to_string_lossy().to_string()).collect();
+    let mut add_args = vec!["add", "--"];
+    add_args.extend(paths.iter().map(|path| path.as_str()));
+    if !git(&add_args).is_ok_and(|output| output.status.success()) {
+        println!("{}  Initialized a git repository but failed to stage the generated files", style::prefix(Status::Warning));
+        return;
+    }
+
+    if git(&["commit", "--quiet", "-m", "Initial commit"]).is_ok_and(|output| output.status.success()) {
+        println!("{} Initialized git repository with an initial commit", style::prefix(Status::Package));
+    } else {
+        println!("{}  Initialized a git repository and staged the generated files, but the initial commit failed (are git user.name and user.email set?)", style::prefix(Status::Warning));
+    }
+}
+
+/// Collect every file a new project will contain without touching the filesystem
+fn plan_project_files(config: &StoffelConfig, is_lib: bool, template: Option<&str>) -> Result<ProjectFiles, String> {
+    let mut files = ProjectFiles::default();
 
     // Create Stoffel.toml
     let toml_content = toml::to_string(config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(path.join("Stoffel.toml"), toml_content)
-        .map_err(|e| format!("Failed to write Stoffel.toml: {}", e))?;
+    files.add("Stoffel.toml", toml_content);
 
     if is_lib {
-        create_library_structure(path, config, template)?;
+        create_library_structure(&mut files, config, template)?;
+        create_gitignore(&mut files, template)?;
     } else {
-        create_project_structure_full(path, config, template)?;
+        create_project_structure_full(&mut files, config, template)?;
+        create_gitignore(&mut files, template)?;
     }
 
-    Ok(())
+    Ok(files)
 }
 
-fn create_project_structure_full(path: &Path, config: &StoffelConfig, template: Option<&str>) -> Result<(), String> {
-    let template = template.unwrap_or("stoffel");
-
-    match template {
-        "python" => create_python_project(path, config)?,
-        "rust" => create_rust_project(path, config)?,
-        "typescript" => create_typescript_project(path, config)?,
-        "solidity" => create_solidity_project(path, config)?,
-        _ => create_stoffel_project(path, config)?,
+/// Add a .gitignore covering build output plus the template's ecosystem artifacts
+fn create_gitignore(files: &mut ProjectFiles, template: Option<&str>) -> Result<(), String> {
+    let mut content = load_template("stoffel", "gitignore")?;
+    if let Some(template @ ("python" | "rust" | "typescript" | "solidity" | "go" | "java")) = template {
+        content.push('\n');
+        content.push_str(&load_template(template, "gitignore")?);
     }
 
-    // Create README for all templates
-    let readme_content = get_template_readme(config, template);
-    fs::write(path.join("README.md"), readme_content)
-        .map_err(|e| format!("Failed to write README.md: {}", e))?;
-
+    files.add(".gitignore", content);
     Ok(())
 }
 
-fn create_library_structure(path: &Path, config: &StoffelConfig, _template: Option<&str>) -> Result<(), String> {
-    // Create lib structure
-    fs::create_dir_all(path.join("src")).map_err(|e| format!("Failed to create src directory: {}", e))?;
+/// A language ecosystem template selectable with `stoffel init -t`
+pub struct Template {
+    pub name: &'static str,
+    /// Whether the template is fully implemented or a development skeleton
+    pub status: &'static str,
+    pub description: &'static str,
+    /// Files the template generates besides Stoffel.toml, README.md, LICENSE and .gitignore
+    pub files: &'static [&'static str],
+    create: fn(&mut ProjectFiles, &StoffelConfig) -> Result<(), String>,
+    /// What `--lib` generates with this template, if it supports libraries
+    pub library: Option<LibraryLayout>,
+}
+
+/// A template's library skeleton
+pub struct LibraryLayout {
+    /// Files generated besides Stoffel.toml, README.md, LICENSE and .gitignore
+    pub files: &'static [&'static str],
+    create: fn(&mut ProjectFiles, &StoffelConfig) -> Result<(), String>,
+}
 
-    // Create lib.stfl
-    let lib_content = r#"# Stoffel Library
-# This library provides privacy-preserving computation functions
+/// Template used when `-t` isn't given
+pub const DEFAULT_TEMPLATE: &str = "stoffel";
+
+/// Every init template, in the order the interactive menu offers them
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "python",
+        status: "implemented",
+        description: "Python SDK integration for MPC applications",
+        files: &["pyproject.toml", "src/main.py", "src/secure_computation.stfl", "tests/test_main.py"],
+        create: create_python_project,
+        library: Some(LibraryLayout {
+            files: &["pyproject.toml", "src/<package>/__init__.py", "src/lib.stfl"],
+            create: create_python_library,
+        }),
+    },
+    Template {
+        name: "rust",
+        status: "skeleton",
+        description: "Rust FFI integration with StoffelVM",
+        files: &["Cargo.toml", "src/main.rs"],
+        create: create_rust_project,
+        library: Some(LibraryLayout {
+            files: &["Cargo.toml", "src/lib.rs", "src/lib.stfl"],
+            create: create_rust_library,
+        }),
+    },
+    Template {
+        name: "typescript",
+        status: "skeleton",
+        description: "TypeScript/Node.js MPC integration",
+        files: &["package.json", "tsconfig.json", "src/main.ts"],
+        create: create_typescript_project,
+        library: None,
+    },
+    Template {
+        name: "solidity",
+        status: "skeleton",
+        description: "Solidity smart contract with MPC integration",
+        files: &["hardhat.config.js", "package.json", "contracts/StoffelMPC.sol", "scripts/deploy.js"],
+        create: create_solidity_project,
+        library: None,
+    },
+    Template {
+        name: "go",
+        status: "skeleton",
+        description: "Go client integration for MPC applications",
+        files: &["go.mod", "main.go", "src/secure_computation.stfl"],
+        create: create_go_project,
+        library: None,
+    },
+    Template {
+        name: "java",
+        status: "skeleton",
+        description: "Java/Maven client integration for MPC applications",
+        files: &["pom.xml", "src/main/java/<package>/Main.java", "src/secure_computation.stfl"],
+        create: create_java_project,
+        library: None,
+    },
+    Template {
+        name: "stoffel",
+        status: "implemented",
+        description: "A Stoffel MPC application",
+        files: &["src/main.stfl", "tests/integration.stfl"],
+        create: create_stoffel_project,
+        library: Some(LibraryLayout {
+            files: &["src/lib.stfl"],
+            create: create_stoffel_library,
+        }),
+    },
+];
+
+/// Look up a template by name
+pub fn find_template(name: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|template| template.name == name)
+}
 
-# Example function for secure computation
-proc secure_add(a: secret int64, b: secret int64): secret int64 =
-  return a + b
+/// Look up a template, suggesting the closest name when it doesn't exist
+fn validate_template(name: &str) -> Result<&'static Template, String> {
+    if let Some(template) = find_template(name) {
+        return Ok(template);
+    }
 
-# Note: Export syntax is still under development
-# export { secure_add }
-"#;
-    fs::write(path.join("src").join("lib.stfl"), lib_content)
-        .map_err(|e| format!("Failed to write lib.stfl: {}", e))?;
+    let closest = TEMPLATES
+        .iter()
+        .map(|template| (edit_distance(name, template.name), template.name))
+        .min()
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(1));
+    match closest {
+        Some((_, candidate)) => Err(format!("unknown template '{}', did you mean '{}'?", name, candidate)),
+        None => Err(format!(
+            "unknown template '{}' (available: {})",
+            name,
+            TEMPLATES.iter().map(|template| template.name).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
 
-    // Create README for library
-    let readme_content = format!(r#"# {}
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
 
-A Stoffel MPC library for privacy-preserving computation.
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
 
-## Installation
+/// Print every template as `name status files`, one per line, followed by
+/// an indented `--lib` line for templates that have a library layout
+pub fn list_templates() {
+    for template in TEMPLATES {
+        println!("{:<12} {:<12} {}", template.name, template.status, template.files.join(" "));
+        if let Some(library) = &template.library {
+            println!("{:<12} {:<12} {}", "  --lib", "", library.files.join(" "));
+        }
+    }
+}
 
-```bash
-stoffel add {}
-```
+fn create_project_structure_full(files: &mut ProjectFiles, config: &StoffelConfig, template: Option<&str>) -> Result<(), String> {
+    let template = template.unwrap_or(DEFAULT_TEMPLATE);
 
-## Usage
+    match find_template(template) {
+        Some(found) => (found.create)(files, config)?,
+        None => create_stoffel_project(files, config)?,
+    }
 
-```stoffel
-import {{ secure_add }} from "{}";
+    // Create README for all templates
+    let readme_content = get_template_readme(config, template)?;
+    files.add("README.md", readme_content);
+    create_license_file(files, &config.package)?;
 
-let result = secure_add(secret_a, secret_b);
-```
+    Ok(())
+}
 
-## Configuration
+fn create_library_structure(files: &mut ProjectFiles, config: &StoffelConfig, template: Option<&str>) -> Result<(), String> {
+    let template = template.unwrap_or(DEFAULT_TEMPLATE);
+    let layout = find_template(template)
+        .and_then(|found| found.library.as_ref())
+        .ok_or_else(|| {
+            let supported: Vec<&str> = TEMPLATES
+                .iter()
+                .filter(|template| template.library.is_some())
+                .map(|template| template.name)
+                .collect();
+            format!("template '{}' has no library layout (libraries support: {})", template, supported.join(", "))
+        })?;
+    (layout.create)(files, config)?;
 
-- Protocol: {}
-- Parties: {}
-- Field: {}
-"#,
-        config.package.name,
-        config.package.name,
-        config.package.name,
-        config.mpc.protocol,
-        config.mpc.parties,
-        config.mpc.field
-    );
+    // Create README for library
+    let readme_template = load_template("stoffel", "README.lib.md")?;
+    let readme_content = substitute_template_vars(&readme_template, config)?;
 
-    fs::write(path.join("README.md"), readme_content)
-        .map_err(|e| format!("Failed to write README.md: {}", e))?;
+    files.add("README.md", readme_content);
+    create_license_file(files, &config.package)?;
 
     Ok(())
 }
@@ -305,14 +1059,107 @@ fn load_template(template_name: &str, file_name: &str) -> Result<String, String>
     match (template_name, file_name) {
         ("python", "main.py") => Ok(include_str!("templates/python/main.py").to_string()),
         ("python", "pyproject.toml") => Ok(include_str!("templates/python/pyproject.toml").to_string()),
+        ("python", "pyproject.lib.toml") => Ok(include_str!("templates/python/pyproject.lib.toml").to_string()),
+        ("python", "__init__.py") => Ok(include_str!("templates/python/__init__.py").to_string()),
         ("python", "secure_computation.stfl") => Ok(include_str!("templates/python/secure_computation.stfl").to_string()),
         ("python", "test_main.py") => Ok(include_str!("templates/python/test_main.py").to_string()),
+        // Not named Cargo.toml so cargo doesn't treat the directory as a separate package
+        ("rust", "Cargo.toml.template") => Ok(include_str!("templates/rust/Cargo.toml.template").to_string()),
+        ("rust", "main.rs") => Ok(include_str!("templates/rust/main.rs").to_string()),
+        ("rust", "lib.rs") => Ok(include_str!("templates/rust/lib.rs").to_string()),
+        ("rust", "Cargo.lib.toml.template") => Ok(include_str!("templates/rust/Cargo.lib.toml.template").to_string()),
+        ("typescript", "package.json") => Ok(include_str!("templates/typescript/package.json").to_string()),
+        ("typescript", "tsconfig.json") => Ok(include_str!("templates/typescript/tsconfig.json").to_string()),
+        ("typescript", "main.ts") => Ok(include_str!("templates/typescript/main.ts").to_string()),
+        ("solidity", "hardhat.config.js") => Ok(include_str!("templates/solidity/hardhat.config.js").to_string()),
+        ("solidity", "package.json") => Ok(include_str!("templates/solidity/package.json").to_string()),
+        ("solidity", "StoffelMPC.sol") => Ok(include_str!("templates/solidity/StoffelMPC.sol").to_string()),
+        ("solidity", "deploy.js") => Ok(include_str!("templates/solidity/deploy.js").to_string()),
+        ("go", "go.mod") => Ok(include_str!("templates/go/go.mod").to_string()),
+        ("go", "main.go") => Ok(include_str!("templates/go/main.go").to_string()),
+        ("java", "pom.xml") => Ok(include_str!("templates/java/pom.xml").to_string()),
+        ("java", "Main.java") => Ok(include_str!("templates/java/Main.java").to_string()),
+        ("stoffel", "main.stfl") => Ok(include_str!("templates/stoffel/main.stfl").to_string()),
+        ("stoffel", "integration.stfl") => Ok(include_str!("templates/stoffel/integration.stfl").to_string()),
+        ("stoffel", "lib.stfl") => Ok(include_str!("templates/stoffel/lib.stfl").to_string()),
+        ("stoffel", "README.lib.md") => Ok(include_str!("templates/stoffel/README.lib.md").to_string()),
+        ("stoffel", "gitignore") => Ok(include_str!("templates/stoffel/gitignore").to_string()),
+        ("python", "gitignore") => Ok(include_str!("templates/python/gitignore").to_string()),
+        ("rust", "gitignore") => Ok(include_str!("templates/rust/gitignore").to_string()),
+        ("typescript", "gitignore") => Ok(include_str!("templates/typescript/gitignore").to_string()),
+        ("solidity", "gitignore") => Ok(include_str!("templates/solidity/gitignore").to_string()),
+        ("go", "gitignore") => Ok(include_str!("templates/go/gitignore").to_string()),
+        ("java", "gitignore") => Ok(include_str!("templates/java/gitignore").to_string()),
+        ("licenses", "MIT") => Ok(include_str!("templates/licenses/MIT").to_string()),
+        ("licenses", "Apache-2.0") => Ok(include_str!("templates/licenses/Apache-2.0").to_string()),
         _ => Err(format!("Template file not found: {}/{}", template_name, file_name))
     }
 }
 
-fn substitute_template_vars(template_content: &str, config: &StoffelConfig) -> String {
-    template_content
+/// Add a LICENSE with the full text of the package's license
+///
+/// Licenses without a bundled text get a placeholder and a warning.
+fn create_license_file(files: &mut ProjectFiles, package: &PackageConfig) -> Result<(), String> {
+    let Some(license) = &package.license else {
+        return Ok(());
+    };
+
+    let holder = match &package.authors {
+        Some(authors) if !authors.is_empty() => authors.join(", "),
+        _ => format!("The {} Authors", package.name),
+    };
+
+    let content = if license.eq_ignore_ascii_case("MIT") {
+        load_template("licenses", "MIT")?
+    } else if license.eq_ignore_ascii_case("Apache-2.0") {
+        load_template("licenses", "Apache-2.0")?
+    } else {
+        eprintln!("{}  No built-in text for license '{}'; writing a placeholder LICENSE", style::prefix(Status::Warning), license);
+        format!(
+            "Copyright {{{{year}}}} {{{{copyright_holder}}}}\n\nThis project is licensed under {}.\nReplace this file with the full license text.\n",
+            license
+        )
+    };
+
+    files.add(
+        "LICENSE",
+        content
+            .replace("{{year}}", &current_year().to_string())
+            .replace("{{copyright_holder}}", &holder),
+    );
+    Ok(())
+}
+
+/// Current calendar year (UTC)
+fn current_year() -> u64 {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0);
+
+    // Convert days since 1970-01-01 to a civil date (March-based years)
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let year = year_of_era + era * 400;
+    if month_index >= 10 { year + 1 } else { year }
+}
+
+/// The configured threshold, or the one the protocol would calculate for the party count
+fn template_threshold(config: &StoffelConfig) -> Result<u8, String> {
+    Ok(crate::merge_mpc_layers(&[crate::config::base_layer(config)?])?.threshold)
+}
+
+/// Modulus of a known MPC field as a hex literal, or "0" for unrecognized fields
+fn field_modulus(field: &str) -> &'static str {
+    MpcField::from_str(field, true).map_or("0", |field| field.spec().modulus)
+}
+
+fn substitute_template_vars(template_content: &str, config: &StoffelConfig) -> Result<String, String> {
+    Ok(template_content
         .replace("{{package_name}}", &config.package.name)
         .replace("{{package_version}}", &config.package.version)
         .replace("{{package_description}}", config.package.description.as_deref().unwrap_or("Stoffel MPC application"))
@@ -322,821 +1169,300 @@ fn substitute_template_vars(template_content: &str, config: &StoffelConfig) -> S
         .replace("{{package_name_underscore}}", &config.package.name.replace("-", "_"))
         .replace("{{mpc_protocol}}", &config.mpc.protocol)
         .replace("{{mpc_parties}}", &config.mpc.parties.to_string())
+        .replace("{{mpc_field_modulus}}", field_modulus(&config.mpc.field))
         .replace("{{mpc_field}}", &config.mpc.field)
+        .replace("{{mpc_threshold}}", &template_threshold(config)?.to_string()))
 }
 
 // Language-specific project creators
-fn create_python_project(path: &Path, config: &StoffelConfig) -> Result<(), String> {
-    // Create Python project structure
-    fs::create_dir_all(path.join("src")).map_err(|e| format!("Failed to create src directory: {}", e))?;
-    fs::create_dir_all(path.join("tests")).map_err(|e| format!("Failed to create tests directory: {}", e))?;
-
+fn create_python_project(files: &mut ProjectFiles, config: &StoffelConfig) -> Result<(), String> {
     // Create pyproject.toml
     let pyproject_template = load_template("python", "pyproject.toml")?;
-    let pyproject_content = substitute_template_vars(&pyproject_template, config);
-    fs::write(path.join("pyproject.toml"), pyproject_content)
-        .map_err(|e| format!("Failed to write pyproject.toml: {}", e))?;
+    let pyproject_content = substitute_template_vars(&pyproject_template, config)?;
+    files.add("pyproject.toml", pyproject_content);
 
     // Create main Python file with actual SDK integration
     let main_py_template = load_template("python", "main.py")?;
-    let main_py_content = substitute_template_vars(&main_py_template, config);
-    fs::write(path.join("src").join("main.py"), main_py_content)
-        .map_err(|e| format!("Failed to write main.py: {}", e))?;
+    let main_py_content = substitute_template_vars(&main_py_template, config)?;
+    files.add("src/main.py", main_py_content);
 
     // Create StoffelLang program file
     let stfl_template = load_template("python", "secure_computation.stfl")?;
-    let stfl_content = substitute_template_vars(&stfl_template, config);
-    fs::write(path.join("src").join("secure_computation.stfl"), stfl_content)
-        .map_err(|e| format!("Failed to write secure_computation.stfl: {}", e))?;
+    let stfl_content = substitute_template_vars(&stfl_template, config)?;
+    files.add("src/secure_computation.stfl", stfl_content);
 
     // Create test file
     let test_template = load_template("python", "test_main.py")?;
-    let test_content = substitute_template_vars(&test_template, config);
-    fs::write(path.join("tests").join("test_main.py"), test_content)
-        .map_err(|e| format!("Failed to write test file: {}", e))?;
+    let test_content = substitute_template_vars(&test_template, config)?;
+    files.add("tests/test_main.py", test_content);
 
     Ok(())
 }
 
-fn create_rust_project(path: &Path, config: &StoffelConfig) -> Result<(), String> {
-    // Create Rust project structure
-    fs::create_dir_all(path.join("src")).map_err(|e| format!("Failed to create src directory: {}", e))?;
-
+fn create_rust_project(files: &mut ProjectFiles, config: &StoffelConfig) -> Result<(), String> {
     // Create Cargo.toml
-    let cargo_content = format!(r#"[package]
-name = "{}"
-version = "{}"
-edition = "2021"
-authors = [{}]
-description = "{}"
-
-[dependencies]
-# FFI bindings to StoffelVM
-libc = "0.2"
-# stoffel-vm-types = {{ path = "../StoffelVM/crates/stoffel-vm-types" }}
-# stoffel-vm = {{ path = "../StoffelVM/crates/stoffel-vm" }}
-
-[dev-dependencies]
-tokio = {{ version = "1.0", features = ["full"] }}
-"#,
-        config.package.name,
-        config.package.version,
-        config.package.authors.as_ref()
-            .map(|authors| authors.iter().map(|a| format!("\"{}\"", a)).collect::<Vec<_>>().join(", "))
-            .unwrap_or_else(|| "\"Unknown\"".to_string()),
-        config.package.description.as_deref().unwrap_or("Stoffel MPC application")
-    );
-
-    fs::write(path.join("Cargo.toml"), cargo_content)
-        .map_err(|e| format!("Failed to write Cargo.toml: {}", e))?;
+    let cargo_template = load_template("rust", "Cargo.toml.template")?;
+    let cargo_content = substitute_template_vars(&cargo_template, config)?;
+    files.add("Cargo.toml", cargo_content);
 
     // Create main.rs with FFI skeleton - simplified version
-    let main_rs_content = format!(r#"//! {} - {}
-//! Generated by Stoffel CLI
-//!
-//! Rust FFI integration with StoffelVM for MPC computation
-//! Protocol: {}, Parties: {}, Field: {}
-
-// TODO: Uncomment when StoffelVM crates are available
-// use stoffel_vm::core_vm::VirtualMachine;
-// use stoffel_vm::functions::VMFunction;
-// use stoffel_vm::instructions::Instruction;
-// use stoffel_vm::core_types::Value;
-use std::collections::HashMap;
-
-/// Main MPC computation using Rust FFI to StoffelVM
-fn main() -> Result<(), String> {{
-    println!("=== Stoffel Rust MPC Demo ===");
-    println!("Protocol: honeybadger");
-    println!("Parties: {}", {});
-    println!("Field: bls12-381");
-
-    // TODO: Implement StoffelVM integration
-    println!("Rust FFI integration with StoffelVM coming soon!");
+    let main_rs_template = load_template("rust", "main.rs")?;
+    let main_rs_content = substitute_template_vars(&main_rs_template, config)?;
+    files.add("src/main.rs", main_rs_content);
 
     Ok(())
-}}
-
-#[cfg(test)]
-mod tests {{
-    use super::*;
-
-    #[test]
-    fn test_basic() {{
-        assert!(main().is_ok());
-    }}
-}}
-"#,
-        config.package.name,
-        config.package.description.as_deref().unwrap_or("Stoffel MPC application"),
-        config.mpc.protocol,
-        config.mpc.parties,
-        config.mpc.field,
-        config.mpc.parties,
-        config.mpc.parties
-    );
-
-    fs::write(path.join("src").join("main.rs"), main_rs_content)
-        .map_err(|e| format!("Failed to write main.rs: {}", e))?;
+}
 
+/// The StoffelLang side of every library
+fn create_stoffel_library(files: &mut ProjectFiles, _config: &StoffelConfig) -> Result<(), String> {
+    let lib_content = load_template("stoffel", "lib.stfl")?;
+    files.add("src/lib.stfl", lib_content);
     Ok(())
 }
 
-fn create_typescript_project(path: &Path, config: &StoffelConfig) -> Result<(), String> {
-    // Create TypeScript project structure
-    fs::create_dir_all(path.join("src")).map_err(|e| format!("Failed to create src directory: {}", e))?;
+/// A StoffelLang library shipped as a Python package
+fn create_python_library(files: &mut ProjectFiles, config: &StoffelConfig) -> Result<(), String> {
+    create_stoffel_library(files, config)?;
 
-    // Create package.json
-    let package_json = format!(r#"{{
-  "name": "{}",
-  "version": "{}",
-  "description": "{}",
-  "main": "dist/main.js",
-  "scripts": {{
-    "build": "tsc",
-    "start": "node dist/main.js",
-    "dev": "ts-node src/main.ts",
-    "test": "jest"
-  }},
-  "dependencies": {{
-    "@stoffel/sdk": "file:../stoffel-typescript-sdk"
-  }},
-  "devDependencies": {{
-    "@types/node": "^20.0.0",
-    "typescript": "^5.0.0",
-    "ts-node": "^10.9.0",
-    "jest": "^29.0.0",
-    "@types/jest": "^29.0.0"
-  }},
-  "keywords": ["mpc", "privacy", "secure-computation", "stoffel"],
-  "author": "{}",
-  "license": "MIT"
-}}
-"#,
-        config.package.name,
-        config.package.version,
-        config.package.description.as_deref().unwrap_or("Stoffel MPC application"),
-        config.package.authors.as_ref().and_then(|a| a.first()).unwrap_or(&"Unknown".to_string())
-    );
+    let pyproject_template = load_template("python", "pyproject.lib.toml")?;
+    files.add("pyproject.toml", substitute_template_vars(&pyproject_template, config)?);
 
-    fs::write(path.join("package.json"), package_json)
-        .map_err(|e| format!("Failed to write package.json: {}", e))?;
+    // The package directory must match the module name the templates import
+    let init_template = load_template("python", "__init__.py")?;
+    let init_path = substitute_template_vars("src/{{package_name_underscore}}/__init__.py", config)?;
+    files.add(init_path, substitute_template_vars(&init_template, config)?);
 
-    // Create tsconfig.json
-    let tsconfig = r#"{
-  "compilerOptions": {
-    "target": "ES2020",
-    "module": "commonjs",
-    "outDir": "./dist",
-    "rootDir": "./src",
-    "strict": true,
-    "esModuleInterop": true,
-    "skipLibCheck": true,
-    "forceConsistentCasingInFileNames": true
-  }
-}
-"#;
-    fs::write(path.join("tsconfig.json"), tsconfig)
-        .map_err(|e| format!("Failed to write tsconfig.json: {}", e))?;
+    Ok(())
+}
 
-    // Create main.ts with SDK skeleton
-    let main_ts_content = format!(r#"/**
- * {} - {}
- * Generated by Stoffel CLI
- *
- * TypeScript/Node.js integration with Stoffel MPC framework
- * Protocol: {}, Parties: {}, Field: {}
- */
-
-// TODO: Import actual Stoffel TypeScript SDK when available
-// import {{ StoffelClient, StoffelProgram }} from '@stoffel/sdk';
-
-interface StoffelConfig {{
-    nodes: string[];
-    clientId: string;
-    programId: string;
-    protocol: string;
-    parties: number;
-    field: string;
-}}
-
-interface SecretInputs {{
-    [key: string]: number | string | boolean;
-}}
-
-interface PublicInputs {{
-    [key: string]: number | string | boolean;
-}}
-
-/**
- * Stoffel MPC Client (Skeleton Implementation)
- * TODO: Replace with actual SDK import
- */
-class StoffelClient {{
-    private config: StoffelConfig;
-    private connected: boolean = false;
-
-    constructor(config: StoffelConfig) {{
-        this.config = config;
-        console.log(`Initialized Stoffel client for ${{config.parties}} parties`);
-    }}
-
-    async connect(): Promise<void> {{
-        console.log('Connecting to MPC network...');
-        // TODO: Implement actual connection logic
-        this.connected = true;
-        console.log('✓ Connected to MPC network');
-    }}
-
-    async executeWithInputs(
-        secretInputs: SecretInputs,
-        publicInputs?: PublicInputs
-    ): Promise<any> {{
-        console.log('🔒 Executing secure computation...');
-        console.log(`Secret inputs: ${{Object.keys(secretInputs).length}} values`);
-        if (publicInputs) {{
-            console.log(`Public inputs: ${{Object.keys(publicInputs).length}} values`);
-        }}
-
-        // TODO: Implement actual MPC execution
-        // For now, return mock result
-        return {{
-            result: 67, // Mock computation result
-            protocol: this.config.protocol,
-            parties: this.config.parties
-        }};
-    }}
-
-    async disconnect(): Promise<void> {{
-        console.log('Disconnecting from MPC network...');
-        this.connected = false;
-        console.log('✓ Disconnected');
-    }}
-
-    isConnected(): boolean {{
-        return this.connected;
-    }}
-}}
-
-/**
- * Main MPC demonstration
- */
-async function main(): Promise<void> {{
-    console.log('=== Stoffel TypeScript MPC Demo ===\\n');
-
-    // 1. Configure MPC client
-    console.log('1. Setting up MPC client...');
-    const client = new StoffelClient({{
-        nodes: [
-            'http://localhost:9001',
-            'http://localhost:9002',
-            'http://localhost:9003',
-            'http://localhost:9004',
-            'http://localhost:9005'
-        ],
-        clientId: '{}',
-        programId: 'secure_computation',
-        protocol: '{}',
-        parties: {},
-        field: '{}'
-    }});
-
-    // 2. Connect to MPC network
-    await client.connect();
-
-    // 3. Execute secure computation
-    console.log('\\n2. Executing secure computation...');
-    const result = await client.executeWithInputs(
-        {{
-            secretValue1: 42,
-            secretValue2: 25
-        }},
-        {{
-            threshold: 50,
-            operation: 'add'
-        }}
-    );
+/// A StoffelLang library wrapped in a cdylib crate that embeds its source
+fn create_rust_library(files: &mut ProjectFiles, config: &StoffelConfig) -> Result<(), String> {
+    create_stoffel_library(files, config)?;
 
-    console.log(`📊 Computation result: ${{result.result}}`);
-    console.log(`Protocol: ${{result.protocol}}, Parties: ${{result.parties}}`);
-
-    // 4. Healthcare analytics example
-    await healthcareAnalyticsExample(client);
-
-    // 5. Clean up
-    await client.disconnect();
-    console.log('\\n=== Demo Complete ===');
-}}
-
-/**
- * Example: Privacy-preserving healthcare analytics
- */
-async function healthcareAnalyticsExample(client: StoffelClient): Promise<void> {{
-    console.log('\\n3. Healthcare Analytics Example...');
-
-    const result = await client.executeWithInputs(
-        {{
-            patientAges: [25, 34, 45, 67, 23, 56],
-            conditions: [0, 1, 0, 1, 0, 1]
-        }},
-        {{
-            analysisType: 'prevalence_study',
-            minAge: 18,
-            maxAge: 80
-        }}
-    );
+    let cargo_template = load_template("rust", "Cargo.lib.toml.template")?;
+    files.add("Cargo.toml", substitute_template_vars(&cargo_template, config)?);
 
-    console.log('📈 Healthcare analytics (privacy-preserving):');
-    console.log('   Individual patient data remains private');
-    console.log(`   Aggregate statistics: ${{result.result}}`);
-}}
-
-/**
- * Financial risk assessment example
- */
-async function financialRiskExample(): Promise<void> {{
-    console.log('\\n=== Financial Risk Assessment ===');
-
-    const client = new StoffelClient({{
-        nodes: ['http://localhost:9001', 'http://localhost:9002', 'http://localhost:9003',
-                'http://localhost:9004', 'http://localhost:9005'],
-        clientId: 'financial_client',
-        programId: 'risk_assessment',
-        protocol: '{}',
-        parties: {},
-        field: '{}'
-    }});
-
-    await client.connect();
-
-    const result = await client.executeWithInputs(
-        {{
-            portfolioValues: [100000, 250000, 75000],
-            riskFactors: [0.1, 0.05, 0.15]
-        }},
-        {{
-            marketCondition: 'volatile',
-            regulatoryFactor: 1.2
-        }}
-    );
+    let lib_rs_template = load_template("rust", "lib.rs")?;
+    files.add("src/lib.rs", substitute_template_vars(&lib_rs_template, config)?);
 
-    console.log(`💰 Risk assessment: ${{result.result}}`);
-    await client.disconnect();
-}}
+    Ok(())
+}
 
-// Run the examples
-if (require.main === module) {{
-    main().catch(console.error);
-}}
+fn create_typescript_project(files: &mut ProjectFiles, config: &StoffelConfig) -> Result<(), String> {
+    // Create package.json
+    let package_json_template = load_template("typescript", "package.json")?
+        .replace("{{package_author}}", config.package.authors.as_ref().and_then(|a| a.first()).map(String::as_str).unwrap_or("Unknown"));
+    let package_json = substitute_template_vars(&package_json_template, config)?;
+    files.add("package.json", package_json);
 
-export {{ StoffelClient, main, healthcareAnalyticsExample, financialRiskExample }};
-"#,
-        config.package.name,
-        config.package.description.as_deref().unwrap_or("Stoffel MPC application"),
-        config.mpc.protocol,
-        config.mpc.parties,
-        config.mpc.field,
-        config.package.name.replace("-", "_"),
-        config.mpc.protocol,
-        config.mpc.parties,
-        config.mpc.field,
-        config.mpc.protocol,
-        config.mpc.parties,
-        config.mpc.field
-    );
+    // Create tsconfig.json
+    let tsconfig = load_template("typescript", "tsconfig.json")?;
+    files.add("tsconfig.json", tsconfig);
 
-    fs::write(path.join("src").join("main.ts"), main_ts_content)
-        .map_err(|e| format!("Failed to write main.ts: {}", e))?;
+    // Create main.ts with SDK skeleton
+    let main_ts_template = load_template("typescript", "main.ts")?;
+    let main_ts_content = substitute_template_vars(&main_ts_template, config)?;
+    files.add("src/main.ts", main_ts_content);
 
     Ok(())
 }
 
-fn create_solidity_project(path: &Path, config: &StoffelConfig) -> Result<(), String> {
-    // Create Solidity project structure
-    fs::create_dir_all(path.join("contracts")).map_err(|e| format!("Failed to create contracts directory: {}", e))?;
-    fs::create_dir_all(path.join("scripts")).map_err(|e| format!("Failed to create scripts directory: {}", e))?;
-    fs::create_dir_all(path.join("test")).map_err(|e| format!("Failed to create test directory: {}", e))?;
+fn create_solidity_project(files: &mut ProjectFiles, config: &StoffelConfig) -> Result<(), String> {
+    files.add_dir("test");
 
     // Create hardhat.config.js
-    let hardhat_config = r#"require("@nomicfoundation/hardhat-toolbox");
-
-/** @type import('hardhat/config').HardhatUserConfig */
-module.exports = {
-  solidity: "0.8.20",
-  networks: {
-    hardhat: {},
-    // Add Stoffel MPC network configuration here
-    stoffel: {
-      url: "http://localhost:8545",
-      accounts: []
-    }
-  }
-};
-"#;
-    fs::write(path.join("hardhat.config.js"), hardhat_config)
-        .map_err(|e| format!("Failed to write hardhat.config.js: {}", e))?;
+    let hardhat_config = load_template("solidity", "hardhat.config.js")?;
+    files.add("hardhat.config.js", hardhat_config);
 
     // Create package.json for Solidity project
-    let package_json = format!(r#"{{
-  "name": "{}",
-  "version": "{}",
-  "description": "{}",
-  "scripts": {{
-    "compile": "hardhat compile",
-    "test": "hardhat test",
-    "deploy": "hardhat run scripts/deploy.js"
-  }},
-  "devDependencies": {{
-    "@nomicfoundation/hardhat-toolbox": "^3.0.0",
-    "hardhat": "^2.17.0"
-  }},
-  "keywords": ["solidity", "mpc", "privacy", "smart-contracts", "stoffel"]
-}}
-"#,
-        config.package.name,
-        config.package.version,
-        config.package.description.as_deref().unwrap_or("Stoffel MPC smart contract")
-    );
-
-    fs::write(path.join("package.json"), package_json)
-        .map_err(|e| format!("Failed to write package.json: {}", e))?;
+    let package_json_template = load_template("solidity", "package.json")?
+        .replace("{{package_description}}", config.package.description.as_deref().unwrap_or("Stoffel MPC smart contract"));
+    let package_json = substitute_template_vars(&package_json_template, config)?;
+    files.add("package.json", package_json);
 
     // Create main Solidity contract
-    let contract_content = format!(r#"// SPDX-License-Identifier: MIT
-pragma solidity ^0.8.20;
-
-/**
- * {} - {}
- * Generated by Stoffel CLI
- *
- * Solidity smart contract with MPC integration
- * Protocol: {}, Parties: {}, Field: {}
- */
-
-/// @title StoffelMPC
-/// @dev Smart contract interface for Stoffel MPC computations
-/// @notice This contract provides on-chain verification of MPC results
-contract StoffelMPC {{
-
-    struct MPCConfig {{
-        string protocol;
-        uint8 parties;
-        uint8 threshold;
-        string field;
-    }}
-
-    struct ComputationResult {{
-        bytes32 commitmentHash;
-        uint256 result;
-        uint256 timestamp;
-        bool verified;
-    }}
-
-    MPCConfig public mpcConfig;
-    mapping(bytes32 => ComputationResult) public computationResults;
-    mapping(address => bool) public authorizedNodes;
-
-    event ComputationSubmitted(bytes32 indexed computationId, uint256 result);
-    event ComputationVerified(bytes32 indexed computationId, bool success);
-    event NodeAuthorized(address indexed node);
-
-    modifier onlyAuthorizedNode() {{
-        require(authorizedNodes[msg.sender], "Only authorized MPC nodes can submit results");
-        _;
-    }}
-
-    constructor() {{
-        mpcConfig = MPCConfig({{
-            protocol: "{}",
-            parties: {},
-            threshold: {},
-            field: "{}"
-        }});
-
-        // TODO: Initialize with actual MPC node addresses
-        // For now, authorize the deployer
-        authorizedNodes[msg.sender] = true;
-    }}
-
-    /// @notice Submit MPC computation result with proof
-    /// @param computationId Unique identifier for the computation
-    /// @param result The computed result from MPC
-    /// @param proof Zero-knowledge proof of correct computation (placeholder)
-    function submitMPCResult(
-        bytes32 computationId,
-        uint256 result,
-        bytes calldata proof
-    ) external onlyAuthorizedNode {{
-        require(computationResults[computationId].timestamp == 0, "Computation already exists");
-
-        // TODO: Verify the MPC proof
-        bool isValid = verifyMPCProof(result, proof);
-
-        computationResults[computationId] = ComputationResult({{
-            commitmentHash: keccak256(abi.encodePacked(result, proof)),
-            result: result,
-            timestamp: block.timestamp,
-            verified: isValid
-        }});
-
-        emit ComputationSubmitted(computationId, result);
-
-        if (isValid) {{
-            emit ComputationVerified(computationId, true);
-        }}
-    }}
-
-    /// @notice Verify MPC computation proof (placeholder implementation)
-    /// @param result The computation result
-    /// @param proof The zero-knowledge proof
-    /// @return bool Whether the proof is valid
-    function verifyMPCProof(uint256 result, bytes calldata proof) internal pure returns (bool) {{
-        // TODO: Implement actual proof verification
-        // For now, basic sanity check
-        return proof.length > 0 && result > 0;
-    }}
-
-    /// @notice Get computation result if verified
-    /// @param computationId The computation identifier
-    /// @return result The verified computation result
-    function getVerifiedResult(bytes32 computationId) external view returns (uint256) {{
-        ComputationResult memory comp = computationResults[computationId];
-        require(comp.verified, "Computation not verified");
-        return comp.result;
-    }}
-
-    /// @notice Authorize MPC node to submit results
-    /// @param node Address of the MPC node
-    function authorizeNode(address node) external {{
-        // TODO: Add proper access control (e.g., Ownable)
-        authorizedNodes[node] = true;
-        emit NodeAuthorized(node);
-    }}
-
-    /// @notice Healthcare analytics with privacy preservation
-    /// @param commitmentHash Hash commitment to private patient data
-    /// @param aggregateResult Computed aggregate statistics (no individual data)
-    function submitHealthcareAnalytics(
-        bytes32 commitmentHash,
-        uint256 aggregateResult
-    ) external onlyAuthorizedNode {{
-        bytes32 computationId = keccak256(abi.encodePacked("healthcare", block.timestamp));
-
-        computationResults[computationId] = ComputationResult({{
-            commitmentHash: commitmentHash,
-            result: aggregateResult,
-            timestamp: block.timestamp,
-            verified: true  // Assume verified for this example
-        }});
-
-        emit ComputationSubmitted(computationId, aggregateResult);
-    }}
-
-    /// @notice Financial risk assessment with MPC
-    /// @param riskScore Aggregate risk score (no individual portfolio data revealed)
-    function submitRiskAssessment(uint256 riskScore) external onlyAuthorizedNode {{
-        bytes32 computationId = keccak256(abi.encodePacked("risk", block.timestamp));
-
-        computationResults[computationId] = ComputationResult({{
-            commitmentHash: keccak256(abi.encodePacked(riskScore, msg.sender)),
-            result: riskScore,
-            timestamp: block.timestamp,
-            verified: true
-        }});
-
-        emit ComputationSubmitted(computationId, riskScore);
-    }}
-}}
-
-/// @title Private Auction Contract
-/// @dev Demonstrates MPC integration for private auctions
-contract PrivateAuction {{
-    struct Auction {{
-        bytes32 auctionId;
-        uint256 startTime;
-        uint256 endTime;
-        uint256 winningBid;
-        address winner;
-        bool finalized;
-    }}
-
-    mapping(bytes32 => Auction) public auctions;
-    mapping(bytes32 => mapping(address => bytes32)) public bidCommitments;
-
-    event AuctionCreated(bytes32 indexed auctionId);
-    event BidCommitted(bytes32 indexed auctionId, address bidder);
-    event AuctionFinalized(bytes32 indexed auctionId, address winner, uint256 winningBid);
-
-    /// @notice Commit to a sealed bid (commitment phase)
-    function commitBid(bytes32 auctionId, bytes32 commitment) external {{
-        require(block.timestamp < auctions[auctionId].endTime, "Auction ended");
-        bidCommitments[auctionId][msg.sender] = commitment;
-        emit BidCommitted(auctionId, msg.sender);
-    }}
-
-    /// @notice Finalize auction with MPC-computed winner
-    /// @param auctionId The auction identifier
-    /// @param winner Address of the winning bidder
-    /// @param winningBid The winning bid amount (revealed via MPC)
-    function finalizeAuction(
-        bytes32 auctionId,
-        address winner,
-        uint256 winningBid
-    ) external {{
-        Auction storage auction = auctions[auctionId];
-        require(block.timestamp >= auction.endTime, "Auction still active");
-        require(!auction.finalized, "Already finalized");
-
-        // TODO: Verify MPC proof that winner has highest bid
-        // For now, trust the MPC computation result
-
-        auction.winner = winner;
-        auction.winningBid = winningBid;
-        auction.finalized = true;
-
-        emit AuctionFinalized(auctionId, winner, winningBid);
-    }}
-}}
-"#,
-        config.package.name,
-        config.package.description.as_deref().unwrap_or("Stoffel MPC smart contract"),
-        config.mpc.protocol,
-        config.mpc.parties,
-        config.mpc.field,
-        config.mpc.protocol,
-        config.mpc.parties,
-        config.mpc.threshold.unwrap_or(1),
-        config.mpc.field
-    );
-
-    fs::write(path.join("contracts").join("StoffelMPC.sol"), contract_content)
-        .map_err(|e| format!("Failed to write StoffelMPC.sol: {}", e))?;
+    let contract_template = load_template("solidity", "StoffelMPC.sol")?
+        .replace("{{package_description}}", config.package.description.as_deref().unwrap_or("Stoffel MPC smart contract"));
+    let contract_content = substitute_template_vars(&contract_template, config)?;
+    files.add("contracts/StoffelMPC.sol", contract_content);
 
     // Create deployment script
-    let deploy_script = r#"// Deploy script for Stoffel MPC contracts
-const hre = require("hardhat");
+    let deploy_script = load_template("solidity", "deploy.js")?;
+    files.add("scripts/deploy.js", deploy_script);
 
-async function main() {
-  console.log("Deploying Stoffel MPC contracts...");
+    Ok(())
+}
 
-  const StoffelMPC = await hre.ethers.getContractFactory("StoffelMPC");
-  const stoffelMPC = await StoffelMPC.deploy();
+fn create_go_project(files: &mut ProjectFiles, config: &StoffelConfig) -> Result<(), String> {
+    // Create go.mod
+    let go_mod_template = load_template("go", "go.mod")?;
+    let go_mod = substitute_template_vars(&go_mod_template, config)?;
+    files.add("go.mod", go_mod);
 
-  await stoffelMPC.deployed();
-  console.log("StoffelMPC deployed to:", stoffelMPC.address);
+    // Create main.go with a client skeleton wired to the project's MPC config
+    let main_go_template = load_template("go", "main.go")?;
+    let main_go = substitute_template_vars(&main_go_template, config)?;
+    files.add("main.go", main_go);
 
-  const PrivateAuction = await hre.ethers.getContractFactory("PrivateAuction");
-  const privateAuction = await PrivateAuction.deploy();
+    // Create StoffelLang program file (shared with the Python template)
+    let stfl_template = load_template("python", "secure_computation.stfl")?;
+    let stfl_content = substitute_template_vars(&stfl_template, config)?;
+    files.add("src/secure_computation.stfl", stfl_content);
 
-  await privateAuction.deployed();
-  console.log("PrivateAuction deployed to:", privateAuction.address);
+    Ok(())
 }
 
-main()
-  .then(() => process.exit(0))
-  .catch((error) => {
-    console.error(error);
-    process.exit(1);
-  });
-"#;
+/// Java package name for a Stoffel package name, e.g. "my-app" -> "com.example.my_app"
+fn java_package_name(name: &str) -> String {
+    let mut segment: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !segment.starts_with(|c: char| c.is_ascii_lowercase()) {
+        segment.insert(0, '_');
+    }
+    format!("com.example.{}", segment)
+}
 
-    fs::write(path.join("scripts").join("deploy.js"), deploy_script)
-        .map_err(|e| format!("Failed to write deploy.js: {}", e))?;
+fn create_java_project(files: &mut ProjectFiles, config: &StoffelConfig) -> Result<(), String> {
+    let java_package = java_package_name(&config.package.name);
+
+    // Create pom.xml
+    let pom_template = load_template("java", "pom.xml")?
+        .replace("{{java_package}}", &java_package);
+    let pom = substitute_template_vars(&pom_template, config)?;
+    files.add("pom.xml", pom);
+
+    // Create Main.java with a client skeleton wired to the project's MPC config
+    let main_java_template = load_template("java", "Main.java")?
+        .replace("{{java_package}}", &java_package);
+    let main_java = substitute_template_vars(&main_java_template, config)?;
+    files.add(
+        format!("src/main/java/{}/Main.java", java_package.replace('.', "/")),
+        main_java,
+    );
+
+    // Create StoffelLang program file (shared with the Python template)
+    let stfl_template = load_template("python", "secure_computation.stfl")?;
+    let stfl_content = substitute_template_vars(&stfl_template, config)?;
+    files.add("src/secure_computation.stfl", stfl_content);
 
     Ok(())
 }
 
-fn create_stoffel_project(path: &Path, config: &StoffelConfig) -> Result<(), String> {
-    // Create directories
-    fs::create_dir_all(path.join("src")).map_err(|e| format!("Failed to create src directory: {}", e))?;
-    fs::create_dir_all(path.join("tests")).map_err(|e| format!("Failed to create tests directory: {}", e))?;
-
+fn create_stoffel_project(files: &mut ProjectFiles, config: &StoffelConfig) -> Result<(), String> {
     // Create main.stfl (Pure StoffelLang)
-    let main_content = format!(r#"# {} - {}
-# Generated by Stoffel CLI
-# Protocol: {}, Parties: {}, Field: {}
-#
-# TODO: Update this example when StoffelLang frontend has stabilized
-# Current syntax is based on test files and may change
-
-# Demonstration of StoffelLang MPC features
-proc secure_computation(x: secret int64, y: secret int64): secret int64 =
-  # Secret arithmetic operations
-  let sum = x + y
-  let difference = x - y
-  let product = x * y
-
-  # Mix of public and secret computations
-  let public_factor: int64 = 3
-  let scaled_sum = sum * public_factor
-
-  # Return a combination result
-  return scaled_sum + product
-
-# Main entry point
-proc main() =
-  # Example secret inputs (in real MPC, these would come from different parties)
-  let input_a: secret int64 = 15
-  let input_b: secret int64 = 25
-
-  # Perform secure computation
-  let result = secure_computation(input_a, input_b)
-
-  # In a real application, you might reveal the result or use it in further computations
-  discard result
-"#,
-        config.package.name,
-        config.package.description.as_deref().unwrap_or("Stoffel MPC application"),
-        config.mpc.protocol,
-        config.mpc.parties,
-        config.mpc.field
-    );
-
-    fs::write(path.join("src").join("main.stfl"), main_content)
-        .map_err(|e| format!("Failed to write main.stfl: {}", e))?;
+    let main_template = load_template("stoffel", "main.stfl")?;
+    let main_content = substitute_template_vars(&main_template, config)?;
+    files.add("src/main.stfl", main_content);
 
     // Create test file
-    let test_content = r#"# Integration tests for StoffelLang MPC
-#
-# This file contains basic tests for StoffelLang functionality
-
-# Define a simple secure computation function for testing
-proc secure_computation(x: secret int64, y: secret int64): secret int64 =
-  let sum = x + y
-  let product = x * y
-  let result = sum + product
-  return result
-
-# Test the secure computation function
-proc test_secure_computation() =
-  let x: secret int64 = 10
-  let y: secret int64 = 5
-  let result = secure_computation(x, y)
-  discard result
-  print("Secure computation test completed")
-
-# Test with different values
-proc test_computation_variants() =
-  let a: secret int64 = 20
-  let b: secret int64 = 3
-  let output = secure_computation(a, b)
-  discard output
-  print("Computation variant test completed")
-
-# Run all tests
-proc run_tests() =
-  print("Starting StoffelLang tests")
-  test_secure_computation()
-  test_computation_variants()
-  print("All tests completed")
-"#;
-
-    fs::write(path.join("tests").join("integration.stfl"), test_content)
-        .map_err(|e| format!("Failed to write test file: {}", e))?;
+    let test_content = load_template("stoffel", "integration.stfl")?;
+    files.add("tests/integration.stfl", test_content);
 
     Ok(())
 }
 
 // Helper functions
-fn prompt_with_default(prompt: &str, default: &str) -> Result<String, String> {
-    print!("{} [{}]: ", prompt, default);
-    io::stdout().flush().map_err(|e| format!("IO error: {}", e))?;
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).map_err(|e| format!("IO error: {}", e))?;
+/// Reads answers to interactive prompts, from stdin or from canned input in tests
+///
+/// Prompts go to stdout; an answer is one line of input, and end of input
+/// counts as an empty answer so defaults apply.
+struct Prompter<R> {
+    input: R,
+}
 
-    let input = input.trim();
-    Ok(if input.is_empty() { default.to_string() } else { input.to_string() })
+impl Prompter<io::StdinLock<'static>> {
+    fn stdin() -> Self {
+        Prompter { input: io::stdin().lock() }
+    }
 }
 
-fn prompt_optional(prompt: &str) -> Result<String, String> {
-    print!("{}: ", prompt);
-    io::stdout().flush().map_err(|e| format!("IO error: {}", e))?;
+impl<R: BufRead> Prompter<R> {
+    fn answer(&mut self, prompt: &str) -> Result<String, String> {
+        print!("{}: ", prompt);
+        io::stdout().flush().map_err(|e| format!("IO error: {}", e))?;
+
+        let mut input = String::new();
+        self.input.read_line(&mut input).map_err(|e| format!("IO error: {}", e))?;
+        Ok(input.trim().to_string())
+    }
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).map_err(|e| format!("IO error: {}", e))?;
+    fn with_default(&mut self, prompt: &str, default: &str) -> Result<String, String> {
+        let input = self.answer(&format!("{} [{}]", prompt, default))?;
+        Ok(if input.is_empty() { default.to_string() } else { input })
+    }
 
-    Ok(input.trim().to_string())
+    fn optional(&mut self, prompt: &str) -> Result<String, String> {
+        self.answer(prompt)
+    }
+
+    fn confirm(&mut self, prompt: &str, default: bool) -> Result<bool, String> {
+        let hint = if default { "Y/n" } else { "y/N" };
+        loop {
+            let response = self.answer(&format!("{} [{}]", prompt, hint))?;
+            match response.to_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => println!("Please answer 'y' or 'n'."),
+            }
+        }
+    }
+
+    fn parsed<T>(&mut self, prompt: &str, default: T) -> Result<T, String>
+    where
+        T: std::str::FromStr + std::fmt::Display + Copy,
+        T::Err: std::fmt::Display,
+    {
+        let response = self.with_default(prompt, &default.to_string())?;
+        response.parse().map_err(|e| format!("Invalid input: {}", e))
+    }
+
+    /// Ask for the author's email, offering `default`; `-` or an empty answer without a default skips it
+    fn author_email(&mut self, default: Option<String>) -> Result<Option<String>, String> {
+        let email = match default {
+            Some(default) => self.with_default("Author email (- to skip)", &default)?,
+            None => self.optional("Author email (optional)")?,
+        };
+        Ok(Some(email).filter(|email| !email.is_empty() && email != "-"))
+    }
 }
 
-fn prompt_with_default_parsed<T: std::str::FromStr>(prompt: &str, default: T) -> Result<T, String>
-where
-    T: std::fmt::Display + Copy,
-    T::Err: std::fmt::Display,
-{
-    let response = prompt_with_default(prompt, &default.to_string())?;
-    response.parse().map_err(|e| format!("Invalid input: {}", e))
+/// Ask a yes/no question on stdin
+pub fn prompt_confirm(prompt: &str, default: bool) -> Result<bool, String> {
+    Prompter::stdin().confirm(prompt, default)
+}
+
+/// An author entry in the usual `Name <email>` form, or just the name without an email
+fn format_author(name: &str, email: Option<&str>) -> String {
+    match email {
+        Some(email) => format!("{} <{}>", name, email),
+        None => name.to_string(),
+    }
 }
 
 fn get_git_user() -> Option<String> {
+    git_config("user.name")
+}
+
+fn get_git_email() -> Option<String> {
+    git_config("user.email")
+}
+
+/// A value from the user's git configuration, if set
+fn git_config(key: &str) -> Option<String> {
     std::process::Command::new("git")
-        .args(&["config", "user.name"])
+        .args(["config", key])
         .output()
         .ok()
         .and_then(|output| {
             if output.status.success() {
                 String::from_utf8(output.stdout).ok()
                     .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
             } else {
                 None
             }
@@ -1144,17 +1470,13 @@ fn get_git_user() -> Option<String> {
 }
 
 fn get_template_description(template: &str) -> String {
-    match template {
-        "python" => "Python SDK integration for MPC applications".to_string(),
-        "rust" => "Rust FFI integration with StoffelVM".to_string(),
-        "typescript" => "TypeScript/Node.js MPC integration".to_string(),
-        "solidity" => "Solidity smart contract with MPC integration".to_string(),
-        _ => "A Stoffel MPC application".to_string(),
-    }
+    find_template(template)
+        .map_or("A Stoffel MPC application", |template| template.description)
+        .to_string()
 }
 
 
-fn get_template_readme(config: &StoffelConfig, template: &str) -> String {
+fn get_template_readme(config: &StoffelConfig, template: &str) -> Result<String, String> {
     let (quickstart, additional_info) = match template {
         "python" => (
             r#"```bash
@@ -1285,6 +1607,65 @@ This project provides on-chain verification of MPC computations:
 - Hardhat development environment
 - Solidity 0.8.20
 - OpenZeppelin contracts"#
+        ),
+        "go" => (
+            r#"```bash
+# Compile the StoffelLang program
+stoffel compile src/secure_computation.stfl
+
+# Run the MPC demo
+go run .
+
+# Run tests
+go test ./...
+
+# Build a binary
+go build -o bin/app .
+```"#,
+            r#"## Go Client Integration
+
+This project provides a Go client for Stoffel MPC:
+
+- **MPCConfig**: The project's MPC settings mirrored as a Go struct
+- **StoffelLang Program**: `src/secure_computation.stfl` holds the secure computation
+- **Standard Tooling**: Plain Go modules, no extra build tooling required
+
+## Dependencies
+
+- Go 1.21+
+- Stoffel Go SDK (when available)
+
+Note: This template currently contains skeleton code. Full Go SDK implementation is in progress."#
+        ),
+        "java" => (
+            r#"```bash
+# Compile the StoffelLang program
+stoffel compile src/secure_computation.stfl
+
+# Build the project
+mvn package
+
+# Run the MPC demo
+mvn exec:java
+
+# Run tests
+mvn test
+```"#,
+            r#"## Java Client Integration
+
+This project provides a Java client for Stoffel MPC:
+
+- **MpcConfig**: The project's MPC settings mirrored as a Java record
+- **StoffelLang Program**: `src/secure_computation.stfl` holds the secure computation
+- **Maven**: Standard `pom.xml` build targeting Java 17
+
+## Dependencies
+
+- Java 17+
+- Maven 3.8+
+- Stoffel Java SDK (when available)
+
+Note: This template currently contains skeleton code. Full Java SDK implementation is in progress."#
         ),
         _ => (
             r#"```bash
@@ -1317,7 +1698,7 @@ This project uses pure StoffelLang for MPC computations:
         )
     };
 
-    format!(r#"# {}
+    Ok(format!(r#"# {}
 
 {}
 
@@ -1362,8 +1743,480 @@ This application demonstrates:
         config.mpc.protocol,
         config.mpc.parties,
         config.mpc.field,
-        config.mpc.threshold.unwrap_or(1),
+        template_threshold(config)?,
         template,
         additional_info
-    )
-}
\ No newline at end of file
+    ))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_default_author_flows_into_project() {
+        let home = tempfile::tempdir().unwrap();
+        let config_path = home.path().join("config.toml");
+        fs::write(&config_path, "author = \"Ada Lovelace\"\nlicense = \"Apache-2.0\"\n").unwrap();
+        let defaults = UserDefaults::load_from(&config_path).unwrap();
+
+        let project = tempfile::tempdir().unwrap();
+        let path = project.path().join("demo");
+        let options = init_options(false);
+        initialize_default("demo".to_string(), path.clone(), &options, &defaults).unwrap();
+
+        let manifest: StoffelConfig =
+            toml::from_str(&fs::read_to_string(path.join("Stoffel.toml")).unwrap()).unwrap();
+        assert_eq!(manifest.package.authors, Some(vec!["Ada Lovelace".to_string()]));
+        assert_eq!(manifest.package.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(manifest.mpc.field, "bls12-381");
+    }
+
+    #[test]
+    fn dry_run_lists_the_tree_without_writing() {
+        let mut files = ProjectFiles::default();
+        files.add("Stoffel.toml", "[package]\nname = \"demo\"\n");
+        files.add("src/main.stfl", "\n# demo entry point\nmain() {}\n");
+        files.add_dir("tests");
+
+        assert_eq!(
+            files.dry_run_listing(),
+            [
+                "Stoffel.toml       24 B  [package]",
+                "src/",
+                "  main.stfl        30 B  # demo entry point",
+                "tests/",
+            ]
+        );
+
+        let project = tempfile::tempdir().unwrap();
+        let path = project.path().join("demo");
+        let options = InitOptions { path: Some(path.to_string_lossy().to_string()), dry_run: true, ..init_options(false) };
+        initialize_default("demo".to_string(), path.clone(), &options, &UserDefaults::default()).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn default_library_uses_the_stoffel_layout() {
+        let project = tempfile::tempdir().unwrap();
+        let path = project.path().join("demo");
+        let options = init_options(true);
+        initialize_default("demo".to_string(), path.clone(), &options, &UserDefaults::default()).unwrap();
+        assert!(path.join("src/lib.stfl").is_file());
+        assert!(!path.join("src/main.stfl").exists());
+    }
+
+    #[test]
+    fn new_creates_the_directory_and_refuses_existing_ones() {
+        let parent = tempfile::tempdir().unwrap();
+        let options = || InitOptions { path: Some(parent.path().to_string_lossy().to_string()), ..init_options(false) };
+
+        new_project(options()).unwrap();
+        assert!(parent.path().join("demo/Stoffel.toml").is_file());
+
+        fs::remove_file(parent.path().join("demo/Stoffel.toml")).unwrap();
+        let error = new_project(options()).unwrap_err();
+        assert!(error.contains("already exists"), "{}", error);
+    }
+
+    fn init_options(lib: bool) -> InitOptions {
+        InitOptions {
+            name: Some("demo".to_string()),
+            lib,
+            path: None,
+            interactive: false,
+            template: None,
+            template_git: None,
+            answers: None,
+            print_only: false,
+            dry_run: false,
+            force: false,
+            git: false,
+        }
+    }
+
+    #[test]
+    fn every_template_scaffolds_a_loadable_project() {
+        let entry_points = [
+            ("python", "src/main.py"),
+            ("rust", "src/main.rs"),
+            ("typescript", "src/main.ts"),
+            ("solidity", "contracts/StoffelMPC.sol"),
+            ("go", "main.go"),
+            ("java", "src/main/java/com/example/demo/Main.java"),
+            ("stoffel", "src/main.stfl"),
+        ];
+        assert_eq!(entry_points.len(), TEMPLATES.len());
+
+        for (name, entry_point) in entry_points {
+            let template = find_template(name).unwrap();
+            let layouts = [(false, Some(template.files)), (true, template.library.as_ref().map(|library| library.files))];
+            for (lib, files) in layouts {
+                let Some(files) = files else { continue };
+                let project = tempfile::tempdir().unwrap();
+                let path = project.path().join("demo");
+                initialize_from_template("demo".to_string(), path.clone(), name, &init_options(lib), &UserDefaults::default()).unwrap();
+
+                let manifest = crate::config::load_project(&path).unwrap_or_else(|e| panic!("{} (lib: {}): {}", name, lib, e));
+                assert_eq!(manifest.package.name, "demo");
+                assert_eq!(manifest.mpc.threshold, Some(1));
+                assert!(fs::read_to_string(path.join("README.md")).unwrap().starts_with("# demo\n"), "{} README", name);
+                for file in files {
+                    let file = file.replace("<package>", if name == "java" { "com/example/demo" } else { "demo" });
+                    let contents = fs::read_to_string(path.join(&file)).unwrap_or_else(|e| panic!("{} {}: {}", name, file, e));
+                    assert!(!contents.trim().is_empty(), "{} generated an empty {}", name, file);
+                }
+
+                let (entry_point, expected) = if lib { ("src/lib.stfl", "proc ") } else { (entry_point, "demo") };
+                let contents = fs::read_to_string(path.join(entry_point)).unwrap();
+                assert!(contents.contains(expected), "{} {} doesn't contain {:?}", name, entry_point, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn default_init_creates_the_stoffel_application() {
+        let project = tempfile::tempdir().unwrap();
+        let path = project.path().join("demo");
+        initialize_default("demo".to_string(), path.clone(), &init_options(false), &UserDefaults::default()).unwrap();
+
+        assert_eq!(crate::config::load_project(&path).unwrap().package.name, "demo");
+        assert!(fs::read_to_string(path.join("src/main.stfl")).unwrap().starts_with("# demo - A Stoffel MPC application"));
+        assert!(path.join("tests/integration.stfl").is_file());
+        assert!(path.join("README.md").is_file());
+        assert!(path.join("LICENSE").is_file());
+    }
+
+    #[test]
+    fn prompted_settings_build_a_project_without_stdin() {
+        let settings = |parties| ProjectSettings {
+            name: "demo".to_string(),
+            description: None,
+            author: "Ada Lovelace".to_string(),
+            license: "MIT".to_string(),
+            parties,
+            field: "bn254".to_string(),
+        };
+
+        let config = settings(7).manifest().unwrap();
+        assert_eq!(config.mpc.threshold, Some(2));
+        let project = tempfile::tempdir().unwrap();
+        plan_project_files(&config, false, Some("python")).unwrap().write_to(project.path()).unwrap();
+        let manifest = crate::config::load_project(project.path()).unwrap();
+        assert_eq!((manifest.mpc.parties, manifest.mpc.field.as_str()), (7, "bn254"));
+        assert!(project.path().join("src/main.py").is_file());
+
+        let error = settings(4).manifest().unwrap_err();
+        assert!(error.contains("at least 5 parties"), "{}", error);
+    }
+
+    #[test]
+    fn interactive_init_runs_on_canned_answers() {
+        let project = tempfile::tempdir().unwrap();
+        let path = project.path().join("demo");
+        // name, description, author, parties, field, ecosystem 1 (python), confirm
+        let mut input = Prompter { input: "\nA private auction\nAda <ada@example.com>\n7\nbn254\n1\n\n".as_bytes() };
+        initialize_interactive(&mut input, "demo".to_string(), path.clone(), &init_options(false), &UserDefaults::default()).unwrap();
+
+        let manifest = crate::config::load_project(&path).unwrap();
+        assert_eq!(manifest.package.description.as_deref(), Some("A private auction"));
+        assert_eq!(manifest.package.authors, Some(vec!["Ada <ada@example.com>".to_string()]));
+        assert_eq!((manifest.mpc.parties, manifest.mpc.threshold), (7, Some(2)));
+        assert_eq!(manifest.mpc.field, "bn254");
+        assert!(path.join("src/main.py").is_file());
+    }
+
+    #[test]
+    fn template_flag_prefills_the_ecosystem_answer() {
+        let options = InitOptions { template: Some("rust".to_string()), ..init_options(false) };
+        let mut input = Prompter { input: "my-app\n\nAda\n-\n\n\n".as_bytes() };
+        let (config, template) = prompt_project(&mut input, "demo".to_string(), &options, &UserDefaults::default()).unwrap();
+        assert_eq!(template, "rust");
+        assert_eq!(config.package.name, "my-app");
+        assert_eq!(config.package.description, None);
+        assert_eq!(config.package.authors, Some(vec!["Ada".to_string()]));
+        assert_eq!(config.mpc.threshold, Some(1));
+
+        let mut input = Prompter { input: "\n\nAda\n-\n4\n".as_bytes() };
+        let error = prompt_project(&mut input, "demo".to_string(), &options, &UserDefaults::default()).unwrap_err();
+        assert!(error.contains("at least 5 parties"), "{}", error);
+
+        // Declining the confirmation writes nothing
+        let project = tempfile::tempdir().unwrap();
+        let path = project.path().join("demo");
+        let mut input = Prompter { input: "\n\nAda\n-\n\n\nn\n".as_bytes() };
+        initialize_interactive(&mut input, "demo".to_string(), path.clone(), &options, &UserDefaults::default()).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn answers_file_scaffolds_without_prompts() {
+        let dir = tempfile::tempdir().unwrap();
+        let answers = dir.path().join("answers.toml");
+        fs::write(
+            &answers,
+            "name = \"auction\"\ndescription = \"Sealed bids\"\nauthor = \"Ada\"\nparties = 7\nfield = \"bn254\"\ntemplate = \"python\"\n",
+        )
+        .unwrap();
+        let options = InitOptions {
+            path: Some(dir.path().to_string_lossy().to_string()),
+            answers: Some(answers.to_string_lossy().to_string()),
+            ..init_options(false)
+        };
+        initialize_project(options).unwrap();
+
+        let path = dir.path().join("demo");
+        let manifest = crate::config::load_project(&path).unwrap();
+        assert_eq!(manifest.package.name, "auction");
+        assert_eq!(manifest.package.description.as_deref(), Some("Sealed bids"));
+        assert_eq!((manifest.mpc.parties, manifest.mpc.threshold), (7, Some(2)));
+        assert!(path.join("src/main.py").is_file());
+
+        // Missing keys take the prompts' defaults, and --template wins over the file
+        let scaffold = |answers: &str, template: Option<&str>| {
+            let project = tempfile::tempdir().unwrap();
+            let path = project.path().join("demo");
+            let answers = toml::from_str(answers).map_err(|e| e.to_string())?;
+            let options = InitOptions { template: template.map(str::to_string), ..init_options(false) };
+            initialize_from_answers("demo".to_string(), path.clone(), answers, &options, &UserDefaults::default())?;
+            Ok::<_, String>((project, crate::config::load_project(&path)?))
+        };
+        let (project, manifest) = scaffold("template = \"python\"\n", Some("go")).unwrap();
+        assert_eq!((manifest.package.name.as_str(), manifest.mpc.parties), ("demo", 5));
+        assert!(project.path().join("demo/main.go").is_file());
+        let (project, _) = scaffold("", None).unwrap();
+        assert!(project.path().join("demo/src/main.stfl").is_file());
+
+        // The same rules as the prompts apply
+        for (answers, expected) in [
+            ("parties = 4\n", "at least 5 parties"),
+            ("field = \"p256\"\n", "unsupported field 'p256'"),
+            ("name = \"My App\"\n", "invalid project name"),
+            ("template = \"pyton\"\n", "did you mean 'python'"),
+            ("colour = \"blue\"\n", "unknown field `colour`"),
+        ] {
+            let error = scaffold(answers, None).err().unwrap();
+            assert!(error.contains(expected), "{}: {}", answers, error);
+        }
+    }
+
+    #[test]
+    fn authors_include_the_email_when_given() {
+        assert_eq!(format_author("Ada Lovelace", Some("ada@example.com")), "Ada Lovelace <ada@example.com>");
+        assert_eq!(format_author("Ada Lovelace", None), "Ada Lovelace");
+    }
+
+    #[test]
+    fn gitignore_matches_template() {
+        let config = StoffelConfig {
+            package: PackageConfig {
+                name: "demo".to_string(),
+                version: "0.1.0".to_string(),
+                description: None,
+                authors: None,
+                license: None,
+                entry: None,
+            },
+            mpc: MpcConfig {
+                protocol: "honeybadger".to_string(),
+                parties: 5,
+                threshold: Some(1),
+                field: "bls12-381".to_string(),
+                profiles: None,
+            },
+            dependencies: None,
+            dev_dependencies: None,
+            workspace: None,
+            tools: None,
+        };
+
+        let cases: &[(Option<&str>, bool, &[&str])] = &[
+            (Some("stoffel"), false, &["target/", "Stoffel.lock.*"]),
+            (None, true, &["target/", "Stoffel.lock.*"]),
+            (Some("python"), false, &["target/", "__pycache__/", "dist/"]),
+            (Some("rust"), false, &["target/", "Cargo.lock"]),
+            (Some("typescript"), false, &["target/", "node_modules/", "dist/"]),
+            (Some("solidity"), false, &["target/", "node_modules/", "artifacts/"]),
+            (Some("go"), false, &["target/", "bin/", "*.test"]),
+            (Some("java"), false, &["target/", "*.class"]),
+        ];
+
+        for (template, is_lib, entries) in cases {
+            let dir = tempfile::tempdir().unwrap();
+            plan_project_files(&config, *is_lib, *template).unwrap().write_to(dir.path()).unwrap();
+
+            let gitignore = fs::read_to_string(dir.path().join(".gitignore")).unwrap();
+            let lines: Vec<&str> = gitignore.lines().collect();
+            for entry in *entries {
+                assert!(lines.contains(entry), "{:?} .gitignore is missing {}", template, entry);
+            }
+        }
+    }
+
+    #[test]
+    fn templates_leave_no_unsubstituted_variables() {
+        let config: StoffelConfig = toml::from_str(
+            "[package]\nname = \"demo-app\"\nversion = \"0.1.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n",
+        )
+        .unwrap();
+
+        for template in ["python", "rust", "typescript", "solidity", "go", "java", "stoffel"] {
+            let files = plan_project_files(&config, false, Some(template)).unwrap();
+            for file in files.files() {
+                assert!(
+                    !file.contents.contains("{{"),
+                    "{} template left a variable in {}",
+                    template,
+                    file.path.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn template_table_lists_the_files_each_template_generates() {
+        let config: StoffelConfig = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n",
+        )
+        .unwrap();
+        let common = ["Stoffel.toml", "README.md", "LICENSE", ".gitignore"];
+
+        for template in TEMPLATES {
+            let planned = plan_project_files(&config, false, Some(template.name)).unwrap();
+            let generated: Vec<String> = planned
+                .files()
+                .iter()
+                .map(|file| file.path.to_string_lossy().replace("com/example/demo", "<package>"))
+                .filter(|path| !common.contains(&path.as_str()))
+                .collect();
+
+            let mut expected: Vec<&str> = template.files.to_vec();
+            let mut generated: Vec<&str> = generated.iter().map(String::as_str).collect();
+            expected.sort();
+            generated.sort();
+            assert_eq!(generated, expected, "{} template", template.name);
+
+            let Some(library) = &template.library else {
+                let error = plan_project_files(&config, true, Some(template.name)).err().unwrap();
+                assert!(error.contains("libraries support: python, rust, stoffel"), "{}", error);
+                continue;
+            };
+            let planned = plan_project_files(&config, true, Some(template.name)).unwrap();
+            let generated: Vec<String> = planned
+                .files()
+                .iter()
+                .map(|file| file.path.to_string_lossy().replace("src/demo/", "src/<package>/"))
+                .filter(|path| !common.contains(&path.as_str()))
+                .collect();
+            let mut generated: Vec<&str> = generated.iter().map(String::as_str).collect();
+            generated.sort();
+            assert_eq!(generated, library.files, "{} library", template.name);
+            assert!(planned.files().iter().all(|file| !file.contents.contains("{{")), "{} library", template.name);
+        }
+    }
+
+    #[test]
+    fn unknown_templates_suggest_the_closest_name() {
+        assert_eq!(
+            validate_template("pyton").err().unwrap(),
+            "unknown template 'pyton', did you mean 'python'?"
+        );
+        assert!(validate_template("cobol").err().unwrap().contains("available: python, rust"));
+        assert_eq!(validate_template("go").unwrap().name, "go");
+    }
+
+    #[test]
+    fn threshold_and_modulus_variables_are_substituted() {
+        let mut config: StoffelConfig = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 7\nfield = \"prime61\"\n",
+        )
+        .unwrap();
+        let template = "t={{mpc_threshold}} p={{mpc_field_modulus}}";
+
+        assert_eq!(substitute_template_vars(template, &config).unwrap(), "t=2 p=0x1fffffffffffffff");
+        config.mpc.threshold = Some(1);
+        assert_eq!(substitute_template_vars(template, &config).unwrap(), "t=1 p=0x1fffffffffffffff");
+    }
+
+    #[test]
+    fn git_templates_are_cloned_at_the_pinned_ref_and_substituted() {
+        assert_eq!(split_git_ref("https://host/t.git#v1"), ("https://host/t.git", Some("v1")));
+        assert_eq!(split_git_ref("https://host/t.git#"), ("https://host/t.git", None));
+
+        let repo = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(repo.path())
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        if !git(&["init", "--quiet"]) {
+            return; // git isn't available
+        }
+        fs::create_dir(repo.path().join("src")).unwrap();
+        fs::write(repo.path().join("src/{{package_name}}.stfl"), "# {{package_name}} with {{mpc_parties}} parties\n").unwrap();
+        fs::write(repo.path().join("Stoffel.toml"), "ignored").unwrap();
+        assert!(git(&["add", "."]) && git(&["commit", "--quiet", "-m", "v1"]) && git(&["tag", "v1"]));
+        fs::write(repo.path().join("src/{{package_name}}.stfl"), "changed after v1\n").unwrap();
+        assert!(git(&["commit", "--quiet", "-am", "v2"]));
+
+        let config: StoffelConfig = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n",
+        )
+        .unwrap();
+        let clone = tempfile::tempdir().unwrap();
+        let clone_dir = clone.path().join("template");
+        clone_template(&format!("{}#v1", repo.path().display()), &clone_dir).unwrap();
+        let files = plan_git_template_files(&config, &clone_dir).unwrap();
+
+        let paths: Vec<_> = files.files().iter().map(|file| file.path.to_string_lossy().to_string()).collect();
+        assert_eq!(paths, ["Stoffel.toml", "src/demo.stfl", ".gitignore"]);
+        assert_eq!(files.files()[1].contents, "# demo with 5 parties\n");
+        assert!(files.files()[0].contents.contains("name = \"demo\""));
+    }
+
+    #[test]
+    fn java_package_names_are_valid_identifiers() {
+        assert_eq!(java_package_name("my-app"), "com.example.my_app");
+        assert_eq!(java_package_name("2fa"), "com.example._2fa");
+    }
+
+    #[test]
+    fn license_file_uses_author_and_year() {
+        let mut files = ProjectFiles::default();
+        let package = PackageConfig {
+            name: "demo".to_string(),
+            version: "0.1.0".to_string(),
+            description: None,
+            authors: Some(vec!["Ada Lovelace".to_string()]),
+            license: Some("MIT".to_string()),
+            entry: None,
+        };
+        create_license_file(&mut files, &package).unwrap();
+
+        let license = &files.files()[0];
+        assert_eq!(license.path, PathBuf::from("LICENSE"));
+        assert!(license.contents.starts_with("MIT License"));
+        assert!(license.contents.contains(&format!("Copyright (c) {} Ada Lovelace", current_year())));
+        assert!(!license.contents.contains("{{"));
+    }
+
+    #[test]
+    fn valid_project_names() {
+        for name in ["app", "my-app", "mpc2", "a-1-b"] {
+            assert!(validate_project_name(name).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn invalid_project_names() {
+        for name in ["", "My App", "MyApp", "my app", "2fast", "-app", "my_app", "app!"] {
+            assert!(validate_project_name(name).is_err(), "{}", name);
+        }
+        assert_eq!(
+            validate_project_name("My App").unwrap_err(),
+            "invalid project name 'My App': use lowercase letters, digits, and hyphens"
+        );
+    }
+}
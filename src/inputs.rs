@@ -0,0 +1,69 @@
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Named program inputs loaded from a `--inputs` JSON file
+pub type Inputs = Map<String, Value>;
+
+/// Load a JSON object mapping input names to values
+///
+/// Values may be numbers, strings, booleans, or arrays of those; anything
+/// else is rejected with the name of the offending input.
+pub fn load(path: &Path) -> Result<Inputs, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read inputs file {}: {}", path.display(), e))?;
+    parse(&content).map_err(|e| format!("Invalid inputs file {}: {}", path.display(), e))
+}
+
+fn parse(content: &str) -> Result<Inputs, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let Value::Object(inputs) = value else {
+        return Err("expected a JSON object mapping input names to values".to_string());
+    };
+
+    for (name, value) in &inputs {
+        let valid = match value {
+            Value::Array(items) => items.iter().all(is_scalar),
+            value => is_scalar(value),
+        };
+        if !valid {
+            return Err(format!(
+                "input '{}' must be a number, string, boolean, or an array of those (got {})",
+                name, value
+            ));
+        }
+    }
+
+    Ok(inputs)
+}
+
+fn is_scalar(value: &Value) -> bool {
+    matches!(value, Value::Number(_) | Value::String(_) | Value::Bool(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_scalars_and_arrays() {
+        let inputs = parse(r#"{"salary": 120000, "name": "alice", "bids": [1, 2, 3], "ok": true}"#).unwrap();
+        assert_eq!(inputs.len(), 4);
+        assert_eq!(inputs["salary"], 120000);
+    }
+
+    #[test]
+    fn names_the_offending_key() {
+        let err = parse(r#"{"salary": 1, "nested": {"a": 1}}"#).unwrap_err();
+        assert!(err.contains("'nested'"), "{}", err);
+
+        let err = parse(r#"{"bids": [1, null]}"#).unwrap_err();
+        assert!(err.contains("'bids'"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_non_objects_and_bad_json() {
+        assert!(parse("[1, 2]").is_err());
+        assert!(parse("{\"salary\": }").is_err());
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// One test's result, as reported in a JUnit testcase
+pub struct TestCase {
+    pub name: String,
+    pub duration: Duration,
+    /// Short failure message and the captured output explaining it
+    pub failure: Option<(String, String)>,
+}
+
+/// Write a single-suite JUnit XML report
+///
+/// `properties` are recorded on the suite, e.g. the seed needed to replay it.
+pub fn write_report(path: &Path, suite: &str, properties: &[(&str, String)], cases: &[TestCase]) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, render(suite, properties, cases))
+        .map_err(|e| format!("Failed to write JUnit report {}: {}", path.display(), e))
+}
+
+fn render(suite: &str, properties: &[(&str, String)], cases: &[TestCase]) -> String {
+    let failures = cases.iter().filter(|case| case.failure.is_some()).count();
+    let total: Duration = cases.iter().map(|case| case.duration).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        cases.len(),
+        failures,
+        total.as_secs_f64()
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\" time=\"{:.3}\">",
+        escape(suite),
+        cases.len(),
+        failures,
+        total.as_secs_f64()
+    );
+
+    if !properties.is_empty() {
+        xml.push_str("    <properties>\n");
+        for (name, value) in properties {
+            let _ = writeln!(
+                xml,
+                "      <property name=\"{}\" value=\"{}\"/>",
+                escape(name),
+                escape(value)
+            );
+        }
+        xml.push_str("    </properties>\n");
+    }
+
+    for case in cases {
+        let _ = write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape(&case.name),
+            escape(suite),
+            case.duration.as_secs_f64()
+        );
+        match &case.failure {
+            Some((message, details)) => {
+                let _ = writeln!(xml, ">");
+                let _ = writeln!(
+                    xml,
+                    "      <failure message=\"{}\">{}</failure>",
+                    escape(message),
+                    escape(details)
+                );
+                let _ = writeln!(xml, "    </testcase>");
+            }
+            None => {
+                let _ = writeln!(xml, "/>");
+            }
+        }
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_carry_escaped_output() {
+        let cases = [
+            TestCase {
+                name: "math/add".to_string(),
+                duration: Duration::from_millis(1500),
+                failure: None,
+            },
+            TestCase {
+                name: "unit".to_string(),
+                duration: Duration::from_millis(20),
+                failure: Some(("Party 2 exited".to_string(), "expected <5> & got \"6\"".to_string())),
+            },
+        ];
+        let xml = render("demo", &[("seed", "42".to_string())], &cases);
+
+        assert!(xml.contains("<testsuite name=\"demo\" tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<property name=\"seed\" value=\"42\"/>"));
+        assert!(xml.contains("<testcase name=\"math/add\" classname=\"demo\" time=\"1.500\"/>"));
+        assert!(xml.contains(
+            "<failure message=\"Party 2 exited\">expected &lt;5&gt; &amp; got &quot;6&quot;</failure>"
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// File name of the dependency lockfile, next to Stoffel.toml
+pub const LOCKFILE_NAME: &str = "Stoffel.lock";
+
+/// Current lockfile format version
+const LOCKFILE_VERSION: u32 = 1;
+
+const HEADER: &str = "# This file is generated by stoffel. It is not intended for manual editing.\n";
+
+/// Resolved dependency versions recorded in Stoffel.lock
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A single resolved package
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Lockfile {
+            version: LOCKFILE_VERSION,
+            packages: Vec::new(),
+        }
+    }
+}
+
+impl Lockfile {
+    /// Load a lockfile; a missing file yields an empty lockfile
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let lockfile: Lockfile = toml::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        if lockfile.version != LOCKFILE_VERSION {
+            return Err(format!(
+                "Unsupported {} version {} (expected {})",
+                path.display(),
+                lockfile.version,
+                LOCKFILE_VERSION
+            ));
+        }
+        Ok(lockfile)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = toml::to_string(self)
+            .map_err(|e| format!("Failed to serialize lockfile: {}", e))?;
+        fs::write(path, format!("{}\n{}", HEADER, content))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Drop the entry for `name`, returning it if it was locked
+    pub fn remove(&mut self, name: &str) -> Option<LockedPackage> {
+        let index = self.packages.iter().position(|package| package.name == name)?;
+        Some(self.packages.remove(index))
+    }
+
+    /// Record `name` at `version`, returning the previously locked version if it changed
+    ///
+    /// Entries are kept sorted by name so the file diffs cleanly.
+    pub fn lock(&mut self, name: &str, version: &str) -> LockChange {
+        match self.packages.iter_mut().find(|package| package.name == name) {
+            Some(package) if package.version == version => LockChange::Unchanged,
+            Some(package) => {
+                let previous = std::mem::replace(&mut package.version, version.to_string());
+                LockChange::Updated(previous)
+            }
+            None => {
+                self.packages.push(LockedPackage {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                });
+                self.packages.sort_by(|a, b| a.name.cmp(&b.name));
+                LockChange::Added
+            }
+        }
+    }
+
+    /// Drop entries for packages that are no longer declared, returning them
+    pub fn retain_declared(&mut self, is_declared: impl Fn(&str) -> bool) -> Vec<LockedPackage> {
+        let (kept, removed) = self.packages.drain(..).partition(|package| is_declared(&package.name));
+        self.packages = kept;
+        removed
+    }
+}
+
+/// Effect of locking a package version
+#[derive(Debug, PartialEq)]
+pub enum LockChange {
+    Added,
+    Updated(String),
+    Unchanged,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCKFILE_NAME);
+
+        let mut lockfile = Lockfile::default();
+        lockfile.lock("zeta", "0.2.0");
+        lockfile.lock("alpha", "1.0.0");
+        lockfile.save(&path).unwrap();
+
+        let loaded = Lockfile::load(&path).unwrap();
+        assert_eq!(loaded, lockfile);
+        assert_eq!(loaded.packages[0].name, "alpha");
+    }
+
+    #[test]
+    fn missing_lockfile_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let lockfile = Lockfile::load(&dir.path().join(LOCKFILE_NAME)).unwrap();
+        assert_eq!(lockfile, Lockfile::default());
+    }
+
+    #[test]
+    fn lock_reports_changes() {
+        let mut lockfile = Lockfile::default();
+        assert_eq!(lockfile.lock("stdlib", "1.0"), LockChange::Added);
+        assert_eq!(lockfile.lock("stdlib", "1.0"), LockChange::Unchanged);
+        assert_eq!(lockfile.lock("stdlib", "1.1"), LockChange::Updated("1.0".to_string()));
+        assert_eq!(lockfile.packages[0].version, "1.1");
+    }
+}
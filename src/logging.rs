@@ -0,0 +1,40 @@
+use log::LevelFilter;
+use std::io::Write;
+
+/// Environment variable that overrides the level chosen by `-v`/`--quiet`, e.g. `STOFFEL_LOG=debug`
+pub const LOG_ENV: &str = "STOFFEL_LOG";
+
+/// Level for the given number of `-v` flags
+///
+/// Status messages are logged at info, diagnostics at debug, and the noisiest
+/// detail at trace. `--quiet` keeps only warnings and errors.
+pub fn level(verbosity: u8, quiet: bool) -> LevelFilter {
+    match (quiet, verbosity) {
+        (true, _) => LevelFilter::Warn,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    }
+}
+
+/// Route log records to stderr as bare messages, so status lines read the same as before
+pub fn init(verbosity: u8, quiet: bool) {
+    env_logger::Builder::new()
+        .filter_level(level(verbosity, quiet))
+        .parse_env(LOG_ENV)
+        .format(|buf, record| writeln!(buf, "{}", record.args()))
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_verbosity_flag_raises_the_level() {
+        assert_eq!(level(0, false), LevelFilter::Info);
+        assert_eq!(level(1, false), LevelFilter::Debug);
+        assert_eq!(level(3, false), LevelFilter::Trace);
+        assert_eq!(level(0, true), LevelFilter::Warn);
+    }
+}
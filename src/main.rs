@@ -1,26 +1,67 @@
-use clap::{Parser, Subcommand, ValueEnum};
-
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use log::{debug, info};
+use style::Status;
+
+mod build_manifest;
+mod cache;
+mod compile_cache;
+mod config;
+mod coverage;
+mod deploy;
+mod deps;
+mod dev;
+mod formatter;
+mod graph;
+mod home;
 mod init;
+mod inputs;
+mod junit;
+mod logging;
+mod lockfile;
+mod package;
+mod plugins;
+mod sim;
+mod source_filter;
+mod style;
+mod testing;
+mod timings;
+mod toolchain;
+mod watch;
+
+/// CLI version with the commit it was built from, as shown by `--version`
+const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), " (", env!("STOFFEL_GIT_COMMIT"), ")");
 
 /// Stoffel - A framework for building privacy-preserving applications using multiparty computation
 #[derive(Parser, Debug)]
 #[command(
     name = "stoffel",
     author,
-    version,
+    version = VERSION,
     about,
     long_about = "Stoffel is a framework for building privacy-preserving applications using multiparty computation"
 )]
 struct Cli {
-    /// Enable verbose output
-    #[arg(short, long, default_value_t = false)]
-    verbose: bool,
+    /// Show diagnostics; repeat (-vv) for more detail
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only print results, warnings and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// When to color output; auto colors only when stdout is a terminal
+    #[arg(long, value_name = "WHEN", global = true, default_value = "auto")]
+    color: style::ColorChoice,
+
+    /// Print the result of status, compile, build and bench as one JSON object on stdout
+    #[arg(long, global = true)]
+    json: bool,
 
     #[command(subcommand)]
     command: Commands,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 enum Commands {
     /// Initialize a new Stoffel project or library
     #[command(
@@ -32,12 +73,17 @@ EXAMPLES:
     stoffel init -i                           # Interactive setup with prompts
     stoffel init -t python my-mpc-app         # Create project with Python SDK integration
     stoffel init --path /tmp/test --lib       # Create library at specific path
+    stoffel init --list-templates             # List templates and the files they generate
+    stoffel init --template-git <URL>#v1.0    # Create project from a template in a git repository
+    stoffel init my-app --dry-run             # Preview the generated files without writing them
 
 AVAILABLE TEMPLATES:
     python      - Python SDK integration with StoffelProgram and StoffelClient
     rust        - Rust FFI integration with StoffelVM (skeleton)
     typescript  - TypeScript/Node.js MPC client (skeleton)
     solidity    - Smart contracts with MPC result verification
+    go          - Go client integration (skeleton)
+    java        - Java/Maven client integration (skeleton)
     stoffel     - Pure StoffelLang implementation (default)
 
 INTERACTIVE MODE:
@@ -58,7 +104,44 @@ INTERACTIVE MODE:
         #[arg(
             long,
             help = "Create a library project instead of an application",
-            long_help = "Initialize as a library project suitable for publishing and use as a dependency. Libraries include src/lib.stfl and focus on reusable MPC functions rather than executable applications."
+            long_help = "Initialize as a library project suitable for publishing and use as a dependency. Libraries include src/lib.stfl and focus on reusable MPC functions rather than executable applications.
+
+USAGE:
+    stoffel init --lib [PROJECT_NAME]
+
+LIBRARY PROJECT STRUCTURE:
+    my-library/
+    ├── Stoffel.toml              # Package configuration
+    ├── src/
+    │   └── lib.stfl              # Library entry point with exported functions
+    └── README.md                 # Documentation
+
+LIBRARY FEATURES:
+    ├─ Reusable MPC Functions
+    │  ├─ Exportable secure computation functions
+    │  ├─ Composable privacy-preserving algorithms
+    │  └─ Well-defined interfaces for integration
+    │
+    ├─ Distribution Ready
+    │  ├─ Proper package metadata
+    │  ├─ Dependency management
+    │  └─ Version compatibility
+    │
+    └─ Testing Infrastructure
+       ├─ Unit tests for individual functions
+       ├─ Integration tests for MPC workflows
+       └─ Benchmarking for performance validation
+
+EXAMPLES:
+    stoffel init --lib crypto-utils           # Create cryptographic utilities library
+    stoffel init --lib --path ./libs mpc-ml  # Create ML library in specific directory
+    stoffel init --lib -i secure-stats       # Interactive library setup
+
+USE CASES:
+    ✅ Cryptographic primitives and utilities
+    ✅ Domain-specific MPC algorithms (ML, finance, healthcare)
+    ✅ Reusable privacy-preserving building blocks
+    ✅ Third-party integrations and connectors"
         )]
         lib: bool,
 
@@ -66,7 +149,37 @@ INTERACTIVE MODE:
         #[arg(
             long,
             help = "Directory path where the project should be created",
-            long_help = "Path where the new project should be initialized. If not specified, creates the project in the current directory. The path will be created if it doesn't exist."
+            long_help = "Path where the new project should be initialized. If not specified, creates the project in the current directory. The path will be created if it doesn't exist.
+
+USAGE:
+    stoffel init --path <DIRECTORY> [PROJECT_NAME]
+
+PATH BEHAVIOR:
+    ├─ Absolute Paths: /home/user/projects/my-app
+    ├─ Relative Paths: ./my-project, ../parent-dir/project
+    ├─ Auto-creation: Creates directories if they don't exist
+    └─ Validation: Ensures write permissions and valid path
+
+EXAMPLES:
+    stoffel init --path /tmp/test-project              # Absolute path
+    stoffel init --path ./secure-apps my-app           # Relative path
+    stoffel init --path ~/Development/MPC secure-calc  # Home directory
+    stoffel init --path . existing-dir                 # Current directory
+
+PATH RESOLUTION:
+    Without --path:    Uses current directory or creates subdirectory with project name
+    With --path:       Creates project at specified location
+
+COMBINED WITH OTHER FLAGS:
+    stoffel init --path /tmp --lib my-library          # Library at specific path
+    stoffel init --path ./apps -t python webapp        # Python template at path
+    stoffel init --path ~/projects -i                  # Interactive at path
+
+VALIDATION:
+    ✅ Checks directory write permissions
+    ✅ Creates parent directories as needed
+    ⚠️  Fails if the directory is not empty or contains Stoffel.toml
+        (use --force to initialize anyway)"
         )]
         path: Option<String>,
 
@@ -75,7 +188,39 @@ INTERACTIVE MODE:
             short,
             long,
             help = "Enable interactive setup with guided prompts",
-            long_help = "Interactive mode provides step-by-step setup with prompts for project details, MPC configuration, and template selection. Recommended for first-time users or when you want to customize all settings."
+            long_help = "Interactive mode provides step-by-step setup with prompts for project details, MPC configuration, and template selection. Recommended for first-time users or when you want to customize all settings.
+
+USAGE:
+    stoffel init --interactive [PROJECT_NAME]
+    stoffel init -i [PROJECT_NAME]
+
+INTERACTIVE FEATURES:
+    ├─ Project Configuration
+    │  ├─ Project name (with validation)
+    │  ├─ Description
+    │  └─ Author (auto-detected from git config)
+    │
+    ├─ MPC Configuration
+    │  ├─ Number of parties (minimum 5 for HoneyBadger)
+    │  ├─ Cryptographic field selection
+    │  └─ Security threshold (auto-calculated)
+    │
+    └─ Template Selection
+       ├─ Detailed explanations of each template
+       ├─ Recommendations based on use case
+       └─ Preview of files that will be created
+
+EXAMPLES:
+    stoffel init -i                           # Interactive setup in current directory
+    stoffel init -i my-secure-app             # Interactive setup with project name
+    stoffel init --interactive --path /tmp    # Interactive setup at specific path
+    stoffel init -i -t python                 # Prompt for everything but the template
+
+WHEN TO USE:
+    ✅ First-time users learning Stoffel
+    ✅ When you want to explore all configuration options
+    ✅ Setting up complex MPC configurations
+    ✅ When unsure which template to choose"
         )]
         interactive: bool,
 
@@ -84,27 +229,192 @@ INTERACTIVE MODE:
             short,
             long,
             help = "Template for project initialization",
-            long_help = "Language-specific template to use for project structure:
+            long_help = "The --template (-t) flag specifies which programming language ecosystem template to use when initializing a new Stoffel project.
+
+USAGE:
+    stoffel init --template <TEMPLATE> [PROJECT_NAME]
+    stoffel init -t <TEMPLATE> [PROJECT_NAME]
+
+AVAILABLE TEMPLATES:
+
+  python
+    ├─ Full Python SDK integration with StoffelProgram and StoffelClient
+    ├─ Creates: src/main.py, src/secure_computation.stfl, pyproject.toml
+    ├─ Dependencies: Poetry, stoffel-python-sdk
+    ├─ Status: ✅ Fully implemented with working MPC examples
+    └─ Best for: Python developers, data science, rapid prototyping
+
+  rust
+    ├─ Rust FFI integration with StoffelVM (development skeleton)
+    ├─ Creates: src/main.rs, Cargo.toml with FFI dependencies
+    ├─ Dependencies: libc, tokio (StoffelVM crates when available)
+    ├─ Status: 🚧 Development skeleton, FFI integration pending
+    └─ Best for: Performance-critical applications, systems programming
+
+  typescript
+    ├─ TypeScript/Node.js client integration (development skeleton)
+    ├─ Creates: src/main.ts, package.json, tsconfig.json
+    ├─ Dependencies: @stoffel/sdk (when available)
+    ├─ Status: 🚧 Development skeleton, SDK implementation pending
+    └─ Best for: Web applications, JavaScript ecosystem integration
+
+  solidity
+    ├─ Smart contracts with MPC result verification
+    ├─ Creates: contracts/StoffelMPC.sol, hardhat.config.js, deployment scripts
+    ├─ Dependencies: Hardhat, OpenZeppelin contracts
+    ├─ Status: 🚧 Development skeleton, on-chain verification concepts
+    └─ Best for: Blockchain integration, DeFi applications
+
+  go
+    ├─ Go client integration (development skeleton)
+    ├─ Creates: main.go, go.mod, src/secure_computation.stfl
+    ├─ Dependencies: Go 1.21+ (Stoffel Go SDK when available)
+    ├─ Status: 🚧 Development skeleton, SDK implementation pending
+    └─ Best for: Backend services, cloud-native tooling
+
+  java
+    ├─ Java/Maven client integration (development skeleton)
+    ├─ Creates: pom.xml, src/main/java/.../Main.java, src/secure_computation.stfl
+    ├─ Dependencies: Java 17+, Maven (Stoffel Java SDK when available)
+    ├─ Status: 🚧 Development skeleton, SDK implementation pending
+    └─ Best for: Enterprise backends, JVM ecosystem integration
+
+  stoffel (default)
+    ├─ Pure StoffelLang implementation
+    ├─ Creates: src/main.stfl, tests/integration.stfl
+    ├─ Dependencies: None (native StoffelLang)
+    ├─ Status: ✅ Fully supported with proper syntax
+    └─ Best for: Learning StoffelLang, pure MPC applications
+
+EXAMPLES:
+    stoffel init -t python my-mpc-app          # Python template
+    stoffel init --template rust secure-calc   # Rust template
+    stoffel init -t solidity mpc-auction       # Solidity template
+    stoffel init my-project                    # Default (stoffel) template
+
+INTERACTIVE MODE:
+    Use -i/--interactive to get guided template selection with explanations:
+
+    stoffel init -i                           # Guided setup with template help"
+        )]
+        template: Option<String>,
+
+        /// Git repository to use as the project template
+        #[arg(
+            long,
+            value_name = "URL[#REF]",
+            conflicts_with_all = ["template", "interactive", "lib"],
+            help = "Create the project from a template in a git repository",
+            long_help = "Clone a git repository and use its files as the project template. Every file (except the .git directory) goes through the same variable substitution as the built-in templates, so {{package_name}}, {{mpc_parties}} and friends are filled in. Append #REF to pin a branch, tag or commit:
+
+  stoffel init my-app --template-git https://github.com/acme/stoffel-template.git#v1.2
+
+Stoffel.toml is always generated from the init settings; a .gitignore is added unless the template ships one. The temporary clone is removed afterwards, whether or not init succeeds."
+        )]
+        template_git: Option<String>,
+
+        /// Answers file for scripted setup
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["interactive", "template_git"],
+            help = "Take the interactive setup answers from a TOML file",
+            long_help = "Set up the project from a TOML file of answers instead of prompting, so CI and scripts can scaffold projects without a terminal. The file may set any of these keys:
+
+  name = \"my-app\"                 # package name (defaults to the project name)
+  description = \"Private auction\"
+  author = \"Ada <ada@example.com>\"
+  parties = 7
+  field = \"bn254\"
+  template = \"python\"
+
+Keys that are left out take the defaults --interactive would offer. The answers are checked against the same rules as interactive mode, and --template overrides the file's template."
+        )]
+        answers: Option<String>,
+
+        /// Stream the generated project to stdout as a tar archive
+        #[arg(
+            long,
+            conflicts_with = "interactive",
+            help = "Write the generated project to stdout as a tar archive",
+            long_help = "Instead of creating files on disk, serialize every generated file (Stoffel.toml and all template files) into a tar stream on stdout. Paths in the archive are relative to the project root, so the output can be unpacked anywhere:
 
-TEMPLATES:
-  python      - Full Python SDK integration with StoffelProgram and StoffelClient
-                Creates: src/main.py, pyproject.toml, Poetry configuration
+  stoffel init my-app --print-only | tar x -C /somewhere/my-app
 
-  rust        - Rust FFI integration with StoffelVM (development skeleton)
-                Creates: src/main.rs, Cargo.toml with FFI dependencies
+Progress messages are written to stderr so they don't corrupt the archive."
+        )]
+        print_only: bool,
 
-  typescript  - TypeScript/Node.js client integration (development skeleton)
-                Creates: src/main.ts, package.json, tsconfig.json
+        /// Show what would be generated without writing anything
+        #[arg(
+            long,
+            conflicts_with = "print_only",
+            help = "List the files and directories init would create, then exit",
+            long_help = "Plan the project exactly as init normally would, then print the tree of directories and files it would create instead of writing them. Each file is shown with its size and first line, which makes it easy to preview what a --template or --template-git template generates. The target directory is still checked, so a dry run also reports when init would refuse to run. With --interactive the listing replaces the final confirmation."
+        )]
+        dry_run: bool,
 
-  solidity    - Smart contracts with MPC result verification
-                Creates: contracts/StoffelMPC.sol, Hardhat configuration
+        /// Overwrite an existing project
+        #[arg(
+            long,
+            help = "Initialize even if the directory is not empty or already has a Stoffel.toml",
+            long_help = "By default init refuses to run in a directory that already contains a Stoffel.toml or any other files, so existing work is never clobbered. Pass --force to proceed anyway; generated files overwrite existing files with the same name and other files are left untouched."
+        )]
+        force: bool,
 
-  stoffel     - Pure StoffelLang implementation (default if not specified)
-                Creates: src/main.stfl, tests/integration.stfl
+        /// Skip creating a git repository
+        #[arg(
+            long = "no-git",
+            action = clap::ArgAction::SetFalse,
+            help = "Don't initialize a git repository",
+            long_help = "By default init runs `git init` in the new project and commits the generated files as an initial commit. Pass --no-git to skip this. Git setup is also skipped when the project is created inside an existing repository or git isn't installed."
+        )]
+        git: bool,
 
-The Python template is fully implemented with working SDK integration. Other templates provide development skeletons for their respective ecosystems."
+        /// List the available templates
+        #[arg(
+            long,
+            help = "List the available templates and exit",
+            long_help = "Print one line per template with its name, its status (implemented or skeleton) and the files it generates, then exit without creating a project."
         )]
+        list_templates: bool,
+    },
+
+    /// Create a new Stoffel project in a new directory
+    #[command(
+        long_about = "Create a new Stoffel project in a directory named after it, the way `cargo new` does.
+
+Unlike `init`, which can also set up the current directory, `new` always creates <NAME>/ and refuses to run if that directory already exists.
+
+EXAMPLES:
+    stoffel new my-app                         # Create my-app/ with the default StoffelLang template
+    stoffel new --lib crypto-utils             # Create a library
+    stoffel new -t python my-mpc-app           # Create a project with Python SDK integration
+    stoffel new --path ./apps my-app           # Create ./apps/my-app/"
+    )]
+    New {
+        /// Project name, also used as the directory name
+        name: String,
+
+        /// Create a library instead of an application
+        #[arg(long)]
+        lib: bool,
+
+        /// Template to generate (see `stoffel init --list-templates`)
+        #[arg(short, long)]
         template: Option<String>,
+
+        /// Create the project directory inside PATH instead of the current directory
+        #[arg(long)]
+        path: Option<String>,
+
+        /// Prompt for project details and MPC settings
+        #[arg(short, long)]
+        interactive: bool,
+
+        /// Don't initialize a git repository
+        #[arg(long = "no-git", action = clap::ArgAction::SetFalse)]
+        git: bool,
     },
 
     /// Start development server with hot reloading
@@ -132,11 +442,35 @@ MPC CONFIGURATION:
         /// Number of parties for simulation (minimum 5 for HoneyBadger)
         #[arg(
             long,
-            default_value = "5",
-            help = "Number of MPC parties to simulate",
-            long_help = "Number of parties in the simulated MPC network. For HoneyBadger protocol, minimum is 5 parties. More parties increase security but reduce performance. Typical development uses 5-7 parties."
+            help = "Number of MPC parties to simulate [default: 5]",
+            long_help = "Number of parties in the simulated MPC network. For HoneyBadger protocol, minimum is 5 parties. More parties increase security but reduce performance. Typical development uses 5-7 parties. Defaults to 5.
+
+USAGE:
+    stoffel dev --parties <NUMBER>
+
+PARTY CONFIGURATION:
+    Minimum:    5 parties (HoneyBadger protocol requirement)
+    Typical:    5-7 parties (good balance of security and performance)
+    Maximum:    No hard limit, but performance decreases with more parties
+
+SECURITY IMPLICATIONS:
+    ├─ More parties = Higher security against corruption
+    ├─ Threshold = (parties - 1) / 3 for HoneyBadger
+    ├─ Can tolerate up to threshold corrupted parties
+    └─ Example: 7 parties can tolerate 2 corrupted parties
+
+PERFORMANCE CONSIDERATIONS:
+    ├─ More parties = More network communication
+    ├─ More parties = Slower computation
+    ├─ Development typically uses 5-7 parties
+    └─ Production may use 10+ parties for higher security
+
+EXAMPLES:
+    stoffel dev --parties 5                   # Minimum configuration (fast)
+    stoffel dev --parties 7                   # Balanced security/performance
+    stoffel dev --parties 10                  # Higher security (slower)"
         )]
-        parties: u8,
+        parties: Option<u8>,
 
         /// Port to run on
         #[arg(
@@ -144,39 +478,236 @@ MPC CONFIGURATION:
             long,
             default_value = "8080",
             help = "Port for the development server",
-            long_help = "Port where the development server will listen for connections. The server provides a web interface for monitoring MPC execution and logs."
+            long_help = "Port where the development server will listen for connections. GET /health returns JSON with the party count, protocol, threshold, field, and current reload generation.
+
+USAGE:
+    stoffel dev --port <PORT>
+    stoffel dev -p <PORT>
+
+PORT REQUIREMENTS:
+    ├─ Range: 1024-65535 (ports below 1024 need --allow-privileged)
+    ├─ Available: Port must not be in use by another service
+    ├─ Firewall: Ensure port is not blocked by firewall
+    └─ Default: 8080 if not specified
+
+DEVELOPMENT SERVER FEATURES:
+    ├─ Web Dashboard: Real-time MPC execution monitoring
+    ├─ Log Viewer: Detailed logs from all simulated parties
+    ├─ Performance Metrics: Computation time, network stats
+    ├─ Debug Interface: Inspect MPC state and variables
+    └─ Hot Reload Status: File change detection and recompilation
+
+EXAMPLES:
+    stoffel dev -p 3000                       # Run on port 3000
+    stoffel dev --port 8080                   # Default port (explicit)
+    stoffel dev --port 9000 --parties 7       # Custom port with more parties
+
+COMMON PORTS:
+    3000    Often used for React/Node.js development
+    8080    Default for many development servers
+    8000    Alternative development port
+    5000    Common for Flask/Python applications"
         )]
         port: u16,
 
-        /// MPC protocol to use
+        /// Address to bind the status server to
         #[arg(
             long,
-            default_value = "honeybadger",
-            help = "MPC protocol for simulation",
-            long_help = "Multiparty computation protocol to use for development. Currently only HoneyBadger is supported, which provides Byzantine fault tolerance and is production-ready."
+            value_name = "ADDR",
+            default_value = "127.0.0.1",
+            help = "Address for the development server to bind to",
+            long_help = "IP address the development server binds to together with --port. Defaults to 127.0.0.1 so the status endpoint is only reachable locally; use 0.0.0.0 to expose it on every interface."
         )]
-        protocol: MpcProtocol,
+        bind: std::net::IpAddr,
 
-        /// Security threshold (max corrupted parties, auto-calculated if not provided)
+        /// MPC protocol to use
         #[arg(
             long,
-            help = "Maximum number of corrupted parties (auto-calculated if not specified)",
-            long_help = "Security threshold: maximum number of parties that can be corrupted while maintaining security. For HoneyBadger, must be < n/3. If not specified, automatically calculated as (parties-1)/3."
-        )]
-        threshold: Option<u8>,
+            help = "MPC protocol for simulation [default: honeybadger]",
+            long_help = "Multiparty computation protocol to use for development. HoneyBadger (the default) provides asynchronous Byzantine fault tolerance and is production-ready; shamir offers faster passive security with an honest majority, and bgw active security for synchronous networks.
+
+USAGE:
+    stoffel dev --protocol <PROTOCOL>
+
+AVAILABLE PROTOCOLS:
+    honeybadger (default)
+    ├─ Byzantine Fault Tolerant (BFT)
+    ├─ Asynchronous network model
+    ├─ Threshold: Can tolerate up to (n-1)/3 corrupted parties
+    ├─ Minimum parties: 5
+    ├─ Security: Production-ready, formally verified
+    └─ Performance: Good for most applications
+
+    shamir
+    ├─ Passive (semi-honest) security
+    ├─ Threshold: Can tolerate up to (n-1)/2 corrupted parties
+    ├─ Minimum parties: 3
+    └─ Performance: Fastest; parties must follow the protocol
+
+    bgw
+    ├─ Active (malicious) security
+    ├─ Synchronous network model
+    ├─ Threshold: Can tolerate up to (n-1)/3 corrupted parties
+    └─ Minimum parties: 4
+
+PROTOCOL FEATURES:
+    ├─ Robustness
+    │  ├─ Works even with network delays and failures
+    │  ├─ No synchronization assumptions
+    │  └─ Guaranteed termination under honest majority
+    │
+    ├─ Security
+    │  ├─ Information-theoretic security
+    │  ├─ Protects against adaptive adversaries
+    │  └─ Secure against Byzantine corruption
+    │
+    └─ Practical
+       ├─ Efficient for real-world deployments
+       ├─ Scales to reasonable party numbers
+       └─ Well-tested implementation
+
+EXAMPLES:
+    stoffel dev --protocol honeybadger        # Explicit protocol selection
+    stoffel dev                               # Uses honeybadger by default
+
+FUTURE PROTOCOLS:
+    Additional protocols may be added in future versions based on:
+    ├─ Research advances in MPC protocols
+    ├─ Specific use case requirements (speed vs security)
+    └─ Community feedback and requests"
+        )]
+        protocol: Option<MpcProtocol>,
+
+        /// Security threshold (max corrupted parties, auto-calculated if not provided)
+        #[arg(
+            long,
+            help = "Maximum number of corrupted parties (auto-calculated if not specified)",
+            long_help = "Security threshold: maximum number of parties that can be corrupted while maintaining security. For HoneyBadger, must be < n/3. If not specified, automatically calculated as (parties-1)/3.
+
+USAGE:
+    stoffel dev --threshold <NUMBER>
+
+THRESHOLD CALCULATION:
+    For HoneyBadger and BGW: threshold = (parties - 1) / 3
+    For Shamir:              threshold = (parties - 1) / 2
+
+    Examples:
+    ├─ 5 parties → threshold 1 (can tolerate 1 corrupted party)
+    ├─ 7 parties → threshold 2 (can tolerate 2 corrupted parties)
+    ├─ 10 parties → threshold 3 (can tolerate 3 corrupted parties)
+    └─ 16 parties → threshold 5 (can tolerate 5 corrupted parties)
+
+SECURITY IMPLICATIONS:
+    ├─ Higher threshold = More fault tolerance
+    ├─ Lower threshold = Less fault tolerance but faster
+    ├─ Threshold must be < parties/3 for HoneyBadger and BGW, < parties/2 for Shamir
+    └─ Invalid thresholds will cause initialization to fail
+
+WHEN TO CUSTOMIZE:
+    ├─ Testing specific threat models
+    ├─ Simulating network with known number of adversaries
+    ├─ Performance testing with different security levels
+    └─ Research and experimentation
+
+EXAMPLES:
+    stoffel dev --parties 7 --threshold 1     # Lower security, faster
+    stoffel dev --parties 7                   # Auto: threshold = 2
+    stoffel dev --parties 10 --threshold 3    # Explicit threshold
+
+VALIDATION:
+    ✅ threshold < (parties + 2) / 3 (HoneyBadger, BGW)
+    ✅ threshold < (parties + 1) / 2 (Shamir)
+    ⚠️  Too high threshold will fail with security error
+    ⚠️  Too low threshold reduces security unnecessarily"
+        )]
+        threshold: Option<u8>,
 
         /// Field type for computation
         #[arg(
             long,
-            default_value = "bls12-381",
-            help = "Cryptographic field for MPC operations",
-            long_help = "Finite field used for MPC computations:
-  bls12-381  - BLS12-381 scalar field (recommended, good performance and security)
-  bn254      - BN254 scalar field (alternative pairing-friendly curve)
-  secp256k1  - Secp256k1 scalar field (Ethereum/Bitcoin compatibility)
-  prime61    - Small prime field for testing (fast but not secure)"
+            help = "Cryptographic field for MPC operations [default: bls12-381]",
+            long_help = "The --field flag specifies the finite field used for MPC computations. Different fields offer different performance and compatibility characteristics.
+
+USAGE:
+    stoffel dev --field <FIELD>
+
+AVAILABLE FIELDS:
+
+  bls12-381 (default)
+    ├─ Security: ~128-bit security level
+    ├─ Performance: Good balance of speed and security
+    ├─ Compatibility: Works with BLS signatures and pairings
+    ├─ Size: ~381-bit prime field
+    └─ Best for: General-purpose MPC applications
+
+  bn254
+    ├─ Security: ~100-bit security level
+    ├─ Performance: Faster than BLS12-381
+    ├─ Compatibility: Ethereum's alt_bn128 precompiles
+    ├─ Size: ~254-bit prime field
+    └─ Best for: Ethereum integration, when speed matters
+
+  secp256k1
+    ├─ Security: ~128-bit security level
+    ├─ Performance: Good, widely optimized
+    ├─ Compatibility: Bitcoin/Ethereum ECDSA curve
+    ├─ Size: ~256-bit prime field
+    └─ Best for: Cryptocurrency applications
+
+  prime61
+    ├─ Security: ⚠️ Testing only (not secure)
+    ├─ Performance: Very fast
+    ├─ Compatibility: Simple operations
+    ├─ Size: 61-bit prime field
+    └─ Best for: Development, testing, benchmarking
+
+SELECTION CRITERIA:
+    ├─ Security Requirements: Choose field with adequate security level
+    ├─ Performance Needs: Smaller fields are faster but less secure
+    ├─ Integration: Match field to existing cryptographic infrastructure
+    └─ Development Phase: Use prime61 for fast iteration, production fields for release
+
+EXAMPLES:
+    stoffel dev --field bls12-381             # Default, good for most use cases
+    stoffel dev --field bn254                 # Ethereum-compatible
+    stoffel dev --field prime61               # Fast development/testing
+    stoffel dev --field secp256k1             # Bitcoin/crypto compatibility"
+        )]
+        field: Option<MpcField>,
+
+        /// MPC profile from Stoffel.toml
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Use the named [mpc.profiles.<NAME>] settings from Stoffel.toml",
+            long_help = "Select a named MPC profile declared as [mpc.profiles.<NAME>] in Stoffel.toml. Settings missing from the profile fall back to the base [mpc] section. Explicit --parties, --protocol, --threshold, and --field flags still take precedence."
+        )]
+        profile: Option<String>,
+
+        /// Print where each effective setting came from
+        #[arg(
+            long,
+            help = "Show each effective setting and its source, then exit",
+            long_help = "Print every resolved setting together with where it came from: a command-line flag, a named profile in Stoffel.toml, the [mpc] section of Stoffel.toml, the user-global ~/.stoffel/config.toml, or a built-in default. Useful for diagnosing precedence surprises. Nothing is started."
+        )]
+        explain_config: bool,
+
+        /// Explicit address for each simulated party
+        #[arg(
+            long,
+            value_name = "MAP",
+            help = "Assign a specific address to each simulated party",
+            long_help = "Comma-separated list of INDEX=ADDRESS pairs overriding the default sequential port assignment, e.g. \"0=127.0.0.1:9001,1=127.0.0.1:9002,...\". Every party index from 0 to parties-1 must appear exactly once and no two parties may share an address. When omitted, parties bind to 127.0.0.1 starting at port 9001."
+        )]
+        parties_map: Option<String>,
+
+        /// Allow a --port below 1024
+        #[arg(
+            long,
+            help = "Allow binding the development server to a privileged port (below 1024)",
+            long_help = "Ports below 1024 are privileged: binding them usually needs root and they are often taken by system services, so --port rejects them by default. Pass this flag to use one anyway."
         )]
-        field: MpcField,
+        allow_privileged: bool,
     },
 
     /// Compile StoffelLang source files to bytecode
@@ -194,6 +725,7 @@ EXAMPLES:
     stoffel compile --binary                          # Compile all files as binaries
     stoffel compile -O3                               # Compile all with optimization
     stoffel compile --disassemble compiled.bin         # Disassemble compiled binary
+    stoffel compile --std ../stdlib                    # Use an alternate standard library
 
 BATCH COMPILATION:
     When compiling multiple files from src/:
@@ -232,7 +764,33 @@ DEBUGGING:
             short,
             long,
             help = "Output file path for compiled bytecode",
-            long_help = "Specify the output file path for the compiled bytecode. If not provided, uses the input filename with appropriate extension (.bin for binary, .bc for bytecode)."
+            long_help = "Specify the output file path for the compiled bytecode. If not provided, uses the input filename with appropriate extension (.bin for binary, .bc for bytecode).
+
+USAGE:
+    stoffel compile src/main.stfl --output compiled.bin
+    stoffel compile src/main.stfl -o output.bc
+
+OUTPUT FILE EXTENSIONS:
+    .bin    VM-compatible binary (use with --binary flag)
+    .bc     Bytecode format (default)
+    .stfl   Source file extension (input files)
+
+FILE PATH RESOLUTION:
+    ├─ Absolute paths: /path/to/output.bin
+    ├─ Relative paths: ./output.bin, ../compiled/main.bc
+    ├─ Automatic extension: Adds .bc if no extension provided
+    └─ Directory creation: Creates parent directories if needed
+
+EXAMPLES:
+    stoffel compile main.stfl -o compiled.bin          # Specific output file
+    stoffel compile main.stfl --output release.bc     # Bytecode output
+    stoffel compile main.stfl -o /tmp/test.bin         # Absolute path
+    stoffel compile main.stfl                          # Auto: main.bc
+
+INTEGRATION WITH OTHER FLAGS:
+    stoffel compile main.stfl -o app.bin --binary     # Binary format output
+    stoffel compile main.stfl -o debug.bc --print-ir  # Debug output with IR
+    stoffel compile main.stfl -o opt.bin -O3 --binary # Optimized binary"
         )]
         output: Option<String>,
 
@@ -241,15 +799,86 @@ DEBUGGING:
             short = 'b',
             long,
             help = "Generate VM-compatible binary format",
-            long_help = "Generate a VM-compatible binary format suitable for execution on StoffelVM. This is the recommended format for production deployment."
+            long_help = "Generate a VM-compatible binary format suitable for execution on StoffelVM. This is the recommended format for production deployment.
+
+USAGE:
+    stoffel compile src/main.stfl --binary
+    stoffel compile src/main.stfl -b
+
+BINARY FORMAT FEATURES:
+    ├─ VM Compatibility: Direct execution on StoffelVM
+    ├─ Optimized Loading: Faster startup times
+    ├─ Compact Size: Efficient binary representation
+    ├─ Production Ready: Suitable for deployment
+    └─ Platform Independent: Runs on any StoffelVM instance
+
+BINARY VS BYTECODE:
+    Bytecode (.bc):
+    ├─ Human-readable representation
+    ├─ Debugging friendly
+    ├─ Larger file size
+    └─ Requires additional processing
+
+    Binary (.bin):
+    ├─ VM-optimized format
+    ├─ Faster execution
+    ├─ Smaller file size
+    └─ Production deployment
+
+EXAMPLES:
+    stoffel compile main.stfl --binary                 # Generate binary
+    stoffel compile main.stfl -b -o release.bin        # Binary with custom name
+    stoffel compile main.stfl --binary -O3             # Optimized binary
+
+DEPLOYMENT WORKFLOW:
+    1. Development: Compile without --binary for debugging
+    2. Testing: Use --binary for performance testing
+    3. Production: Always use --binary for deployment"
         )]
         binary: bool,
 
         /// Disassemble compiled binary instead of compiling
         #[arg(
             long,
-            help = "Disassemble a compiled binary file",
-            long_help = "Disassemble a previously compiled Stoffel binary (.bin) file to show the bytecode instructions. Useful for debugging and understanding compilation output."
+            help = "Disassemble a compiled binary file (same as `stoffel disasm`)",
+            long_help = "Disassemble a previously compiled Stoffel binary (.bin) file to show the bytecode instructions. Useful for debugging and understanding compilation output. Equivalent to `stoffel disasm <FILE>`; -o/--output names the listing file.
+
+USAGE:
+    stoffel compile compiled.bin --disassemble
+
+DISASSEMBLY FEATURES:
+    ├─ Bytecode Instructions: Shows VM opcodes and operands
+    ├─ Memory Layout: Displays data section and constants
+    ├─ Jump Targets: Shows labels and branch destinations
+    ├─ Debug Information: Includes source line mappings (if available)
+    └─ Human Readable: Formatted output for analysis
+
+INPUT FILE TYPES:
+    .bin    VM-compatible binary files
+    .bc     Bytecode files (also supported)
+
+DISASSEMBLY OUTPUT:
+    ├─ Instruction listing with addresses
+    ├─ Register usage and data flow
+    ├─ Function boundaries and call sites
+    └─ Constant pool and literal values
+
+EXAMPLES:
+    stoffel compile app.bin --disassemble              # Disassemble binary
+    stoffel compile debug.bc --disassemble             # Disassemble bytecode
+    stoffel compile app.bin --disassemble > dump.txt   # Save to file
+
+DEBUGGING WORKFLOW:
+    1. Compile with debug info: stoffel compile main.stfl --print-ir
+    2. Generate binary: stoffel compile main.stfl --binary -o app.bin
+    3. Disassemble: stoffel compile app.bin --disassemble
+    4. Analyze output for optimization opportunities
+
+COMMON USE CASES:
+    ✅ Debugging compilation issues
+    ✅ Understanding compiler optimizations
+    ✅ Reverse engineering binary files
+    ✅ Performance analysis and profiling"
         )]
         disassemble: bool,
 
@@ -257,227 +886,1109 @@ DEBUGGING:
         #[arg(
             long,
             help = "Print intermediate representations (tokens, AST, etc.)",
-            long_help = "Print intermediate representations during compilation including tokens, Abstract Syntax Tree (AST), and other debug information. Useful for compiler development and debugging complex compilation issues."
-        )]
-        print_ir: bool,
+            long_help = "Print intermediate representations during compilation including tokens, Abstract Syntax Tree (AST), and other debug information. Useful for compiler development and debugging complex compilation issues.
 
-        /// Optimization level (0-3)
-        #[arg(
-            short = 'O',
-            long = "opt-level",
-            default_value = "0",
-            help = "Set optimization level (0-3)",
-            long_help = "Set the optimization level for compilation:
-  0  No optimization (fastest compilation, good for development)
-  1  Basic optimizations (dead code elimination, constant folding)
-  2  Standard optimizations (good balance of speed and size)
-  3  Maximum optimization (aggressive optimization, slowest compilation)"
-        )]
-        opt_level: u8,
-    },
+USAGE:
+    stoffel compile src/main.stfl --print-ir
 
-    /// Build the current project
-    #[command(
-        long_about = "Compile the current Stoffel project into executable MPC bytecode.
+INTERMEDIATE REPRESENTATIONS:
+    ├─ Tokens: Lexical analysis output (keywords, identifiers, literals)
+    ├─ Abstract Syntax Tree (AST): Parsed program structure
+    ├─ Symbol Table: Variable and function declarations
+    ├─ Type Information: Inferred and declared types
+    ├─ Semantic Analysis: Type checking and validation results
+    └─ Code Generation: Bytecode generation steps
 
-EXAMPLES:
-    stoffel build                              # Debug build with default settings
-    stoffel build --release                    # Optimized release build
-    stoffel build --target wasm               # Build for WebAssembly target
-    stoffel build --optimize --release         # Maximum optimizations for production
+DEBUG OUTPUT SECTIONS:
+    1. LEXICAL ANALYSIS
+       ├─ Token stream with positions
+       ├─ Keyword recognition
+       └─ Literal parsing
 
-BUILD PROCESS:
-    1. Compiles StoffelLang (.stfl) files to MPC bytecode
-    2. Validates MPC protocol compatibility
-    3. Optimizes for the target execution environment
-    4. Generates deployment artifacts
+    2. SYNTAX ANALYSIS
+       ├─ Parse tree structure
+       ├─ Grammar rule applications
+       └─ Error recovery attempts
 
-OUTPUT:
-    - Compiled bytecode in target/ directory
-    - Deployment manifests for MPC networks
-    - Debug symbols (if debug build)"
-    )]
-    Build {
-        /// Target to build for
-        #[arg(
-            long,
-            help = "Build target platform",
-            long_help = "Target platform for compilation:
-  native     - Native MPC execution (default)
-  wasm       - WebAssembly for browser MPC
-  tee        - Trusted Execution Environment
-  gpu        - GPU-accelerated computation"
-        )]
-        target: Option<String>,
+    3. SEMANTIC ANALYSIS
+       ├─ Type checking results
+       ├─ Symbol resolution
+       └─ Scope analysis
 
-        /// Enable optimizations
-        #[arg(
-            long,
-            help = "Enable compiler optimizations",
-            long_help = "Enable advanced compiler optimizations for better performance. This includes dead code elimination, constant folding, and MPC-specific optimizations. May increase build time."
+    4. CODE GENERATION
+       ├─ Bytecode instruction selection
+       ├─ Register allocation
+       └─ Optimization passes
+
+EXAMPLES:
+    stoffel compile main.stfl --print-ir               # Full IR output
+    stoffel compile main.stfl --print-ir > debug.log   # Save to file
+    stoffel compile main.stfl --print-ir -O2           # IR with optimizations
+
+DEBUGGING WORKFLOW:
+    1. Basic compilation: Check for syntax errors
+    2. Add --print-ir: Examine parse tree and types
+    3. Fix issues: Use IR to identify problems
+    4. Optimize: Compare IR before/after optimization
+
+WHEN TO USE:
+    ✅ Debugging compilation errors
+    ✅ Understanding compiler behavior
+    ✅ Learning StoffelLang internals
+    ✅ Contributing to compiler development
+    ⚠️  Produces verbose output (use redirection)"
         )]
-        optimize: bool,
+        print_ir: bool,
 
-        /// Release build
+        /// Optimization level (0-3)
         #[arg(
-            short,
-            long,
-            help = "Build in release mode with full optimizations",
-            long_help = "Release mode enables all optimizations and removes debug information for maximum performance. Use for production deployments. Debug builds are faster to compile and include debugging symbols."
-        )]
-        release: bool,
-    },
+            short = 'O',
+            long = "opt-level",
+            default_value = "0",
+            value_parser = clap::value_parser!(u8).range(0..=3),
+            help = "Set optimization level (0-3)",
+            long_help = "The --opt-level (-O) flag sets the optimization level for compilation. Higher levels improve performance but increase compilation time.
 
-    /// Test the current project
-    Test {
-        /// Run specific test
-        #[arg(long)]
-        test: Option<String>,
+USAGE:
+    stoffel compile src/main.stfl --opt-level 2
+    stoffel compile src/main.stfl -O3
 
-        /// Number of parties for testing (minimum 5 for HoneyBadger)
-        #[arg(long, default_value = "5")]
-        parties: u8,
+OPTIMIZATION LEVELS:
 
-        /// MPC protocol to use for testing
-        #[arg(long, default_value = "honeybadger")]
-        protocol: MpcProtocol,
+  -O0 (default)
+    ├─ No optimization
+    ├─ Fastest compilation
+    ├─ Best for development and debugging
+    ├─ Preserves all debug information
+    └─ Larger bytecode size
 
-        /// Security threshold (max corrupted parties, auto-calculated if not provided)
-        #[arg(long)]
-        threshold: Option<u8>,
+  -O1
+    ├─ Basic optimizations
+    ├─ Dead code elimination
+    ├─ Constant folding
+    ├─ Fast compilation
+    └─ Good balance for development
 
-        /// Field type for computation
-        #[arg(long, default_value = "bls12-381")]
-        field: MpcField,
+  -O2
+    ├─ Standard optimizations
+    ├─ Loop optimizations
+    ├─ Function inlining (small functions)
+    ├─ Register optimization
+    └─ Recommended for production
 
-        /// Run integration tests
-        #[arg(long)]
-        integration: bool,
-    },
+  -O3
+    ├─ Aggressive optimizations
+    ├─ Advanced loop transformations
+    ├─ Extensive function inlining
+    ├─ Cross-function optimizations
+    └─ Maximum performance (slowest compilation)
 
-    /// Run the current project
-    Run {
-        /// Arguments to pass to the program
-        args: Vec<String>,
+OPTIMIZATION TECHNIQUES:
+    ├─ Dead Code Elimination: Removes unused code
+    ├─ Constant Folding: Pre-computes constant expressions
+    ├─ Loop Optimization: Reduces loop overhead
+    ├─ Function Inlining: Eliminates function call overhead
+    ├─ Register Allocation: Optimizes register usage
+    └─ MPC-Specific: Optimizes secure computation patterns
 
-        /// Number of parties for execution (minimum 5 for HoneyBadger)
-        #[arg(long, default_value = "5")]
-        parties: u8,
+PERFORMANCE IMPACT:
+    Level    Compile Time    Runtime Speed    Binary Size
+    -O0      Fastest        Slowest          Largest
+    -O1      Fast           Good             Medium
+    -O2      Medium         Better           Smaller
+    -O3      Slowest        Fastest          Smallest
 
-        /// MPC protocol to use for execution
-        #[arg(long, default_value = "honeybadger")]
-        protocol: MpcProtocol,
+EXAMPLES:
+    stoffel compile main.stfl -O0                      # Debug build
+    stoffel compile main.stfl -O2                      # Production build
+    stoffel compile main.stfl -O3 --binary             # Maximum optimization
+    stoffel compile main.stfl --opt-level 1            # Explicit level 1
 
-        /// Security threshold (max corrupted parties, auto-calculated if not provided)
-        #[arg(long)]
-        threshold: Option<u8>,
+WHEN TO USE EACH LEVEL:
+    -O0: Development, debugging, rapid iteration
+    -O1: Testing builds, continuous integration
+    -O2: Production releases, performance testing
+    -O3: Performance-critical applications, benchmarking"
+        )]
+        opt_level: u8,
 
-        /// Field type for computation
-        #[arg(long, default_value = "bls12-381")]
-        field: MpcField,
+        /// Standard library location
+        #[arg(
+            long = "std",
+            env = "STOFFEL_STD_PATH",
+            value_name = "PATH",
+            help = "Override the StoffelLang standard library location",
+            long_help = "Path to the StoffelLang standard library passed through to the compiler. Useful for testing a project against an alternate stdlib without reinstalling the toolchain. Can also be set with the STOFFEL_STD_PATH environment variable. The path must exist."
+        )]
+        std_path: Option<String>,
 
-        /// VM optimization level
-        #[arg(long, default_value = "standard")]
-        vm_opt: VmOptLevel,
-    },
+        /// Preprocessor define passed to the compiler (repeatable)
+        #[arg(
+            short = 'D',
+            long = "define",
+            value_name = "KEY=VALUE",
+            value_parser = parse_define,
+            help = "Define a compile-time constant (repeatable)",
+            long_help = "Define a KEY=VALUE preprocessor constant forwarded to the Stoffel-Lang compiler, like `cc -D`. May be given multiple times; each key may only be defined once. Keys must start with a letter or underscore and contain only letters, digits, and underscores."
+        )]
+        defines: Vec<(String, String)>,
 
-    /// Deploy the current project
-    Deploy {
-        /// Deployment environment
-        #[arg(short, long, default_value = "local")]
-        environment: String,
+        /// Directory for reports and other generated side artifacts
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory for reports and side artifacts (default: target/)",
+            long_help = "Base directory under which generated side artifacts such as timing reports are written with conventional names. The directory is created if it doesn't exist. -o/--output still controls the primary compiled output. Defaults to target/."
+        )]
+        output_dir: Option<String>,
 
-        /// Use TEE deployment
-        #[arg(long)]
-        tee: bool,
+        /// Write a per-file timing report
+        #[arg(
+            long,
+            help = "Write a per-file compilation timing report",
+            long_help = "Record when each file's compilation started and finished and write an HTML timeline to stoffel-timings.html in the output directory (target/ by default). Useful for spotting the file that dominates a slow build."
+        )]
+        timings: bool,
 
-        /// Kubernetes deployment
-        #[arg(long)]
-        k8s: bool,
-    },
+        /// Highlight files slower than this many milliseconds
+        #[arg(
+            long,
+            value_name = "MS",
+            help = "List files whose compilation took longer than MS milliseconds",
+            long_help = "After compiling, print a \"slow files\" section listing every file whose compilation exceeded the given number of milliseconds, slowest first. A lightweight alternative to the full --timings report."
+        )]
+        slow_threshold: Option<u64>,
 
-    /// Add a dependency to the project
-    Add {
-        /// Package name
-        package: String,
+        /// Recompile changed files until interrupted
+        #[arg(
+            long,
+            conflicts_with_all = ["file", "disassemble"],
+            help = "Watch src/ and recompile .stfl files as they change",
+            long_help = "Compile every .stfl file in src/ once, then keep watching src/ and recompile each file as it is saved. Rapid successive saves are collapsed into one rebuild, and every rebuild prints a timestamped line. Press Ctrl-C to stop."
+        )]
+        watch: bool,
 
-        /// Specific version
-        #[arg(long)]
-        version: Option<String>,
+        /// Recompile even if sources are unchanged
+        #[arg(
+            long,
+            help = "Ignore the compile cache and recompile every file",
+            long_help = "Skip the .stoffel-cache/ index and invoke the compiler for every file. Normally a file is skipped when its contents, optimization level, and --binary setting match the previous run and its output still exists."
+        )]
+        no_cache: bool,
 
-        /// Add as dev dependency
-        #[arg(long)]
-        dev: bool,
-    },
+        /// Directory for compiled output
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Write compiled output under DIR, mirroring src/",
+            long_help = "Write compiled files under DIR instead of next to their sources. When compiling all of src/, each file's path below src/ is recreated under DIR (src/lib/math.stfl becomes DIR/lib/math.bin). A single file is also written to DIR unless -o/--output is given. Missing directories are created."
+        )]
+        out_dir: Option<String>,
 
-    /// Publish package to registry
-    Publish {
-        /// Dry run without actually publishing
-        #[arg(long)]
-        dry_run: bool,
-    },
+        /// Only compile files matching this glob (repeatable)
+        #[arg(
+            long,
+            value_name = "GLOB",
+            value_parser = source_filter::parse_glob,
+            help = "Only compile src/ files matching GLOB (repeatable)",
+            long_help = "Restrict batch compilation to files matching GLOB. Patterns match the path relative to src/ (lib/*.stfl) or the full path (src/lib/*.stfl). May be given multiple times; a file is kept if any pattern matches. Ignored when a single file is given."
+        )]
+        include: Vec<glob::Pattern>,
 
-    /// Install and manage plugins
-    Plugin {
-        #[command(subcommand)]
-        action: PluginCommands,
+        /// Skip files matching this glob (repeatable)
+        #[arg(
+            long,
+            value_name = "GLOB",
+            value_parser = source_filter::parse_glob,
+            help = "Skip src/ files matching GLOB (repeatable)",
+            long_help = "Leave files matching GLOB out of batch compilation, e.g. --exclude 'generated/**'. Patterns match the path relative to src/ or the full path, and are applied after --include. May be given multiple times. Ignored when a single file is given."
+        )]
+        exclude: Vec<glob::Pattern>,
+
+        /// Stop at the first file that fails to compile
+        #[arg(
+            long,
+            help = "Stop at the first file that fails to compile",
+            long_help = "Abort batch compilation as soon as a file fails, naming the file that stopped it. Files still waiting to be compiled are skipped. By default every file is compiled and the command exits non-zero at the end if any failed."
+        )]
+        fail_fast: bool,
+
+        /// How to report per-file results
+        #[arg(
+            long,
+            value_enum,
+            default_value = "human",
+            help = "Output format for per-file results",
+            long_help = "Format for reporting compilation results:
+  human  Progress lines, compiler output, and a summary (default)
+  json   One JSON object per file on stdout with its path, success, and the
+         captured compiler stdout/stderr; nothing else is printed to stdout"
+        )]
+        message_format: MessageFormat,
     },
 
-    /// Check the status of the current project
-    Status,
+    /// Type-check StoffelLang sources without generating any output
+    Check {
+        /// File to check [default: every .stfl file in src/]
+        file: Option<String>,
+    },
 
-    /// Clean build artifacts
-    Clean,
+    /// Format StoffelLang sources in place
+    Fmt {
+        /// Files to format [default: every .stfl file in src/, tests/ and examples/]
+        files: Vec<String>,
 
-    /// Update dependencies
-    Update {
-        /// Package to update (all if not specified)
-        package: Option<String>,
+        /// List files that need formatting and exit non-zero instead of rewriting them
+        #[arg(long)]
+        check: bool,
     },
-}
 
-#[derive(Subcommand, Debug)]
-enum PluginCommands {
-    /// Install a plugin
-    Install {
-        /// Plugin name
-        name: String,
+    /// Print the import graph of the modules in src/
+    Graph {
+        /// Graph description language to print
+        #[arg(long, default_value = "dot")]
+        format: GraphFormat,
     },
 
-    /// List installed plugins
-    List,
+    /// Disassemble a compiled program into an instruction listing
+    Disasm {
+        /// Compiled program to disassemble (.bin or .bc)
+        file: String,
 
-    /// Remove a plugin
-    Remove {
-        /// Plugin name
-        name: String,
+        /// Write the listing to FILE instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
     },
-}
 
-/// Available MPC protocols
-#[derive(ValueEnum, Debug, Clone)]
-enum MpcProtocol {
-    /// HoneyBadger MPC protocol (default, production-ready)
-    Honeybadger,
-}
+    /// Build the current project
+    #[command(
+        long_about = "Compile the current Stoffel project into executable MPC bytecode.
 
-/// Available finite fields for MPC computation
-#[derive(ValueEnum, Debug, Clone)]
-enum MpcField {
-    /// BLS12-381 scalar field (default, recommended)
-    #[value(name = "bls12-381")]
-    Bls12_381,
-    /// BN254 scalar field
-    #[value(name = "bn254")]
-    Bn254,
-    /// Secp256k1 scalar field
-    #[value(name = "secp256k1")]
+EXAMPLES:
+    stoffel build                              # Debug build with default settings
+    stoffel build --release                    # Optimized release build
+    stoffel build --target wasm               # Build for WebAssembly target
+    stoffel build --optimize --release         # Maximum optimizations for production
+
+BUILD PROCESS:
+    1. Compiles StoffelLang (.stfl) files to MPC bytecode
+    2. Validates MPC protocol compatibility
+    3. Optimizes for the target execution environment
+    4. Generates deployment artifacts
+
+OUTPUT:
+    - Compiled bytecode in target/ directory
+    - Deployment manifests for MPC networks
+    - Debug symbols (if debug build)"
+    )]
+    Build {
+        /// Target to build for
+        #[arg(
+            long,
+            help = "Build target platform",
+            long_help = "The --target flag specifies the platform to build for. Different targets enable deployment to different environments.
+
+USAGE:
+    stoffel build --target <TARGET>
+
+AVAILABLE TARGETS:
+
+  native (default)
+    ├─ Native MPC execution on the current platform
+    ├─ Best performance for local and server deployment
+    ├─ Full feature support
+    └─ Direct integration with system resources
+
+  wasm
+    ├─ WebAssembly for browser-based MPC
+    ├─ Cross-platform compatibility
+    ├─ Sandboxed execution environment
+    └─ Web application integration
+
+  tee
+    ├─ Trusted Execution Environment (Intel SGX, ARM TrustZone)
+    ├─ Hardware-based security guarantees
+    ├─ Additional protection against side-channel attacks
+    └─ Cloud deployment with confidential computing
+
+  gpu
+    ├─ GPU-accelerated computation
+    ├─ Parallel processing for large-scale MPC
+    ├─ Optimized for computationally intensive operations
+    └─ Requires CUDA or OpenCL support
+
+EXAMPLES:
+    stoffel build --target native             # Default native build
+    stoffel build --target wasm               # Browser deployment
+    stoffel build --target tee                # Confidential computing
+    stoffel build --target gpu                # High-performance computing"
+        )]
+        target: Option<String>,
+
+        /// Enable optimizations
+        #[arg(
+            long,
+            help = "Enable compiler optimizations",
+            long_help = "Enable advanced compiler optimizations for better performance. This includes dead code elimination, constant folding, and MPC-specific optimizations. May increase build time. Equivalent to -O2 unless --opt-level is given.
+
+USAGE:
+    stoffel build --optimize
+
+OPTIMIZATION FEATURES:
+    ├─ Dead Code Elimination: Removes unused functions and variables
+    ├─ Constant Folding: Pre-computes constant expressions
+    ├─ Loop Optimization: Improves loop performance and memory usage
+    ├─ MPC-Specific: Optimizations for secure computation patterns
+    └─ Bytecode Optimization: Generates more efficient VM instructions
+
+PERFORMANCE IMPACT:
+    ├─ Runtime Speed: 20-50% faster execution typical
+    ├─ Memory Usage: Reduced memory footprint
+    ├─ Network Traffic: Optimized communication patterns
+    └─ Build Time: Increased compilation time
+
+WHEN TO USE:
+    ✅ Production builds
+    ✅ Performance testing
+    ✅ Final deployment artifacts
+    ⚠️  Not recommended for debug builds (harder to debug)
+
+EXAMPLES:
+    stoffel build --optimize                  # Optimized debug build
+    stoffel build --optimize --release        # Full optimization
+    stoffel build --optimize --target wasm    # Optimized WebAssembly
+
+OPTIMIZATION LEVELS:
+    Without --optimize:    Fast compilation, basic optimizations
+    With --optimize:       Advanced optimizations, slower compilation
+    With --release:        Maximum optimizations (implies --optimize)"
+        )]
+        optimize: bool,
+
+        /// Explicit optimization level
+        #[arg(
+            short = 'O',
+            long = "opt-level",
+            value_name = "LEVEL",
+            value_parser = clap::value_parser!(u8).range(0..=3),
+            help = "Set optimization level (0-3)",
+            long_help = "Set the optimization level passed to the compiler. Takes precedence over --optimize (-O2) and --release (-O3). Debug builds default to 0."
+        )]
+        opt_level: Option<u8>,
+
+        /// Release build
+        #[arg(
+            short,
+            long,
+            help = "Build in release mode with full optimizations",
+            long_help = "Release mode enables all optimizations and removes debug information for maximum performance. Use for production deployments. Debug builds are faster to compile and include debugging symbols.
+
+USAGE:
+    stoffel build --release
+    stoffel build -r
+
+RELEASE BUILD FEATURES:
+    ├─ Maximum Optimizations: All optimization passes enabled
+    ├─ No Debug Info: Smaller binary size, faster loading
+    ├─ Production Ready: Suitable for deployment
+    ├─ Security Hardening: Additional security measures
+    └─ Performance Tuned: Optimized for runtime performance
+
+DIFFERENCES FROM DEBUG:
+    Debug Build:
+    ├─ Fast compilation
+    ├─ Debug symbols included
+    ├─ Assertions enabled
+    ├─ Larger binary size
+    └─ Easier debugging
+
+    Release Build:
+    ├─ Slower compilation
+    ├─ No debug symbols
+    ├─ Assertions disabled
+    ├─ Smaller binary size
+    └─ Maximum performance
+
+BUILD ARTIFACTS:
+    ├─ Optimized bytecode in target/release/
+    ├─ Deployment manifests
+    ├─ Production configuration templates
+    └─ Performance reports
+
+EXAMPLES:
+    stoffel build -r                          # Standard release build
+    stoffel build --release --target wasm     # Release WebAssembly build
+    stoffel build --release --target tee      # Release TEE build
+
+DEPLOYMENT CHECKLIST:
+    ✅ Build with --release flag
+    ✅ Test on target environment
+    ✅ Verify performance requirements
+    ✅ Security audit if required"
+        )]
+        release: bool,
+
+        /// Allow a release build with the insecure prime61 field
+        #[arg(
+            long,
+            help = "Allow --release builds with a field that is not secure (prime61)",
+            long_help = "prime61 is a small field meant for fast testing and offers no real security. Debug builds only warn about it, but release builds refuse it unless this flag is passed."
+        )]
+        allow_insecure_field: bool,
+
+        /// Directory for reports and other generated side artifacts
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory for reports and side artifacts (default: target/)",
+            long_help = "Base directory under which generated side artifacts such as timing reports are written with conventional names. The directory is created if it doesn't exist. -o/--output still controls the primary compiled output. Defaults to target/."
+        )]
+        output_dir: Option<String>,
+
+        /// Write a per-file timing report
+        #[arg(
+            long,
+            help = "Write a per-file compilation timing report",
+            long_help = "Record when each file's compilation started and finished and write an HTML timeline to stoffel-timings.html in the output directory (target/ by default). Useful for spotting the file that dominates a slow build."
+        )]
+        timings: bool,
+
+        /// Highlight files slower than this many milliseconds
+        #[arg(
+            long,
+            value_name = "MS",
+            help = "List files whose compilation took longer than MS milliseconds",
+            long_help = "After compiling, print a \"slow files\" section listing every file whose compilation exceeded the given number of milliseconds, slowest first. A lightweight alternative to the full --timings report."
+        )]
+        slow_threshold: Option<u64>,
+
+        /// Preprocessor define passed to the compiler (repeatable)
+        #[arg(
+            short = 'D',
+            long = "define",
+            value_name = "KEY=VALUE",
+            value_parser = parse_define,
+            help = "Define a compile-time constant (repeatable)",
+            long_help = "Define a KEY=VALUE preprocessor constant forwarded to the Stoffel-Lang compiler, like `cc -D`. May be given multiple times; each key may only be defined once. Keys must start with a letter or underscore and contain only letters, digits, and underscores."
+        )]
+        defines: Vec<(String, String)>,
+
+        /// Also build examples/
+        #[arg(
+            long,
+            help = "Also compile the project's examples",
+            long_help = "Compile every .stfl file under examples/ in addition to the project sources. Examples are created with `stoffel example add <name>`."
+        )]
+        examples: bool,
+
+        /// Stop at the first file that fails to compile
+        #[arg(
+            long,
+            help = "Stop at the first file that fails to compile",
+            long_help = "Abort the build as soon as a file fails, naming the file that stopped it. Files still waiting to be compiled are skipped. By default every file is compiled and the build fails at the end if any did."
+        )]
+        fail_fast: bool,
+
+        #[command(flatten)]
+        lock_flags: LockFlags,
+    },
+
+    /// Test the current project
+    Test {
+        /// Run specific test
+        #[arg(long)]
+        test: Option<String>,
+
+        /// Number of parties for testing (minimum 5 for HoneyBadger) [default: 5]
+        #[arg(long)]
+        parties: Option<u8>,
+
+        /// MPC protocol to use for testing [default: honeybadger]
+        #[arg(long)]
+        protocol: Option<MpcProtocol>,
+
+        /// Security threshold (max corrupted parties, auto-calculated if not provided)
+        #[arg(long)]
+        threshold: Option<u8>,
+
+        /// Field type for computation [default: bls12-381]
+        #[arg(long)]
+        field: Option<MpcField>,
+
+        /// Use the named [mpc.profiles.<NAME>] settings from Stoffel.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Show each effective setting and its source, then exit
+        #[arg(long)]
+        explain_config: bool,
+
+        /// Run integration tests
+        #[arg(long)]
+        integration: bool,
+
+        /// Record which procs and lines the tests execute; needs compiler and VM coverage support
+        #[arg(long)]
+        coverage: bool,
+
+        /// Coverage output format
+        #[arg(long, default_value = "summary", requires = "coverage")]
+        coverage_format: CoverageFormat,
+
+        /// Test report format; the console summary is always printed
+        #[arg(long, default_value = "human")]
+        format: TestReportFormat,
+
+        /// Where to write the report [default: target/junit.xml]
+        #[arg(long, value_name = "FILE")]
+        out: Option<std::path::PathBuf>,
+
+        /// Seed for the simulation's randomness; printed when chosen at random
+        #[arg(long, value_name = "SEED")]
+        seed: Option<u64>,
+    },
+
+    /// Run the current project
+    Run {
+        /// Arguments to pass to the program
+        args: Vec<String>,
+
+        /// Number of parties for execution (minimum 5 for HoneyBadger) [default: 5]
+        #[arg(long)]
+        parties: Option<u8>,
+
+        /// MPC protocol to use for execution [default: honeybadger]
+        #[arg(long)]
+        protocol: Option<MpcProtocol>,
+
+        /// Security threshold (max corrupted parties, auto-calculated if not provided)
+        #[arg(long)]
+        threshold: Option<u8>,
+
+        /// Field type for computation [default: bls12-381]
+        #[arg(long)]
+        field: Option<MpcField>,
+
+        /// Use the named [mpc.profiles.<NAME>] settings from Stoffel.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Show each effective setting and its source, then exit
+        #[arg(long)]
+        explain_config: bool,
+
+        /// VM optimization level [default: standard]
+        #[arg(long)]
+        vm_opt: Option<VmOptLevel>,
+
+        /// Pause before each VM instruction (commands: step, continue, print, quit)
+        #[arg(long)]
+        step: bool,
+
+        /// Explicit address for each party, e.g. "0=127.0.0.1:9001,1=127.0.0.1:9002"
+        #[arg(long, value_name = "MAP")]
+        parties_map: Option<String>,
+
+        /// JSON file mapping input names to values, e.g. {"salary": 120000}
+        #[arg(long, value_name = "FILE")]
+        inputs: Option<std::path::PathBuf>,
+
+        /// Index of the party the --inputs belong to [default: 0]
+        #[arg(long, value_name = "INDEX", requires = "inputs")]
+        party: Option<u8>,
+
+        /// Seed for the simulation's randomness; printed when chosen at random
+        #[arg(long, value_name = "SEED")]
+        seed: Option<u64>,
+    },
+
+    /// Time repeated simulated runs of a program compiled with -O3
+    Bench {
+        /// Program to benchmark [default: src/main.stfl]
+        file: Option<String>,
+
+        /// Number of measured runs
+        #[arg(long, default_value = "10", value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+
+        /// Runs before measuring, left out of the statistics
+        #[arg(long, default_value = "1")]
+        warmup: u32,
+
+        /// Number of parties (minimum 5 for HoneyBadger) [default: 5]
+        #[arg(long)]
+        parties: Option<u8>,
+
+        /// MPC protocol to benchmark [default: honeybadger]
+        #[arg(long)]
+        protocol: Option<MpcProtocol>,
+
+        /// Security threshold (max corrupted parties, auto-calculated if not provided)
+        #[arg(long)]
+        threshold: Option<u8>,
+
+        /// Field type for computation [default: bls12-381]
+        #[arg(long)]
+        field: Option<MpcField>,
+
+        /// Use the named [mpc.profiles.<NAME>] settings from Stoffel.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Seed shared by every run; printed when chosen at random
+        #[arg(long, value_name = "SEED")]
+        seed: Option<u64>,
+    },
+
+    /// Deploy the current project
+    Deploy {
+        /// Deployment environment
+        #[arg(short, long, default_value = "local")]
+        environment: String,
+
+        /// Package a TEE deployment bundle into <out-dir>/<package>-<version>-tee
+        #[arg(long)]
+        tee: bool,
+
+        /// Generate a Kubernetes manifest
+        #[arg(long)]
+        k8s: bool,
+
+        /// Number of parties the deployment provisions
+        #[arg(long)]
+        parties: Option<u8>,
+
+        /// Named MPC profile from [mpc.profiles] in Stoffel.toml
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Deploy artifacts from target/release instead of target/debug
+        #[arg(long)]
+        release: bool,
+
+        /// Overwrite previously generated deployment files, and deploy even without
+        /// a build manifest or when it was produced for a different party count
+        #[arg(long)]
+        force: bool,
+
+        /// Like --force for the build manifest check, without overwriting generated files
+        #[arg(long)]
+        allow_party_mismatch: bool,
+
+        /// Deploy even though the configured field is not secure (prime61)
+        #[arg(long)]
+        allow_insecure_field: bool,
+
+        /// Directory to write generated deployment files to [default: deploy]
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<std::path::PathBuf>,
+
+        /// Where to write the Kubernetes manifest [default: <out-dir>/k8s.yaml]
+        #[arg(long, value_name = "FILE", requires = "k8s")]
+        out: Option<std::path::PathBuf>,
+    },
+
+    /// Add a dependency to the project
+    Add {
+        /// Package name
+        package: String,
+
+        /// Specific version
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Add as dev dependency
+        #[arg(long)]
+        dev: bool,
+
+        #[command(flatten)]
+        lock_flags: LockFlags,
+    },
+
+    /// Remove a dependency
+    Remove {
+        /// Package name
+        package: String,
+
+        /// Remove from dev dependencies
+        #[arg(long)]
+        dev: bool,
+
+        #[command(flatten)]
+        lock_flags: LockFlags,
+    },
+
+    /// Package the project for publishing; uploading to a registry is not supported yet
+    Publish {
+        /// Dry run without actually publishing
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Add, list, and run project examples
+    Example {
+        #[command(subcommand)]
+        action: ExampleCommands,
+    },
+
+    /// Install and manage plugins
+    Plugin {
+        #[command(subcommand)]
+        action: PluginCommands,
+    },
+
+    /// Show and install Stoffel-Lang compiler versions
+    Toolchain {
+        #[command(subcommand)]
+        action: ToolchainCommands,
+    },
+
+    /// Show CLI, compiler and build information for bug reports
+    Version,
+
+    /// Show reference information about fields and other MPC settings
+    Info {
+        #[command(subcommand)]
+        topic: InfoCommands,
+    },
+
+    /// Explain how MPC settings are derived and what they guarantee
+    Explain {
+        #[command(subcommand)]
+        topic: ExplainCommands,
+    },
+
+    /// Check Stoffel.toml and project layout without compiling
+    Validate,
+
+    /// Diagnose the compiler, git and project setup, e.g. before filing an issue
+    Doctor,
+
+    /// Explain why a package is included in the dependency graph
+    Why {
+        /// Package name
+        package: String,
+    },
+
+    /// Check the status of the current project
+    Status,
+
+    /// Clean build artifacts
+    Clean {
+        /// Also clear the global caches in ~/.stoffel/cache
+        #[arg(long, conflicts_with = "cache_only")]
+        all: bool,
+
+        /// Clear only the global caches, leaving the project's target/ in place
+        #[arg(long)]
+        cache_only: bool,
+
+        /// Don't ask for confirmation before clearing global caches
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Update dependencies
+    Update {
+        /// Package to update (all if not specified)
+        package: Option<String>,
+
+        #[command(flatten)]
+        lock_flags: LockFlags,
+    },
+}
+
+/// Lockfile and network restrictions shared by dependency-aware commands
+#[derive(Args, Debug, Clone, Copy)]
+struct LockFlags {
+    /// Require Stoffel.lock to be up to date and never modify it
+    #[arg(long)]
+    locked: bool,
+
+    /// Never access the network
+    #[arg(long)]
+    offline: bool,
+
+    /// Equivalent to --locked --offline
+    #[arg(long)]
+    frozen: bool,
+}
+
+/// Effective lockfile and network restrictions after applying --frozen
+#[derive(Debug, Clone, Copy)]
+struct LockPolicy {
+    locked: bool,
+    offline: bool,
+}
+
+impl LockFlags {
+    fn policy(&self) -> LockPolicy {
+        LockPolicy {
+            locked: self.locked || self.frozen,
+            offline: self.offline || self.frozen,
+        }
+    }
+}
+
+impl LockPolicy {
+    /// Fail if an operation would have to rewrite Stoffel.lock
+    fn ensure_lockfile_writable(&self, operation: &str) -> Result<(), String> {
+        if self.locked {
+            return Err(format!(
+                "Cannot {} with --locked or --frozen: Stoffel.lock would need to change",
+                operation
+            ));
+        }
+        Ok(())
+    }
+
+    fn print(&self) {
+        if self.locked {
+            info!("   Lockfile: Locked");
+        }
+        if self.offline {
+            info!("   Network: Offline");
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ExampleCommands {
+    /// Create examples/<name>.stfl from a starter template
+    Add {
+        /// Example name
+        name: String,
+    },
+
+    /// List the examples in this project
+    List,
+
+    /// Compile and run an example
+    Run {
+        /// Example name
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum PluginCommands {
+    /// Install a plugin into ~/.stoffel/plugins
+    Install {
+        /// Local path or git URL of the plugin
+        source: String,
+    },
+
+    /// List installed plugins
+    List,
+
+    /// Remove a plugin
+    Remove {
+        /// Plugin name
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ToolchainCommands {
+    /// Show the CLI version, the compiler in use and installed toolchains
+    Status,
+
+    /// Install a compiler into ~/.stoffel/toolchains/<version> and make it active
+    Install {
+        /// Version to install the compiler as
+        version: String,
+
+        /// Compiler binary or built Stoffel-Lang checkout to install from
+        #[arg(long, value_name = "PATH")]
+        path: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum InfoCommands {
+    /// Show the modulus, bit length and security level of a field
+    Field {
+        /// Field to describe; all fields are listed when omitted
+        name: Option<MpcField>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ExplainCommands {
+    /// Show the threshold calculated for a party count and the corruption it tolerates
+    Threshold {
+        /// Number of parties
+        #[arg(long, default_value_t = 5)]
+        parties: u8,
+
+        /// MPC protocol
+        #[arg(long, default_value = "honeybadger")]
+        protocol: MpcProtocol,
+    },
+
+    /// Show what a field's modulus and security level mean for a program
+    Field {
+        /// Field to explain
+        name: MpcField,
+    },
+}
+
+/// Available MPC protocols
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
+enum MpcProtocol {
+    /// HoneyBadger MPC protocol (default, production-ready)
+    Honeybadger,
+    /// Shamir secret sharing with passive security (honest majority, semi-honest parties)
+    Shamir,
+    /// BGW with active security (tolerates fewer than a third of parties misbehaving)
+    Bgw,
+}
+
+/// Party-count and corruption-threshold rules of an MPC protocol
+struct ProtocolSpec {
+    /// Protocol name as written in messages
+    name: &'static str,
+    /// Fewest parties the protocol can run with
+    min_parties: u8,
+    /// Divisor `d` of the corruption bound `t < n/d`
+    threshold_divisor: u8,
+    /// How corrupted parties may behave without breaking the protocol
+    adversary: &'static str,
+}
+
+impl ProtocolSpec {
+    /// Largest threshold tolerated with `parties` parties
+    fn max_threshold(&self, parties: u8) -> u8 {
+        parties.div_ceil(self.threshold_divisor).saturating_sub(1)
+    }
+
+    /// The corruption bound as quoted in messages, e.g. `t < n/3`
+    fn bound(&self) -> String {
+        format!("t < n/{}", self.threshold_divisor)
+    }
+}
+
+const MALICIOUS: &str = "malicious: they may deviate from the protocol arbitrarily";
+const SEMI_HONEST: &str = "semi-honest: they follow the protocol but may pool what they see";
+
+impl MpcProtocol {
+    /// The protocol's rules; validation and threshold calculation only read these
+    fn spec(&self) -> ProtocolSpec {
+        match self {
+            MpcProtocol::Honeybadger => ProtocolSpec { name: "HoneyBadger", min_parties: 5, threshold_divisor: 3, adversary: MALICIOUS },
+            MpcProtocol::Shamir => ProtocolSpec { name: "Shamir", min_parties: 3, threshold_divisor: 2, adversary: SEMI_HONEST },
+            MpcProtocol::Bgw => ProtocolSpec { name: "BGW", min_parties: 4, threshold_divisor: 3, adversary: MALICIOUS },
+        }
+    }
+}
+
+/// Available finite fields for MPC computation
+///
+/// Names, moduli and security levels live in `MpcField::spec`; the command-line
+/// and Stoffel.toml spellings are read from there too.
+#[derive(Debug, Clone, PartialEq)]
+enum MpcField {
+    Bls12_381,
+    Bn254,
     Secp256k1,
-    /// Prime field with 61-bit modulus (for testing)
-    #[value(name = "prime61")]
     Prime61,
 }
 
+/// Facts about an MPC field
+struct FieldSpec {
+    /// Name used on the command line and in Stoffel.toml
+    name: &'static str,
+    description: &'static str,
+    /// Prime modulus as a hex literal
+    modulus: &'static str,
+    /// Bit length of the modulus
+    bits: u16,
+    /// Approximate security level in bits; `None` for fields that are for testing only
+    security_bits: Option<u16>,
+}
+
+impl MpcField {
+    fn spec(&self) -> FieldSpec {
+        match self {
+            MpcField::Bls12_381 => FieldSpec {
+                name: "bls12-381",
+                description: "BLS12-381 scalar field (default, recommended)",
+                modulus: "0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+                bits: 255,
+                security_bits: Some(128),
+            },
+            MpcField::Bn254 => FieldSpec {
+                name: "bn254",
+                description: "BN254 scalar field",
+                modulus: "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+                bits: 254,
+                security_bits: Some(100),
+            },
+            MpcField::Secp256k1 => FieldSpec {
+                name: "secp256k1",
+                description: "Secp256k1 scalar field",
+                modulus: "0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+                bits: 256,
+                security_bits: Some(128),
+            },
+            MpcField::Prime61 => FieldSpec {
+                name: "prime61",
+                description: "Prime field with 61-bit modulus (for testing)",
+                modulus: "0x1fffffffffffffff",
+                bits: 61,
+                security_bits: None,
+            },
+        }
+    }
+}
+
+impl ValueEnum for MpcField {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[MpcField::Bls12_381, MpcField::Bn254, MpcField::Secp256k1, MpcField::Prime61]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        let spec = self.spec();
+        Some(clap::builder::PossibleValue::new(spec.name).help(spec.description))
+    }
+}
+
+/// Output formats for `stoffel test --coverage`
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
+enum CoverageFormat {
+    /// Percentage covered plus the list of uncovered procs
+    Summary,
+    /// Summary plus an lcov tracefile in target/coverage/lcov.info
+    Lcov,
+}
+
+/// Output formats for per-file compile results
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+enum MessageFormat {
+    /// Human-readable progress and compiler output
+    Human,
+    /// One JSON object per file, for editors and CI
+    Json,
+}
+
+/// Output formats for `stoffel graph`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+enum GraphFormat {
+    /// Graphviz DOT, e.g. for `dot -Tsvg`
+    Dot,
+    /// Mermaid flowchart, e.g. for Markdown docs
+    Mermaid,
+}
+
+/// Report formats for `stoffel test`
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
+enum TestReportFormat {
+    /// Console output only
+    Human,
+    /// Console output plus a JUnit XML file for CI
+    Junit,
+}
+
 /// VM optimization levels
 #[derive(ValueEnum, Debug, Clone)]
 enum VmOptLevel {
@@ -489,1463 +2000,3301 @@ enum VmOptLevel {
     Aggressive,
 }
 
-fn show_init_template_help() {
+fn display_honeybadger() {
     println!(r#"
-HELP: stoffel init --template (-t)
+    Stoffel is a honeybadger that helps you build MPC applications.
+    Honeybadgers are a fearless breed of animals that are known for their tenacity and resilience.
+    MPC is a powerful tool that allows you to build applications that are secure, scalable, and efficient. Just like Stoffel.
+
+                                                                                                                                                  
+                                                   @    .                                           
+                                                @@@@@@@@@*@@                                        
+                                              @@+-@   --@@@@                                        
+                                          @@@@ --------------@@@                                    
+                                     @@@@   -----------------@@@@@                                  
+                                 @@@@  ---------------------------@@@@                              
+                              *@@@  :::::::::::::::::::-------------- @@@                           
+                            @@@  :::::::::::::::::::::::::::------------ @@                         
+                          @@@  :::::::::::::::::::::::::::::::::-*----%--- @@                       
+                         @@  :+=%%%%%%%%@%@@@:::::::::::::@::::=%%@%@-@%%%@- @@                     
+                       @@:%%%%%%%%%%%%#########%::::::::##%%%%%%%%%%%%%%%%%%%@ @@.                  
+                      @@-%%%%%%%%%################@:@#########################%@@@                  
+                     @:#%%#######################################################@@                 
+                   @@:#############@@#############################%@##############:@                
+                  @@:##################@#######################@###################@@@@##@@         
+           @@##@@@@:######################@#################@*######################@###@#@         
+          @@#@####:########################*@#############@++##########################%%@@@.       
+          @#%%%#############################+@###########@++##########################@%%%@@        
+          @#%%%%##########@@=====@@@@@######++%#####****%@+*#####@@@@@@====@@#########%%%%%@        
+         #@#%%%%@##########@=====@  @@@@@@###+%*********%@***#@% @@@@@=====@#########@#@%%%@        
+          @#%%@#############@....@%%%%%%@@#**@***********#***@@  %%%%@....@############@%%@@        
+          @:%%%@###########*#@....@%####.-*******##@@@##*****@.%%%@@@....@@*############%#@@        
+          @##%@##############*++@@..@@@...*****++++++++++****+  @@@..@@++*############%%%:@         
+           @#%%%#################++#####@.*********@@@******* @*****#+++##############%%:@%         
+           @@#%%########################****@%%%%%%%%%%%%%%@********###################:@@          
+            @@%:#########%%@@@%%%#######***@@@@   .        @@*******####%%@@%%########:@@           
+             @@:##############@##########***%%%@%%%%%%%%%%%%********##%#@@############@:@           
+             @@##%#############@##########***%%%#%%%%%%@%%%********####@=#############:@@@          
+             @:%%##############@=#############**@#%%%%#@*********#####@=##############:@            
+            .@%%@#%%############@=#######@#########@%################@=#############%##@+           
+            @@#@:#%%#############==@###############################%=@@#############%#:@            
+               @:%%%%#############@=.@%#######%%%%%%%%%%%#######%%@.=@#############%%%:@            
+               @@%%%%%%%###########=..@=.@@@@@@@@-....@@@@@@@@@==@..@##############:%:@@            
+               @@%@:%%%%%###########=..==......................=@.==###########%:#:@@:@             
+                @%@@:%%%%%%#########@..@=...@...=@....@=..@...==...###########%:%:@@@@              
+                @@  @:%%%%%%%%########..=..@..................=@.@#######%%#%:@@:@@                 
+                     @@:%@::%%%%#######@-=@.................=.=@#######%%%%%:@@@@                   
+                       @@:@@@::%%%%##########@@@@@@@@@@@@@%##@#######%%:%%:@@                       
+                          @@@@@@::%@:%#############################%::@@%@@                         
+                                 @@:@@@::######################%:%:@@ @:@                           
+                                    @@ @@@:%%%%############%%%:*@@                                  
+                                           @@::@:%%%%%%:%%:@%:@                                     
+                                             @@@@@:::@@+@@@@@                                       
+                                                    @ +                                             
+                                                                                                    
+                                                                                                    
+                                                                                                    
+                                                                                                    
+                                                                                                    
+                                                                                                    
 
-DESCRIPTION:
-    The --template (-t) flag specifies which programming language ecosystem
-    template to use when initializing a new Stoffel project.
 
-USAGE:
-    stoffel init --template <TEMPLATE> [PROJECT_NAME]
-    stoffel init -t <TEMPLATE> [PROJECT_NAME]
+"#);
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let json = cli.json;
+    if let Err(e) = run_cli(cli) {
+        if json {
+            print_json(&ErrorReport { error: &e });
+        } else {
+            eprintln!("{} {}", style::prefix(Status::Failure), e);
+        }
+        std::process::exit(1);
+    }
+}
+
+fn run_cli(cli: Cli) -> Result<(), String> {
+    // If no subcommand is provided, show the honeybadger
+    if std::env::args().len() == 1 {
+        display_honeybadger();
+        return Ok(());
+    }
+
+    style::init(cli.color);
+    // Status lines would only clutter JSON output, so keep to warnings and errors
+    logging::init(cli.verbose, cli.quiet || cli.json);
+    let json = cli.json;
+    debug!("Running command: {:?}", cli.command);
+
+    if runs_per_member(&cli.command) {
+        if let Some(workspace) = config::find_workspace(std::path::Path::new("."))? {
+            return run_workspace(&workspace, cli.command, json);
+        }
+    }
+    run_command(cli.command, json)
+}
+
+/// Whether `command` runs in every member when started at a workspace root
+fn runs_per_member(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Build { .. } | Commands::Compile { file: None, .. } | Commands::Clean { cache_only: false, .. }
+    )
+}
+
+/// Run `command` in each workspace member in turn, stopping at the first failure
+fn run_workspace(workspace: &config::Workspace, command: Commands, json: bool) -> Result<(), String> {
+    if json {
+        return Err("--json isn't supported at a workspace root; run the command in a member directory".to_string());
+    }
+
+    // Global caches are shared, so `clean --all` clears them once after the members
+    let (command, clean_global) = match command {
+        Commands::Clean { all, cache_only, yes } => (Commands::Clean { all: false, cache_only, yes }, all.then_some(yes)),
+        command => (command, None),
+    };
+
+    let root = std::env::current_dir().map_err(|e| format!("Failed to read current directory: {}", e))?;
+    info!("🗂️  Workspace with {} member(s)", workspace.members.len());
+    for member in &workspace.members {
+        info!("");
+        info!("{} Member: {}", style::prefix(Status::Package), member.display());
+        std::env::set_current_dir(root.join(member))
+            .map_err(|e| format!("Failed to enter workspace member {}: {}", member.display(), e))?;
+        let result = run_command(command.clone(), json);
+        std::env::set_current_dir(&root)
+            .map_err(|e| format!("Failed to return to {}: {}", root.display(), e))?;
+        result.map_err(|e| format!("{}: {}", member.display(), e))?;
+    }
+
+    if let Some(yes) = clean_global {
+        clean_global_caches(yes)?;
+    }
+    Ok(())
+}
+
+fn run_command(command: Commands, json: bool) -> Result<(), String> {
+    match command {
+        Commands::Init { name, lib, path, interactive, template, template_git, answers, print_only, dry_run, force, git, list_templates } => {
+            if list_templates {
+                init::list_templates();
+                return Ok(());
+            }
+
+            let init_options = init::InitOptions {
+                name,
+                lib,
+                path,
+                interactive,
+                template,
+                template_git,
+                answers,
+                print_only,
+                dry_run,
+                force,
+                git,
+            };
+
+            init::initialize_project(init_options).map_err(|e| format!("Initialization failed: {}", e))?;
+        }
+
+        Commands::New { name, lib, template, path, interactive, git } => {
+            let options = init::InitOptions {
+                name: Some(name),
+                lib,
+                path,
+                interactive,
+                template,
+                template_git: None,
+                answers: None,
+                print_only: false,
+                dry_run: false,
+                force: false,
+                git,
+            };
+
+            init::new_project(options).map_err(|e| format!("Project creation failed: {}", e))?;
+        }
+
+        Commands::Compile { file, output, binary, disassemble, print_ir, opt_level, std_path, defines, output_dir, timings, slow_threshold, watch, no_cache, out_dir, include, exclude, fail_fast, message_format } => {
+            // Validate standard library override
+            if let Some(std_path) = &std_path {
+                if !std::path::Path::new(std_path).exists() {
+                    return Err(format!("Standard library path not found: {}", std_path));
+                }
+            }
+            if json && watch {
+                return Err("--watch can't be combined with --json".to_string());
+            }
+            // The JSON summary has no room for the IR dump, which would otherwise be dropped
+            if json && print_ir {
+                return Err("--print-ir can't be combined with --json".to_string());
+            }
+            if disassemble {
+                let Some(file) = file else {
+                    return Err("--disassemble needs a compiled file, e.g. `stoffel disasm target/debug/main.bin`".to_string());
+                };
+                return disassemble_file(&file, output.as_deref());
+            }
+
+            ensure_unique_defines(&defines)?;
+            let artifacts_dir = prepare_output_dir(output_dir.as_deref())?;
+
+            let options = CompileOptions {
+                binary,
+                print_ir,
+                opt_level,
+                std_path,
+                defines,
+                ..Default::default()
+            };
+
+            let compiler_path = toolchain::find_compiler()?;
+
+            let extension = if binary { "bin" } else { "bc" };
+            let out_dir_output = |file: &str| -> Result<Option<String>, String> {
+                out_dir.as_deref()
+                    .map(|dir| prepare_artifact_path(std::path::Path::new(dir), file, extension))
+                    .transpose()
+            };
+
+            // IR dumps are wanted for their output, so never skip them
+            let mut compile_cache = (!no_cache && !print_ir)
+                .then(|| compile_cache::CompileCache::load(std::path::Path::new(".")));
+
+            match file {
+                Some(specific_file) => {
+                    let output = match output {
+                        Some(output) => Some(output),
+                        None => out_dir_output(&specific_file)?,
+                    };
+
+                    if json {
+                        let jobs = [(specific_file, output)];
+                        let outcome = compile_batch(&compiler_path, &jobs, &options, compile_cache.as_mut(), None, false)?;
+                        if let Some(cache) = &compile_cache {
+                            cache.save(std::path::Path::new("."))?;
+                        }
+                        print_json(&outcome.summary());
+                        if timings {
+                            write_timings_report(&artifacts_dir, &outcome.timings)?;
+                        }
+                        if outcome.failed > 0 {
+                            std::process::exit(1);
+                        }
+                        return Ok(());
+                    }
+
+                    // Compile specific file
+                    if message_format == MessageFormat::Human {
+                        info!("🔧 Compiling StoffelLang file: {}", specific_file);
+                    }
+
+                    let mut cache = compile_cache.as_mut().map(|cache| -> Result<_, String> {
+                        Ok((cache_entry(&specific_file, &output, &options, &cache_inputs(&compiler_path, &options)?)?, cache))
+                    }).transpose()?;
+                    if let Some((entry, cache)) = &cache {
+                        if cache.is_fresh(&specific_file, entry) {
+                            report_up_to_date(&specific_file, message_format);
+                            return Ok(());
+                        }
+                    }
+
+                    let started = std::time::Instant::now();
+                    let run = run_compiler(&compiler_path, &specific_file, &output, &options)?;
+                    run.report(&specific_file, message_format);
+                    let success = run.success;
+                    if let Some((entry, cache)) = cache.take().filter(|_| success) {
+                        cache.record(&specific_file, entry);
+                        cache.save(std::path::Path::new("."))?;
+                    }
+
+                    if timings {
+                        let file_timings = [timings::FileTiming {
+                            file: specific_file.clone(),
+                            start: std::time::Duration::ZERO,
+                            duration: started.elapsed(),
+                            success,
+                        }];
+                        write_timings_report(&artifacts_dir, &file_timings)?;
+                    }
+
+                    if !success {
+                        std::process::exit(1);
+                    }
+                }
+                None => {
+                    let human = !json && message_format == MessageFormat::Human;
+
+                    // Compile all files in src/ directory
+                    if human {
+                        info!("🔧 Compiling all StoffelLang files in src/ directory...");
+                    }
+
+                    // Check if src/ directory exists
+                    if !std::path::Path::new("src").exists() {
+                        return Err("No src/ directory found. Please run this command from a Stoffel project root,\n   or specify a specific file to compile.".to_string());
+                    }
+
+                    // Find all .stfl files in src/
+                    let filter = source_filter::SourceFilter::new("src", include, exclude);
+                    let stfl_files = filter.apply(find_stfl_files("src")?);
+
+                    if stfl_files.is_empty() && !json {
+                        if human {
+                            info!("{}  No .stfl files found in src/ directory.", style::prefix(Status::Info));
+                        }
+                        return Ok(());
+                    }
+
+                    if human {
+                        info!("   Found {} StoffelLang file(s) to compile:", stfl_files.len());
+                        for file in &stfl_files {
+                            info!("     - {}", file);
+                        }
+                        info!("");
+                    }
+
+                    // For batch compilation, don't use custom output names (they would conflict)
+                    let file_output = if output.is_some() && (stfl_files.len() > 1 || out_dir.is_some()) {
+                        eprintln!("{}  Custom output path ignored for batch compilation (use --out-dir)", style::prefix(Status::Warning));
+                        None
+                    } else {
+                        output.clone()
+                    };
+                    let output_for = |file: &str| -> Result<Option<String>, String> {
+                        Ok(out_dir_output(file)?.or_else(|| file_output.clone()))
+                    };
+                    let jobs = stfl_files
+                        .iter()
+                        .map(|file| Ok((file.clone(), output_for(file)?)))
+                        .collect::<Result<Vec<(String, Option<String>)>, String>>()?;
+
+                    let format = (!json).then_some(message_format);
+                    let outcome = compile_batch(&compiler_path, &jobs, &options, compile_cache.as_mut(), format, fail_fast)?;
+                    if let Some(cache) = &compile_cache {
+                        cache.save(std::path::Path::new("."))?;
+                    }
+
+                    if json {
+                        print_json(&outcome.summary());
+                    } else if human {
+                        outcome.print_summary("Compilation Summary");
+
+                        if let Some(slow_threshold) = slow_threshold {
+                            print_slow_files(&outcome.timings, slow_threshold);
+                        }
+                    }
+
+                    if timings {
+                        write_timings_report(&artifacts_dir, &outcome.timings)?;
+                    }
+
+                    if watch {
+                        if human {
+                            info!("");
+                            info!("👀 Watching src/ for changes (Ctrl-C to stop)...");
+                        }
+                        return watch::watch_sources(std::path::Path::new("src"), |changed| {
+                            let file = changed.to_string_lossy();
+                            if !filter.matches(&file) {
+                                return;
+                            }
+                            if human {
+                                info!("[{}] 🔄 Recompiling {}", watch::timestamp(), file);
+                            }
+                            let result = output_for(&file)
+                                .and_then(|output| run_compiler(&compiler_path, &file, &output, &options));
+                            match result {
+                                // A single file needs no grouping, just the timestamped status
+                                Ok(run) if human => {
+                                    run.print();
+                                    let mark = style::prefix(if run.success { Status::Success } else { Status::Failure });
+                                    println!("[{}] {} {}", watch::timestamp(), mark, file);
+                                }
+                                Ok(run) => run.report(&file, message_format),
+                                Err(e) => eprintln!("[{}] {} {}", watch::timestamp(), style::prefix(Status::Failure), e),
+                            }
+                        });
+                    }
 
-AVAILABLE TEMPLATES:
+                    if outcome.failed > 0 {
+                        // The JSON summary already reports the failures
+                        if json {
+                            std::process::exit(1);
+                        }
+                        return Err(format!("{} file(s) failed to compile", outcome.failed));
+                    } else if human {
+                        println!("{} All files compiled successfully!", style::prefix(Status::Done));
+                    }
+                }
+            }
+        }
 
-  python
-    ├─ Full Python SDK integration with StoffelProgram and StoffelClient
-    ├─ Creates: src/main.py, src/secure_computation.stfl, pyproject.toml
-    ├─ Dependencies: Poetry, stoffel-python-sdk
-    ├─ Status: ✅ Fully implemented with working MPC examples
-    └─ Best for: Python developers, data science, rapid prototyping
+        Commands::Check { file } => {
+            let files = match file {
+                Some(file) => {
+                    if !std::path::Path::new(&file).exists() {
+                        return Err(format!("File not found: {}", file));
+                    }
+                    vec![file]
+                }
+                None => {
+                    if !std::path::Path::new("src").exists() {
+                        return Err("No src/ directory found. Please run this command from a Stoffel project root,\n   or specify a specific file to check.".to_string());
+                    }
+                    find_stfl_files("src")?
+                }
+            };
+            if files.is_empty() {
+                info!("{}  No .stfl files found in src/ directory.", style::prefix(Status::Info));
+                return Ok(());
+            }
 
-  rust
-    ├─ Rust FFI integration with StoffelVM (development skeleton)
-    ├─ Creates: src/main.rs, Cargo.toml with FFI dependencies
-    ├─ Dependencies: libc, tokio (StoffelVM crates when available)
-    ├─ Status: 🚧 Development skeleton, FFI integration pending
-    └─ Best for: Performance-critical applications, systems programming
+            let compiler_path = toolchain::find_compiler()?;
+            let options = CompileOptions {
+                check: true,
+                ..Default::default()
+            };
 
-  typescript
-    ├─ TypeScript/Node.js client integration (development skeleton)
-    ├─ Creates: src/main.ts, package.json, tsconfig.json
-    ├─ Dependencies: @stoffel/sdk (when available)
-    ├─ Status: 🚧 Development skeleton, SDK implementation pending
-    └─ Best for: Web applications, JavaScript ecosystem integration
+            info!("🔍 Checking {} StoffelLang file(s)...", files.len());
+            let mut failed = 0;
+            for file in &files {
+                if compile_single_file(&compiler_path, file, &None, &options)? {
+                    println!("{} {}", style::prefix(Status::Success), file);
+                } else {
+                    println!("{} {}", style::prefix(Status::Failure), file);
+                    failed += 1;
+                }
+            }
 
-  solidity
-    ├─ Smart contracts with MPC result verification
-    ├─ Creates: contracts/StoffelMPC.sol, hardhat.config.js, deployment scripts
-    ├─ Dependencies: Hardhat, OpenZeppelin contracts
-    ├─ Status: 🚧 Development skeleton, on-chain verification concepts
-    └─ Best for: Blockchain integration, DeFi applications
+            if failed > 0 {
+                return Err(format!("{} of {} file(s) failed to check", failed, files.len()));
+            }
+            println!("{} No errors found!", style::prefix(Status::Done));
+        }
 
-  stoffel (default)
-    ├─ Pure StoffelLang implementation
-    ├─ Creates: src/main.stfl, tests/integration.stfl
-    ├─ Dependencies: None (native StoffelLang)
-    ├─ Status: ✅ Fully supported with proper syntax
-    └─ Best for: Learning StoffelLang, pure MPC applications
+        Commands::Graph { format } => {
+            let src_dir = std::path::Path::new("src");
+            if !src_dir.is_dir() {
+                return Err("No src/ directory found. Run this command from a Stoffel project root.".to_string());
+            }
+            let graph = graph::scan(src_dir, &find_stfl_files("src")?)?;
 
-EXAMPLES:
-    stoffel init -t python my-mpc-app          # Python template
-    stoffel init --template rust secure-calc   # Rust template
-    stoffel init -t solidity mpc-auction       # Solidity template
-    stoffel init my-project                    # Default (stoffel) template
+            for cycle in graph.cycles() {
+                eprintln!("{}  Import cycle between: {}", style::prefix(Status::Warning), cycle.join(", "));
+            }
+            match format {
+                GraphFormat::Dot => print!("{}", graph.to_dot()),
+                GraphFormat::Mermaid => print!("{}", graph.to_mermaid()),
+            }
+        }
 
-INTERACTIVE MODE:
-    Use -i/--interactive to get guided template selection with explanations:
+        Commands::Fmt { files, check } => {
+            let files = if files.is_empty() {
+                let mut files = Vec::new();
+                for dir in ["src", testing::TESTS_DIR, EXAMPLES_DIR] {
+                    if std::path::Path::new(dir).is_dir() {
+                        files.extend(find_stfl_files(dir)?);
+                    }
+                }
+                files
+            } else {
+                files
+            };
+            if files.is_empty() {
+                info!("{}  No .stfl files found to format.", style::prefix(Status::Info));
+                return Ok(());
+            }
 
-    stoffel init -i                           # Guided setup with template help
+            let formatter = formatter::Formatter::detect(toolchain::find_compiler().ok());
+            debug!("Formatting with the {}", formatter.describe());
+
+            let mut changed = Vec::new();
+            for file in &files {
+                let path = std::path::Path::new(file);
+                let original = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read {}: {}", file, e))?;
+                let formatted = formatter.format(path)?;
+                if formatted == original {
+                    continue;
+                }
+                if !check {
+                    std::fs::write(path, formatted).map_err(|e| format!("Failed to write {}: {}", file, e))?;
+                    println!("✨ Formatted {}", file);
+                }
+                changed.push(file);
+            }
 
-For more help: stoffel init --help
-"#);
-}
+            if check && !changed.is_empty() {
+                for file in &changed {
+                    println!("   {}", file);
+                }
+                return Err(format!("{} of {} file(s) need formatting. Run `stoffel fmt` to fix them.", changed.len(), files.len()));
+            }
+            if changed.is_empty() {
+                println!("{} {} file(s) already formatted", style::prefix(Status::Success), files.len());
+            }
+        }
 
-fn show_init_interactive_help() {
-    println!(r#"
-HELP: stoffel init --interactive (-i)
+        Commands::Disasm { file, output } => {
+            disassemble_file(&file, output.as_deref())?;
+        }
 
-DESCRIPTION:
-    The --interactive (-i) flag enables guided setup with step-by-step prompts
-    for configuring your new Stoffel project.
+        Commands::Dev { parties, port, bind, protocol, threshold, field, profile, explain_config, parties_map, allow_privileged } => {
+            let settings = resolve_mpc_settings(
+                MpcLayer { protocol, parties, threshold, field, source: ConfigSource::Flag },
+                profile.as_deref(),
+            )?;
+            if explain_config {
+                settings.explain();
+                return Ok(());
+            }
+            let MpcSettings { protocol, parties, threshold, field, .. } = settings;
 
-USAGE:
-    stoffel init --interactive [PROJECT_NAME]
-    stoffel init -i [PROJECT_NAME]
+            info!("🔧 Starting development server...");
+            if let Some(profile) = &profile {
+                info!("   Profile: {}", profile);
+            }
+            info!("   Parties: {}", parties);
+            info!("   Port: {}", port);
+            info!("   Protocol: {:?}", protocol);
+            info!("   Field: {:?}", field);
 
-INTERACTIVE FEATURES:
-    ├─ Project Configuration
-    │  ├─ Project name (with validation)
-    │  ├─ Description
-    │  └─ Author (auto-detected from git config)
-    │
-    ├─ MPC Configuration
-    │  ├─ Number of parties (minimum 5 for HoneyBadger)
-    │  ├─ Cryptographic field selection
-    │  └─ Security threshold (auto-calculated)
-    │
-    └─ Template Selection
-       ├─ Detailed explanations of each template
-       ├─ Recommendations based on use case
-       └─ Preview of files that will be created
+            info!("   Threshold: {}", threshold);
 
-EXAMPLES:
-    stoffel init -i                           # Interactive setup in current directory
-    stoffel init -i my-secure-app             # Interactive setup with project name
-    stoffel init --interactive --path /tmp    # Interactive setup at specific path
+            validate_mpc_params(parties, threshold, &protocol)?;
 
-WHEN TO USE:
-    ✅ First-time users learning Stoffel
-    ✅ When you want to explore all configuration options
-    ✅ Setting up complex MPC configurations
-    ✅ When unsure which template to choose
+            let status_address = std::net::SocketAddr::new(bind, port);
+            let listener = dev::ensure_port_available(status_address, allow_privileged)?;
 
-For more help: stoffel init --help
-"#);
-}
+            let party_addresses = resolve_party_addresses(parties, parties_map.as_deref())?;
+            for (index, address) in party_addresses.iter().enumerate() {
+                info!("   Party {}: {}", index, address);
+            }
 
-fn show_init_lib_help() {
-    println!(r#"
-HELP: stoffel init --lib
+            let status = std::sync::Arc::new(dev::DevStatus {
+                parties,
+                protocol: value_name(&protocol),
+                threshold,
+                field: value_name(&field),
+                generation: Default::default(),
+            });
+            dev::serve_status(listener, status.clone())?;
+            info!("   Health: http://{}/health", status_address);
+
+            let vm_path = toolchain::find_vm()?;
+            let entry = entry_point()?;
+            let program = entry_point_artifact(&entry);
+            let simulation = sim::Simulation {
+                vm_path: &vm_path,
+                program: &program,
+                protocol,
+                field,
+                threshold,
+                vm_opt: VmOptLevel::Standard,
+                addresses: &party_addresses,
+                inputs: None,
+                step: false,
+                seed: sim::random_seed(),
+                args: &[],
+                log_dir: std::path::Path::new(DEFAULT_OUTPUT_DIR).join(sim::LOG_DIR),
+                coverage: None,
+            };
+            debug!("   Seed: {}", simulation.seed);
 
-DESCRIPTION:
-    The --lib flag creates a library project instead of a standalone application.
-    Libraries are designed for reuse and distribution as dependencies.
+            info!("");
+            info!("👀 Watching sources and {} for changes (Ctrl-C to stop)...", config::MANIFEST_NAME);
+            dev::reload_loop(&simulation, std::path::Path::new(&entry), &status, || build_entry_point(&entry).map(|_| ()))?;
+        }
 
-USAGE:
-    stoffel init --lib [PROJECT_NAME]
+        Commands::Build { target, optimize, opt_level, release, allow_insecure_field, output_dir, timings, slow_threshold, defines, examples, fail_fast, lock_flags } => {
+            if !std::path::Path::new("Stoffel.toml").exists() {
+                return Err("No Stoffel.toml found in the current directory.\n   Run this command from a Stoffel project root, or use `stoffel compile <file>` for standalone files.".to_string());
+            }
+            let manifest = config::load_project(std::path::Path::new("."))?;
+            let field_context = if release {
+                FieldContext::Production { command: "stoffel build --release", allow_insecure: allow_insecure_field }
+            } else {
+                FieldContext::Development
+            };
+            check_field_security(&manifest.mpc.field, field_context)?;
 
-LIBRARY PROJECT STRUCTURE:
-    my-library/
-    ├── Stoffel.toml              # Package configuration
-    ├── src/
-    │   └── lib.stfl              # Library entry point with exported functions
-    └── README.md                 # Documentation
+            let opt_level = opt_level.unwrap_or(if optimize { 2 } else if release { 3 } else { 0 });
+            ensure_unique_defines(&defines)?;
 
-LIBRARY FEATURES:
-    ├─ Reusable MPC Functions
-    │  ├─ Exportable secure computation functions
-    │  ├─ Composable privacy-preserving algorithms
-    │  └─ Well-defined interfaces for integration
-    │
-    ├─ Distribution Ready
-    │  ├─ Proper package metadata
-    │  ├─ Dependency management
-    │  └─ Version compatibility
-    │
-    └─ Testing Infrastructure
-       ├─ Unit tests for individual functions
-       ├─ Integration tests for MPC workflows
-       └─ Benchmarking for performance validation
+            let lock_policy = lock_flags.policy();
+            let profile = if release { "release" } else { "debug" };
+            info!("🔨 Building {} v{}...", manifest.package.name, manifest.package.version);
+            if release {
+                info!("   Mode: Release");
+            } else {
+                info!("   Mode: Debug");
+            }
+            if let Some(target) = target {
+                info!("   Target: {}", target);
+            }
+            info!("   Optimization level: {}", opt_level);
+            let artifacts_dir = prepare_output_dir(output_dir.as_deref())?;
+            info!("   Output directory: {}", artifacts_dir.display());
+            if timings {
+                info!("   Timings: Enabled");
+            }
+            if let Some(slow_threshold) = slow_threshold {
+                info!("   Slow file threshold: {} ms", slow_threshold);
+            }
+            if examples {
+                info!("   Examples: Included");
+            }
+            for (key, value) in &defines {
+                info!("   Define: {}={}", key, value);
+            }
+            lock_policy.print();
 
-EXAMPLES:
-    stoffel init --lib crypto-utils           # Create cryptographic utilities library
-    stoffel init --lib --path ./libs mpc-ml  # Create ML library in specific directory
-    stoffel init --lib -i secure-stats       # Interactive library setup
+            let mut sources = Vec::new();
+            if std::path::Path::new("src").exists() {
+                sources.extend(find_stfl_files("src")?);
+            }
+            if examples && std::path::Path::new(EXAMPLES_DIR).exists() {
+                sources.extend(find_stfl_files(EXAMPLES_DIR)?);
+            }
+            // A configured entry point is part of every build, even from outside src/
+            if let Some(entry) = manifest.package.entry.as_deref() {
+                let entry = resolve_entry_point(Some(entry))?;
+                if !sources.contains(&entry) {
+                    sources.push(entry);
+                }
+            }
+            if !json {
+                if sources.is_empty() {
+                    info!("{}  No .stfl files found to build.", style::prefix(Status::Info));
+                    return Ok(());
+                }
+                println!();
+            }
 
-USE CASES:
-    ✅ Cryptographic primitives and utilities
-    ✅ Domain-specific MPC algorithms (ML, finance, healthcare)
-    ✅ Reusable privacy-preserving building blocks
-    ✅ Third-party integrations and connectors
+            let profile_dir = std::path::Path::new(DEFAULT_OUTPUT_DIR).join(profile);
+            let mut jobs = Vec::new();
+            for source in &sources {
+                let artifact = prepare_artifact_path(&profile_dir, source, "bin")?;
+                jobs.push((source.clone(), Some(artifact)));
+            }
 
-For more help: stoffel init --help
-"#);
-}
+            let options = CompileOptions {
+                binary: true,
+                opt_level,
+                defines,
+                ..Default::default()
+            };
 
-fn show_init_path_help() {
-    println!(r#"
-HELP: stoffel init --path
+            let compiler_path = toolchain::find_compiler()?;
+
+            // Only a complete build gets a manifest; deploy tooling trusts it, so
+            // the previous one goes before anything is recompiled
+            let path = build_manifest::manifest_path(&profile_dir);
+            build_manifest::remove(&path)?;
+
+            let root = std::path::Path::new(".");
+            let mut compile_cache = compile_cache::CompileCache::load(root);
+            let format = (!json).then_some(MessageFormat::Human);
+            let outcome = compile_batch(&compiler_path, &jobs, &options, Some(&mut compile_cache), format, fail_fast)?;
+            compile_cache.save(root)?;
+
+            let manifest_path = if outcome.failed == 0 {
+                let settings = merge_mpc_layers(&[config::base_layer(&manifest)?])?;
+                let artifacts = jobs
+                    .iter()
+                    .filter_map(|(source, output)| output.as_deref().map(|output| build_manifest::Artifact::new(source, output)))
+                    .collect::<Result<Vec<_>, String>>()?;
+                build_manifest::save(&path, &build_manifest::Manifest {
+                    opt_level,
+                    protocol: value_name(&settings.protocol),
+                    parties: settings.parties,
+                    threshold: settings.threshold,
+                    field: value_name(&settings.field),
+                    artifacts,
+                })?;
+                Some(path)
+            } else {
+                None
+            };
 
-DESCRIPTION:
-    The --path flag specifies where to create the new Stoffel project.
-    If the directory doesn't exist, it will be created.
+            if json {
+                print_json(&BuildReport {
+                    package: &manifest.package.name,
+                    version: &manifest.package.version,
+                    profile,
+                    opt_level,
+                    artifacts: profile_dir.to_string_lossy(),
+                    manifest: manifest_path.as_deref().map(|path| path.to_string_lossy()),
+                    summary: outcome.summary(),
+                });
+            } else {
+                outcome.print_summary("Build Summary");
+                println!("   {} Artifacts: {}", style::prefix(Status::Package), profile_dir.display());
+                if let Some(path) = &manifest_path {
+                    println!("   📋 Manifest: {}", path.display());
+                }
 
-USAGE:
-    stoffel init --path <DIRECTORY> [PROJECT_NAME]
+                if let Some(slow_threshold) = slow_threshold {
+                    print_slow_files(&outcome.timings, slow_threshold);
+                }
+            }
 
-PATH BEHAVIOR:
-    ├─ Absolute Paths: /home/user/projects/my-app
-    ├─ Relative Paths: ./my-project, ../parent-dir/project
-    ├─ Auto-creation: Creates directories if they don't exist
-    └─ Validation: Ensures write permissions and valid path
+            if timings {
+                write_timings_report(&artifacts_dir, &outcome.timings)?;
+            }
 
-EXAMPLES:
-    stoffel init --path /tmp/test-project              # Absolute path
-    stoffel init --path ./secure-apps my-app           # Relative path
-    stoffel init --path ~/Development/MPC secure-calc  # Home directory
-    stoffel init --path . existing-dir                 # Current directory
+            if outcome.failed > 0 {
+                // The JSON report already carries the failures
+                if json {
+                    std::process::exit(1);
+                }
+                return Err(format!("Build failed: {} file(s) failed to compile", outcome.failed));
+            } else if !json {
+                println!("{} Build finished successfully!", style::prefix(Status::Done));
+            }
+        }
 
-PATH RESOLUTION:
-    Without --path:    Uses current directory or creates subdirectory with project name
-    With --path:       Creates project at specified location
+        Commands::Test { test, parties, protocol, threshold, field, profile, explain_config, integration, coverage, coverage_format, format, out, seed } => {
+            let settings = resolve_mpc_settings(
+                MpcLayer { protocol, parties, threshold, field, source: ConfigSource::Flag },
+                profile.as_deref(),
+            )?;
+            if explain_config {
+                settings.explain();
+                return Ok(());
+            }
+            let MpcSettings { protocol, parties, threshold, field, .. } = settings;
 
-COMBINED WITH OTHER FLAGS:
-    stoffel init --path /tmp --lib my-library          # Library at specific path
-    stoffel init --path ./apps -t python webapp        # Python template at path
-    stoffel init --path ~/projects -i                  # Interactive at path
+            info!("🧪 Running tests...");
+            if let Some(profile) = &profile {
+                info!("   Profile: {}", profile);
+            }
+            info!("   Parties: {}", parties);
+            info!("   Protocol: {:?}", protocol);
+            info!("   Field: {:?}", field);
 
-VALIDATION:
-    ✅ Checks directory write permissions
-    ✅ Warns if directory is not empty
-    ✅ Creates parent directories as needed
-    ⚠️  Fails if path exists and contains Stoffel.toml
+            info!("   Threshold: {}", threshold);
 
-For more help: stoffel init --help
-"#);
-}
+            validate_mpc_params(parties, threshold, &protocol)?;
 
-// Dev command help functions
-fn show_dev_parties_help() {
-    println!(r#"
-HELP: stoffel dev --parties
+            if let Some(test) = &test {
+                info!("   Specific test: {}", test);
+            }
+            if integration {
+                info!("   Type: Integration tests");
+            }
+            if coverage {
+                info!("   Coverage: {:?}", coverage_format);
+            }
 
-DESCRIPTION:
-    The --parties flag specifies the number of parties in the simulated MPC network.
-    For HoneyBadger protocol, minimum is 5 parties.
+            if !std::path::Path::new(testing::TESTS_DIR).is_dir() {
+                return Err(format!("No {}/ directory found. Add .stfl tests there to run them.", testing::TESTS_DIR));
+            }
+            let tests = testing::select(find_stfl_files(testing::TESTS_DIR)?, test.as_deref(), integration);
+            if tests.is_empty() {
+                return Err(match &test {
+                    Some(test) => format!("No test named '{}' in {}/", test, testing::TESTS_DIR),
+                    None => format!("No tests found in {}/", testing::TESTS_DIR),
+                });
+            }
+            let seed = resolve_seed(seed);
+            info!("   Found {} test(s)", tests.len());
+            info!("");
+
+            let compiler_path = toolchain::find_compiler()?;
+            let vm_path = toolchain::find_vm()?;
+            let party_addresses = resolve_party_addresses(parties, None)?;
+            let options = CompileOptions {
+                binary: true,
+                coverage,
+                ..Default::default()
+            };
+            let test_dir = std::path::Path::new(DEFAULT_OUTPUT_DIR).join("test");
+
+            let mut cases = Vec::new();
+            let mut collected = coverage::Coverage::default();
+            for file in &tests {
+                let name = testing::test_name(file);
+                let started = std::time::Instant::now();
+                let program = prepare_artifact_path(&test_dir, file, "bin")?;
+                let run = run_compiler(&compiler_path, file, &Some(program.clone()), &options)?;
+                let failure = if run.success {
+                    let simulation = sim::Simulation {
+                        vm_path: &vm_path,
+                        program: std::path::Path::new(&program),
+                        protocol: protocol.clone(),
+                        field: field.clone(),
+                        threshold,
+                        vm_opt: VmOptLevel::Standard,
+                        addresses: &party_addresses,
+                        inputs: None,
+                        step: false,
+                        seed,
+                        args: &[],
+                        log_dir: test_dir.join(sim::LOG_DIR).join(&name),
+                        coverage: coverage.then(|| test_dir.join(sim::LOG_DIR).join(&name).join(sim::COVERAGE_FILE)),
+                    };
+                    let failure = simulation.run().err().map(|e| (e, simulation.stderr_logs()));
+                    if let Some(tracefile) = simulation.coverage.as_deref().filter(|_| failure.is_none()) {
+                        let counts = std::fs::read_to_string(tracefile).map_err(|e| format!(
+                            "No coverage counters from {} ({}); the VM at {} may not support --coverage-out",
+                            name, e, vm_path.display()
+                        ))?;
+                        collected.merge_lcov(&counts).map_err(|e| format!("{}: {}", tracefile.display(), e))?;
+                    }
+                    failure
+                } else {
+                    run.print();
+                    Some(("compilation failed".to_string(), String::from_utf8_lossy(&run.stderr).to_string()))
+                };
+
+                match &failure {
+                    None => println!("   {} {}", style::prefix(Status::Success), name),
+                    Some((message, _)) => println!("   {} {}: {}", style::prefix(Status::Failure), name, message),
+                }
+                cases.push(junit::TestCase {
+                    name,
+                    duration: started.elapsed(),
+                    failure,
+                });
+            }
 
-USAGE:
-    stoffel dev --parties <NUMBER>
+            let failures: Vec<&str> = cases.iter()
+                .filter(|case| case.failure.is_some())
+                .map(|case| case.name.as_str())
+                .collect();
+            println!();
+            println!("📊 Test Summary:");
+            println!("   {} Passed: {}", style::prefix(Status::Success), cases.len() - failures.len());
+            println!("   {} Failed: {}", style::prefix(Status::Failure), failures.len());
+            for name in &failures {
+                println!("     - {}", name);
+            }
 
-PARTY CONFIGURATION:
-    Minimum:    5 parties (HoneyBadger protocol requirement)
-    Typical:    5-7 parties (good balance of security and performance)
-    Maximum:    No hard limit, but performance decreases with more parties
+            if format == TestReportFormat::Junit {
+                let report = out.unwrap_or_else(|| std::path::Path::new(DEFAULT_OUTPUT_DIR).join("junit.xml"));
+                let suite = config::load(std::path::Path::new(config::MANIFEST_NAME))
+                    .map(|manifest| manifest.package.name)
+                    .unwrap_or_else(|_| "stoffel".to_string());
+                junit::write_report(&report, &suite, &[("seed", seed.to_string())], &cases)?;
+                println!("   📄 JUnit report: {}", report.display());
+            }
 
-SECURITY IMPLICATIONS:
-    ├─ More parties = Higher security against corruption
-    ├─ Threshold = (parties - 1) / 3 for HoneyBadger
-    ├─ Can tolerate up to threshold corrupted parties
-    └─ Example: 7 parties can tolerate 2 corrupted parties
+            if coverage {
+                // The tests themselves aren't what coverage is measuring
+                collected.retain_files(|file| !std::path::Path::new(file).starts_with(testing::TESTS_DIR));
+                let summary = collected.summary();
+                println!();
+                println!("📊 Coverage:");
+                println!("   Procs: {}/{} ({:.1}%)", summary.procs_hit, summary.procs, coverage::Summary::percent(summary.procs_hit, summary.procs));
+                println!("   Lines: {}/{} ({:.1}%)", summary.lines_hit, summary.lines, coverage::Summary::percent(summary.lines_hit, summary.lines));
+                if !summary.uncovered.is_empty() {
+                    println!("   Uncovered procs:");
+                    for proc_name in &summary.uncovered {
+                        println!("     - {}", proc_name);
+                    }
+                }
+                if coverage_format == CoverageFormat::Lcov {
+                    let tracefile = std::path::Path::new(DEFAULT_OUTPUT_DIR).join(coverage::LCOV_PATH);
+                    collected.write_lcov(&tracefile)?;
+                    println!("   📄 lcov: {}", tracefile.display());
+                }
+            }
 
-PERFORMANCE CONSIDERATIONS:
-    ├─ More parties = More network communication
-    ├─ More parties = Slower computation
-    ├─ Development typically uses 5-7 parties
-    └─ Production may use 10+ parties for higher security
+            if !failures.is_empty() {
+                return Err(format!("{} test(s) failed", failures.len()));
+            }
+            println!("{} All tests passed!", style::prefix(Status::Done));
+        }
 
-EXAMPLES:
-    stoffel dev --parties 5                   # Minimum configuration (fast)
-    stoffel dev --parties 7                   # Balanced security/performance
-    stoffel dev --parties 10                  # Higher security (slower)
+        Commands::Run { args, parties, protocol, threshold, field, profile, explain_config, vm_opt, step, parties_map, inputs, party, seed } => {
+            let settings = resolve_mpc_settings(
+                MpcLayer { protocol, parties, threshold, field, source: ConfigSource::Flag },
+                profile.as_deref(),
+            )?;
+            let (vm_opt, vm_opt_source) = match vm_opt {
+                Some(vm_opt) => (vm_opt, ConfigSource::Flag),
+                None => (VmOptLevel::Standard, ConfigSource::BuiltIn),
+            };
+            if explain_config {
+                settings.explain();
+                print_explained("vm-opt", &value_name(&vm_opt), &vm_opt_source.describe("vm-opt", "vm_opt"));
+                return Ok(());
+            }
+            let MpcSettings { protocol, parties, threshold, field, .. } = settings;
 
-For more help: stoffel dev --help
-"#);
-}
+            info!("▶️  Running project...");
+            if let Some(profile) = &profile {
+                info!("   Profile: {}", profile);
+            }
+            info!("   Parties: {}", parties);
+            info!("   Protocol: {:?}", protocol);
+            info!("   Field: {:?}", field);
+            info!("   VM Optimization: {:?}", vm_opt);
 
-fn show_dev_port_help() {
-    println!(r#"
-HELP: stoffel dev --port (-p)
+            info!("   Threshold: {}", threshold);
+
+            validate_mpc_params(parties, threshold, &protocol)?;
+            check_field_security(&value_name(&field), FieldContext::Development)?;
 
-DESCRIPTION:
-    The --port (-p) flag specifies which port the development server listens on.
-    The server provides a web interface for monitoring MPC execution.
+            let party_addresses = resolve_party_addresses(parties, parties_map.as_deref())?;
+            for (index, address) in party_addresses.iter().enumerate() {
+                info!("   Party {}: {}", index, address);
+            }
 
-USAGE:
-    stoffel dev --port <PORT>
-    stoffel dev -p <PORT>
+            if !args.is_empty() {
+                info!("   Args: {:?}", args);
+            }
+            if step {
+                info!("   Stepping: Enabled");
+            }
+            let seed = resolve_seed(seed);
 
-PORT REQUIREMENTS:
-    ├─ Range: 1024-65535 (avoid privileged ports < 1024)
-    ├─ Available: Port must not be in use by another service
-    ├─ Firewall: Ensure port is not blocked by firewall
-    └─ Default: 8080 if not specified
+            if let Some(party) = party {
+                if party >= parties {
+                    return Err(format!("--party {} is out of range for {} parties (0-{})", party, parties, parties - 1));
+                }
+            }
+            if let Some(inputs_path) = &inputs {
+                let loaded = inputs::load(inputs_path)?;
+                match party {
+                    Some(party) => info!("   Inputs: {} for party {} ({} value(s))", inputs_path.display(), party, loaded.len()),
+                    None => info!("   Inputs: {} ({} value(s))", inputs_path.display(), loaded.len()),
+                }
+                for (name, value) in &loaded {
+                    debug!("     {} = {}", name, value);
+                }
+            }
+            info!("");
+
+            let program = build_entry_point(&entry_point()?)?;
+            let vm_path = toolchain::find_vm()?;
+
+            let simulation = sim::Simulation {
+                vm_path: &vm_path,
+                program: &program,
+                protocol,
+                field,
+                threshold,
+                vm_opt,
+                addresses: &party_addresses,
+                inputs: inputs.as_deref().map(|inputs| (inputs, party.unwrap_or(0))),
+                step,
+                seed,
+                args: &args,
+                log_dir: std::path::Path::new(DEFAULT_OUTPUT_DIR).join(sim::LOG_DIR),
+                coverage: None,
+            };
 
-DEVELOPMENT SERVER FEATURES:
-    ├─ Web Dashboard: Real-time MPC execution monitoring
-    ├─ Log Viewer: Detailed logs from all simulated parties
-    ├─ Performance Metrics: Computation time, network stats
-    ├─ Debug Interface: Inspect MPC state and variables
-    └─ Hot Reload Status: File change detection and recompilation
+            info!("▶️  Simulating {} parties locally: {}", parties, program.display());
+            print_simulation_output(parties, &simulation.run()?);
+        }
 
-EXAMPLES:
-    stoffel dev -p 3000                       # Run on port 3000
-    stoffel dev --port 8080                   # Default port (explicit)
-    stoffel dev --port 9000 --parties 7       # Custom port with more parties
+        Commands::Bench { file, iterations, warmup, parties, protocol, threshold, field, profile, seed } => {
+            let settings = resolve_mpc_settings(
+                MpcLayer { protocol, parties, threshold, field, source: ConfigSource::Flag },
+                profile.as_deref(),
+            )?;
+            let MpcSettings { protocol, parties, threshold, field, .. } = settings;
+            validate_mpc_params(parties, threshold, &protocol)?;
 
-COMMON PORTS:
-    3000    Often used for React/Node.js development
-    8080    Default for many development servers
-    8000    Alternative development port
-    5000    Common for Flask/Python applications
+            let file = match file {
+                Some(file) => file,
+                None => entry_point()?,
+            };
+            if !std::path::Path::new(&file).exists() {
+                return Err(format!("File not found: {}", file));
+            }
 
-For more help: stoffel dev --help
-"#);
-}
+            info!("⏱️  Benchmarking {}", file);
+            info!("   Parties: {}", parties);
+            info!("   Protocol: {:?}", protocol);
+            info!("   Field: {:?}", field);
+            info!("   Threshold: {}", threshold);
+            let seed = resolve_seed(seed);
+
+            let bench_dir = std::path::Path::new(DEFAULT_OUTPUT_DIR).join("bench");
+            let program = prepare_artifact_path(&bench_dir, &file, "bin")?;
+            let options = CompileOptions {
+                binary: true,
+                opt_level: 3,
+                ..Default::default()
+            };
+            info!("🔧 Compiling with -O3: {}", file);
+            let compiled = run_compiler(&toolchain::find_compiler()?, &file, &Some(program.clone()), &options)?;
+            if !compiled.success {
+                return Err(format!("Failed to compile {}:\n{}", file, String::from_utf8_lossy(&compiled.stderr).trim_end()));
+            }
 
-fn show_dev_protocol_help() {
-    println!(r#"
-HELP: stoffel dev --protocol
+            let vm_path = toolchain::find_vm()?;
+            let addresses = resolve_party_addresses(parties, None)?;
+            let simulation = sim::Simulation {
+                vm_path: &vm_path,
+                program: std::path::Path::new(&program),
+                protocol: protocol.clone(),
+                field: field.clone(),
+                threshold,
+                vm_opt: VmOptLevel::Aggressive,
+                addresses: &addresses,
+                inputs: None,
+                step: false,
+                seed,
+                args: &[],
+                log_dir: bench_dir.join(sim::LOG_DIR),
+                coverage: None,
+            };
 
-DESCRIPTION:
-    The --protocol flag specifies which MPC protocol to use for development.
-    Currently only HoneyBadger is supported.
+            info!("");
+            info!("▶️  {} warm-up and {} measured run(s)", warmup, iterations);
+            let mut samples = Vec::new();
+            for run in 1..=warmup + iterations {
+                let started = std::time::Instant::now();
+                simulation.run().map_err(|e| format!("Run {} failed: {}", run, e))?;
+                let elapsed = started.elapsed();
+                let measured = run > warmup;
+                debug!("   Run {}: {:.1} ms{}", run, elapsed.as_secs_f64() * 1000.0, if measured { "" } else { " (warm-up)" });
+                if measured {
+                    samples.push(elapsed);
+                }
+            }
+            let spread = timings::spread(&samples).ok_or("No measured runs")?;
+            let traffic = simulation.traffic();
+            let millis = |duration: std::time::Duration| duration.as_secs_f64() * 1000.0;
+
+            if json {
+                print_json(&BenchReport {
+                    file: &file,
+                    protocol: &value_name(&protocol),
+                    parties,
+                    threshold,
+                    field: &value_name(&field),
+                    seed,
+                    warmup,
+                    iterations,
+                    min_ms: millis(spread.min),
+                    median_ms: millis(spread.median),
+                    max_ms: millis(spread.max),
+                    traffic,
+                });
+                return Ok(());
+            }
 
-USAGE:
-    stoffel dev --protocol <PROTOCOL>
+            println!("⏱️  {} run(s) of {}:", iterations, file);
+            println!("   {:<8} {:>10.1} ms", "min", millis(spread.min));
+            println!("   {:<8} {:>10.1} ms", "median", millis(spread.median));
+            println!("   {:<8} {:>10.1} ms", "max", millis(spread.max));
+            if traffic.iter().all(Option::is_none) {
+                println!("   Communication: not reported by the VM");
+            } else {
+                println!();
+                println!("   {:<8} {:>10} {:>12}", "Party", "Messages", "Bytes");
+                for (index, traffic) in traffic.iter().enumerate() {
+                    match traffic {
+                        Some(traffic) => println!("   {:<8} {:>10} {:>12}", index, traffic.messages, traffic.bytes),
+                        None => println!("   {:<8} {:>10} {:>12}", index, "-", "-"),
+                    }
+                }
+            }
+        }
 
-AVAILABLE PROTOCOLS:
-    honeybadger (default)
-    ├─ Byzantine Fault Tolerant (BFT)
-    ├─ Asynchronous network model
-    ├─ Threshold: Can tolerate up to (n-1)/3 corrupted parties
-    ├─ Minimum parties: 5
-    ├─ Security: Production-ready, formally verified
-    └─ Performance: Good for most applications
+        Commands::Deploy { environment, tee, k8s, parties, profile, release, force, allow_party_mismatch, allow_insecure_field, out_dir, out } => {
+            // Only generated deployment files exist so far; nothing is pushed to remote environments
+            if environment != "local" && !tee && !k8s {
+                return Err(format!(
+                    "Deploying to the '{}' environment is not supported yet. Use --environment local, --k8s or --tee to generate deployment files.",
+                    environment
+                ));
+            }
 
-PROTOCOL FEATURES:
-    ├─ Robustness
-    │  ├─ Works even with network delays and failures
-    │  ├─ No synchronization assumptions
-    │  └─ Guaranteed termination under honest majority
-    │
-    ├─ Security
-    │  ├─ Information-theoretic security
-    │  ├─ Protects against adaptive adversaries
-    │  └─ Secure against Byzantine corruption
-    │
-    └─ Practical
-       ├─ Efficient for real-world deployments
-       ├─ Scales to reasonable party numbers
-       └─ Well-tested implementation
+            let manifest = config::load_project(std::path::Path::new("."))?;
+            let settings = manifest_mpc_settings(
+                &manifest,
+                MpcLayer { parties, source: ConfigSource::Flag, ..Default::default() },
+                profile.as_deref(),
+            )?;
+
+            info!("🚀 Deploying project...");
+            info!("   Environment: {}", environment);
+            if let Some(profile) = &profile {
+                info!("   Profile: {}", profile);
+            }
+            info!("   Parties: {}", settings.parties);
 
-EXAMPLES:
-    stoffel dev --protocol honeybadger        # Explicit protocol selection
-    stoffel dev                               # Uses honeybadger by default
+            let field_context = FieldContext::Production { command: "stoffel deploy", allow_insecure: allow_insecure_field };
+            check_field_security(&value_name(&settings.field), field_context)?;
 
-FUTURE PROTOCOLS:
-    Additional protocols may be added in future versions based on:
-    ├─ Research advances in MPC protocols
-    ├─ Specific use case requirements (speed vs security)
-    └─ Community feedback and requests
+            let build_dir = std::path::Path::new(DEFAULT_OUTPUT_DIR).join(if release { "release" } else { "debug" });
+            check_deployed_parties(&build_dir, settings.parties, force || allow_party_mismatch)?;
 
-For more help: stoffel dev --help
-"#);
-}
+            let deployment = deploy::Deployment {
+                name: manifest.package.name.clone(),
+                version: manifest.package.version.clone(),
+                parties: settings.parties,
+                protocol: value_name(&settings.protocol),
+                field: value_name(&settings.field),
+                threshold: settings.threshold,
+            };
 
-fn show_dev_threshold_help() {
-    println!(r#"
-HELP: stoffel dev --threshold
+            let out_dir = out_dir.unwrap_or_else(|| std::path::PathBuf::from(deploy::DEPLOY_DIR));
+            let entry = resolve_entry_point(manifest.package.entry.as_deref())?;
 
-DESCRIPTION:
-    The --threshold flag sets the maximum number of parties that can be corrupted
-    while maintaining security. Auto-calculated if not specified.
+            if tee {
+                let program = artifact_path(&build_dir, &entry, "bin");
+                let bundle = deploy::write_tee_bundle(&deployment, &program, &out_dir, force)?;
+                println!("{} Wrote TEE bundle: {}", style::prefix(Status::Success), bundle.display());
+                println!("   Follow {} to attest each party", bundle.join(deploy::TEE_README_NAME).display());
+                if !k8s {
+                    // The bundle is the whole deployment; no containers to generate
+                    return Ok(());
+                }
+            }
 
-USAGE:
-    stoffel dev --threshold <NUMBER>
+            if k8s {
+                let out = out.unwrap_or_else(|| out_dir.join(deploy::K8S_MANIFEST_NAME));
+                if deploy::write_generated(&out, &deploy::kubernetes(&deployment), force)? {
+                    println!("{} Wrote Kubernetes manifest: {}", style::prefix(Status::Success), out.display());
+                } else {
+                    println!("{}  Kept existing {} (pass --force to overwrite)", style::prefix(Status::Skipped), out.display());
+                }
+                println!("   Image: {} (must contain {})", deployment.image(), deploy::PROGRAM_PATH);
+                println!("   Apply it with: kubectl apply -f {}", out.display());
+            } else {
+                let artifact = artifact_path(&build_dir, &entry, "bin");
+                if !artifact.exists() {
+                    println!("{}  {} does not exist yet; run `stoffel build` before starting the cluster", style::prefix(Status::Warning), artifact.display());
+                }
+                let host_ports: Vec<u16> = resolve_party_addresses(settings.parties, None)?
+                    .iter()
+                    .map(|address| address.port())
+                    .collect();
+
+                let dockerfile = out_dir.join(deploy::DOCKERFILE_NAME);
+                let compose = out_dir.join(deploy::COMPOSE_NAME);
+                let generated = [
+                    (&dockerfile, deploy::dockerfile(&artifact.display().to_string())),
+                    (
+                        &compose,
+                        deploy::compose(
+                            &deployment,
+                            &deploy::project_context(&out_dir)?,
+                            &dockerfile.display().to_string(),
+                            &host_ports,
+                        ),
+                    ),
+                ];
+                for (path, content) in generated {
+                    if deploy::write_generated(path, &content, force)? {
+                        println!("{} Wrote {}", style::prefix(Status::Success), path.display());
+                    } else {
+                        println!("{}  Kept existing {} (pass --force to overwrite)", style::prefix(Status::Skipped), path.display());
+                    }
+                }
+                println!("   Start the cluster with: docker compose -f {} up --build", compose.display());
+            }
+        }
 
-THRESHOLD CALCULATION:
-    For HoneyBadger protocol: threshold = (parties - 1) / 3
+        Commands::Add { package, version, dev, lock_flags } => {
+            let lock_policy = lock_flags.policy();
+            lock_policy.ensure_lockfile_writable("add a dependency")?;
 
-    Examples:
-    ├─ 5 parties → threshold 1 (can tolerate 1 corrupted party)
-    ├─ 7 parties → threshold 2 (can tolerate 2 corrupted parties)
-    ├─ 10 parties → threshold 3 (can tolerate 3 corrupted parties)
-    └─ 16 parties → threshold 5 (can tolerate 5 corrupted parties)
+            let mut dependencies = deps::Dependencies::load(std::path::Path::new("."))?;
+            let version = version.unwrap_or_else(|| "*".to_string());
+            dependencies.add(&package, &version, dev)?;
 
-SECURITY IMPLICATIONS:
-    ├─ Higher threshold = More fault tolerance
-    ├─ Lower threshold = Less fault tolerance but faster
-    ├─ Threshold must be < parties/3 for HoneyBadger
-    └─ Invalid thresholds will cause initialization to fail
+            info!("{} Adding dependency: {}", style::prefix(Status::Package), package);
+            info!("   Version: {}", version);
+            if dev {
+                info!("   Type: Development dependency");
+            }
+            lock_policy.print();
 
-WHEN TO CUSTOMIZE:
-    ├─ Testing specific threat models
-    ├─ Simulating network with known number of adversaries
-    ├─ Performance testing with different security levels
-    └─ Research and experimentation
+            dependencies.save()?;
+            println!("{} Added {} {} to {}", style::prefix(Status::Success), package, version, config::MANIFEST_NAME);
+        }
 
-EXAMPLES:
-    stoffel dev --parties 7 --threshold 1     # Lower security, faster
-    stoffel dev --parties 7                   # Auto: threshold = 2
-    stoffel dev --parties 10 --threshold 3    # Explicit threshold
+        Commands::Remove { package, dev, lock_flags } => {
+            let lock_policy = lock_flags.policy();
+            lock_policy.ensure_lockfile_writable("remove a dependency")?;
 
-VALIDATION:
-    ✅ threshold < (parties + 2) / 3
-    ⚠️  Too high threshold will fail with security error
-    ⚠️  Too low threshold reduces security unnecessarily
+            let mut dependencies = deps::Dependencies::load(std::path::Path::new("."))?;
+            let version = dependencies.remove(&package, dev)?;
 
-For more help: stoffel dev --help
-"#);
-}
+            info!("🗑️  Removing dependency: {}", package);
+            lock_policy.print();
 
-fn show_dev_field_help() {
-    println!(r#"
-HELP: stoffel dev --field
+            dependencies.save()?;
+            println!("{} Removed {} {} from {}", style::prefix(Status::Success), package, version, config::MANIFEST_NAME);
+        }
 
-DESCRIPTION:
-    The --field flag specifies the finite field used for MPC computations.
-    Different fields offer different performance and compatibility characteristics.
+        Commands::Publish { dry_run } => {
+            let root = std::path::Path::new(".");
+            let manifest = config::load_project(root)?;
 
-USAGE:
-    stoffel dev --field <FIELD>
+            info!("📤 Publishing package...");
+            if dry_run {
+                info!("   Mode: Dry run");
+            }
 
-AVAILABLE FIELDS:
+            if let Err(problems) = package::validate_for_publish(&manifest) {
+                return Err(format!("{} is not ready to publish:\n   - {}", config::MANIFEST_NAME, problems.join("\n   - ")));
+            }
 
-  bls12-381 (default)
-    ├─ Security: ~128-bit security level
-    ├─ Performance: Good balance of speed and security
-    ├─ Compatibility: Works with BLS signatures and pairings
-    ├─ Size: ~381-bit prime field
-    └─ Best for: General-purpose MPC applications
+            let package = package::Package::collect(root, &manifest)?;
+            let archive = package.archive(root)?;
+            let archive_path = std::path::Path::new(package::PACKAGE_DIR).join(package.archive_name());
 
-  bn254
-    ├─ Security: ~100-bit security level
-    ├─ Performance: Faster than BLS12-381
-    ├─ Compatibility: Ethereum's alt_bn128 precompiles
-    ├─ Size: ~254-bit prime field
-    └─ Best for: Ethereum integration, when speed matters
+            println!("{} Packaging {} ({} files)", style::prefix(Status::Package), package.id, package.files.len());
+            for file in &package.files {
+                println!("   {}", file.display());
+            }
 
-  secp256k1
-    ├─ Security: ~128-bit security level
-    ├─ Performance: Good, widely optimized
-    ├─ Compatibility: Bitcoin/Ethereum ECDSA curve
-    ├─ Size: ~256-bit prime field
-    └─ Best for: Cryptocurrency applications
+            if dry_run {
+                println!("{} Would write {} ({} bytes)", style::prefix(Status::Success), archive_path.display(), archive.len());
+                return Ok(());
+            }
 
-  prime61
-    ├─ Security: ⚠️ Testing only (not secure)
-    ├─ Performance: Very fast
-    ├─ Compatibility: Simple operations
-    ├─ Size: 61-bit prime field
-    └─ Best for: Development, testing, benchmarking
+            std::fs::create_dir_all(package::PACKAGE_DIR)
+                .map_err(|e| format!("Failed to create {}: {}", package::PACKAGE_DIR, e))?;
+            std::fs::write(&archive_path, &archive)
+                .map_err(|e| format!("Failed to write {}: {}", archive_path.display(), e))?;
+            println!("{} Packaged {} ({} bytes)", style::prefix(Status::Success), archive_path.display(), archive.len());
+            eprintln!("{}  Uploading to a registry is not supported yet; the package was only written locally", style::prefix(Status::Warning));
+        }
 
-SELECTION CRITERIA:
-    ├─ Security Requirements: Choose field with adequate security level
-    ├─ Performance Needs: Smaller fields are faster but less secure
-    ├─ Integration: Match field to existing cryptographic infrastructure
-    └─ Development Phase: Use prime61 for fast iteration, production fields for release
+        Commands::Example { action } => {
+            if !std::path::Path::new("Stoffel.toml").exists() {
+                return Err("No Stoffel.toml found. Run this command from a Stoffel project root.".to_string());
+            }
 
-EXAMPLES:
-    stoffel dev --field bls12-381             # Default, good for most use cases
-    stoffel dev --field bn254                 # Ethereum-compatible
-    stoffel dev --field prime61               # Fast development/testing
-    stoffel dev --field secp256k1             # Bitcoin/crypto compatibility
+            match action {
+                ExampleCommands::Add { name } => {
+                    let example_path = add_example(&name)?;
+                    println!("{} Created example: {}", style::prefix(Status::Success), example_path.display());
+                    println!("   Run it with: stoffel example run {}", name);
+                }
+                ExampleCommands::List => {
+                    let examples = list_examples()?;
+                    if examples.is_empty() {
+                        println!("{}  No examples found. Create one with: stoffel example add <name>", style::prefix(Status::Info));
+                    } else {
+                        println!("📚 Examples:");
+                        for example in examples {
+                            println!("   {}", example);
+                        }
+                    }
+                }
+                ExampleCommands::Run { name } => {
+                    let example_path = std::path::Path::new(EXAMPLES_DIR).join(format!("{}.stfl", name));
+                    if !example_path.exists() {
+                        return Err(format!("Example '{}' not found at {}", name, example_path.display()));
+                    }
 
-For more help: stoffel dev --help
-"#);
-}
+                    let MpcSettings { protocol, parties, threshold, field, .. } = resolve_mpc_settings(MpcLayer::default(), None)?;
+                    validate_mpc_params(parties, threshold, &protocol)?;
+                    check_field_security(&value_name(&field), FieldContext::Development)?;
+
+                    let program = build_entry_point(&example_path.to_string_lossy())?;
+                    let vm_path = toolchain::find_vm()?;
+                    let addresses = resolve_party_addresses(parties, None)?;
+                    info!("▶️  Running example {} with {} parties: {}", name, parties, program.display());
+                    let simulation = sim::Simulation {
+                        vm_path: &vm_path,
+                        program: &program,
+                        protocol,
+                        field,
+                        threshold,
+                        vm_opt: VmOptLevel::Standard,
+                        addresses: &addresses,
+                        inputs: None,
+                        step: false,
+                        seed: resolve_seed(None),
+                        args: &[],
+                        log_dir: std::path::Path::new(DEFAULT_OUTPUT_DIR).join(sim::LOG_DIR),
+                        coverage: None,
+                    };
+
+                    print_simulation_output(parties, &simulation.run()?);
+                }
+            }
+        }
 
-// Build command help functions
-fn show_build_target_help() {
-    println!(r#"
-HELP: stoffel build --target
+        Commands::Plugin { action } => {
+            match action {
+                PluginCommands::Install { source } => {
+                    let dir = plugins::plugins_dir().ok_or("Could not determine the Stoffel home directory")?;
+                    info!("🔌 Installing plugin from {}", source);
+                    let plugin = plugins::install(&source, &dir)?;
+                    println!("{} Installed {} {} into {}", style::prefix(Status::Success), plugin.name, plugin.version, dir.join(&plugin.name).display());
+                }
+                PluginCommands::List => {
+                    let dir = plugins::plugins_dir().ok_or("Could not determine the Stoffel home directory")?;
+                    let index = plugins::PluginIndex::load(&dir)?;
+                    println!("🔌 Installed plugins:");
+                    if index.plugins.is_empty() {
+                        println!("   No plugins installed");
+                    }
+                    for plugin in &index.plugins {
+                        println!("   {} {} ({})", plugin.name, plugin.version, plugin.source);
+                    }
+                }
+                PluginCommands::Remove { name } => {
+                    let dir = plugins::plugins_dir().ok_or("Could not determine the Stoffel home directory")?;
+                    info!("🔌 Removing plugin: {}", name);
+                    let plugin = plugins::remove(&name, &dir)?;
+                    println!("{} Removed {} {}", style::prefix(Status::Success), plugin.name, plugin.version);
+                }
+            }
+        }
 
-DESCRIPTION:
-    The --target flag specifies the platform to build for.
-    Different targets enable deployment to different environments.
+        Commands::Toolchain { action } => {
+            match action {
+                ToolchainCommands::Status => print_toolchain_status()?,
+                ToolchainCommands::Install { version, path } => {
+                    let path = path.ok_or(
+                        "Downloading toolchains isn't supported yet; install a local build with --path <compiler or Stoffel-Lang checkout>",
+                    )?;
+                    let dir = toolchain::toolchains_dir().ok_or("Could not determine the Stoffel home directory")?;
+                    info!("🧰 Installing toolchain {} from {}", version, path.display());
+                    let installed = toolchain::install(&version, &path, &dir)?;
+                    println!("{} Installed toolchain {} into {}", style::prefix(Status::Success), installed.version, dir.join(&installed.version).display());
+                    println!("   Now the active toolchain");
+                }
+            }
+        }
 
-USAGE:
-    stoffel build --target <TARGET>
+        Commands::Version => print_version_report(),
 
-AVAILABLE TARGETS:
+        Commands::Explain { topic } => {
+            let lines = match topic {
+                ExplainCommands::Threshold { parties, protocol } => explain_threshold(parties, &protocol),
+                ExplainCommands::Field { name } => explain_field(&name.spec()),
+            };
+            for line in lines {
+                println!("{}", line);
+            }
+        }
 
-  native (default)
-    ├─ Native MPC execution on the current platform
-    ├─ Best performance for local and server deployment
-    ├─ Full feature support
-    └─ Direct integration with system resources
+        Commands::Info { topic } => {
+            match topic {
+                InfoCommands::Field { name } => {
+                    let fields = match name {
+                        Some(field) => vec![field],
+                        None => MpcField::value_variants().to_vec(),
+                    };
+                    for (index, field) in fields.iter().enumerate() {
+                        if index > 0 {
+                            println!();
+                        }
+                        print_field_info(&field.spec());
+                    }
+                }
+            }
+        }
 
-  wasm
-    ├─ WebAssembly for browser-based MPC
-    ├─ Cross-platform compatibility
-    ├─ Sandboxed execution environment
-    └─ Web application integration
+        Commands::Validate => {
+            info!("🔍 Validating project...");
 
-  tee
-    ├─ Trusted Execution Environment (Intel SGX, ARM TrustZone)
-    ├─ Hardware-based security guarantees
-    ├─ Additional protection against side-channel attacks
-    └─ Cloud deployment with confidential computing
+            let checks = validate_project(std::path::Path::new("."));
+            let failures = checks.iter().filter(|check| check.result.is_err()).count();
 
-  gpu
-    ├─ GPU-accelerated computation
-    ├─ Parallel processing for large-scale MPC
-    ├─ Optimized for computationally intensive operations
-    └─ Requires CUDA or OpenCL support
+            for check in &checks {
+                match &check.result {
+                    Ok(detail) => println!("   {} {}: {}", style::prefix(Status::Success), check.name, detail),
+                    Err(problem) => println!("   {} {}: {}", style::prefix(Status::Failure), check.name, problem),
+                }
+            }
 
-EXAMPLES:
-    stoffel build --target native             # Default native build
-    stoffel build --target wasm               # Browser deployment
-    stoffel build --target tee                # Confidential computing
-    stoffel build --target gpu                # High-performance computing
+            println!();
+            if failures > 0 {
+                return Err(format!("Validation failed with {} problem(s)", failures));
+            }
+            println!("{} Validation passed", style::prefix(Status::Success));
+        }
 
-For more help: stoffel build --help
-"#);
-}
+        Commands::Doctor => {
+            info!("🩺 Checking your Stoffel environment...");
 
-fn show_build_optimize_help() {
-    println!(r#"
-HELP: stoffel build --optimize
+            let checks = diagnose(std::path::Path::new("."));
+            let failures = checks.iter().filter(|check| check.status == Status::Failure).count();
+            let warnings = checks.iter().filter(|check| check.status == Status::Warning).count();
 
-DESCRIPTION:
-    The --optimize flag enables advanced compiler optimizations for better performance.
-    This may increase build time but improves runtime performance.
+            for check in &checks {
+                println!("   {} {}: {}", style::prefix(check.status), check.name, check.detail);
+            }
 
-USAGE:
-    stoffel build --optimize
+            println!();
+            if failures > 0 {
+                return Err(format!("{} check(s) failed, {} warning(s)", failures, warnings));
+            }
+            if warnings > 0 {
+                println!("{} No problems found, {} warning(s)", style::prefix(Status::Warning), warnings);
+            } else {
+                println!("{} No problems found", style::prefix(Status::Success));
+            }
+        }
 
-OPTIMIZATION FEATURES:
-    ├─ Dead Code Elimination: Removes unused functions and variables
-    ├─ Constant Folding: Pre-computes constant expressions
-    ├─ Loop Optimization: Improves loop performance and memory usage
-    ├─ MPC-Specific: Optimizations for secure computation patterns
-    └─ Bytecode Optimization: Generates more efficient VM instructions
+        Commands::Why { package } => {
+            let manifest = config::load(std::path::Path::new(config::MANIFEST_NAME))?;
+            let paths = find_dependency_paths(std::path::Path::new("."), &manifest, &package)?;
+
+            if paths.is_empty() {
+                return Err(format!("Package '{}' is not in the dependency graph of {}", package, manifest.package.name));
+            }
 
-PERFORMANCE IMPACT:
-    ├─ Runtime Speed: 20-50% faster execution typical
-    ├─ Memory Usage: Reduced memory footprint
-    ├─ Network Traffic: Optimized communication patterns
-    └─ Build Time: Increased compilation time
+            println!("{} {} is included via:", style::prefix(Status::Package), package);
+            for path in paths {
+                println!("   {}", path.join(" -> "));
+            }
+        }
 
-WHEN TO USE:
-    ✅ Production builds
-    ✅ Performance testing
-    ✅ Final deployment artifacts
-    ⚠️  Not recommended for debug builds (harder to debug)
+        Commands::Status => {
+            let manifest_path = std::path::Path::new(config::MANIFEST_NAME);
+            if !manifest_path.exists() {
+                if json {
+                    return Err(format!("Not a Stoffel project (no {} in the current directory)", config::MANIFEST_NAME));
+                }
+                println!("{}  Not a Stoffel project (no {} in the current directory)", style::prefix(Status::Info), config::MANIFEST_NAME);
+                std::process::exit(1);
+            }
+            let manifest = config::load(manifest_path)?;
+            let config_error = config::validate(&manifest).err();
 
-EXAMPLES:
-    stoffel build --optimize                  # Optimized debug build
-    stoffel build --optimize --release        # Full optimization
-    stoffel build --optimize --target wasm    # Optimized WebAssembly
+            let sources = if std::path::Path::new("src").exists() {
+                find_stfl_files("src")?
+            } else {
+                Vec::new()
+            };
 
-OPTIMIZATION LEVELS:
-    Without --optimize:    Fast compilation, basic optimizations
-    With --optimize:       Advanced optimizations, slower compilation
-    With --release:        Maximum optimizations (implies --optimize)
+            let target = std::path::Path::new(DEFAULT_OUTPUT_DIR);
+            let newest_source = sources
+                .iter()
+                .filter_map(|source| std::fs::metadata(source).and_then(|m| m.modified()).ok())
+                .max();
+            let built = newest_modification(target);
+            let build = match built {
+                None => BuildState::NotBuilt,
+                Some(built) if newest_source.is_some_and(|source| source > built) => BuildState::Stale,
+                Some(_) => BuildState::UpToDate,
+            };
 
-For more help: stoffel build --help
-"#);
-}
+            if json {
+                print_json(&StatusReport {
+                    package: &manifest.package.name,
+                    version: &manifest.package.version,
+                    protocol: &manifest.mpc.protocol,
+                    parties: manifest.mpc.parties,
+                    threshold: manifest.mpc.threshold,
+                    field: &manifest.mpc.field,
+                    config_error,
+                    sources: sources.len(),
+                    build,
+                });
+                return Ok(());
+            }
 
-fn show_build_release_help() {
-    println!(r#"
-HELP: stoffel build --release (-r)
+            println!("📊 Project Status:");
+            println!("   Package: {} v{}", manifest.package.name, manifest.package.version);
+            println!("   Protocol: {}", manifest.mpc.protocol);
+            println!("   Parties: {}", manifest.mpc.parties);
+            match manifest.mpc.threshold {
+                Some(threshold) => println!("   Threshold: {}", threshold),
+                None => println!("   Threshold: auto"),
+            }
+            println!("   Field: {}", manifest.mpc.field);
+            match config_error {
+                None => println!("   Configuration: {} valid", style::prefix(Status::Success)),
+                Some(e) => println!("   Configuration: {} {}", style::prefix(Status::Failure), e),
+            }
+            println!("   Sources: {} .stfl file(s) in src/", sources.len());
 
-DESCRIPTION:
-    The --release (-r) flag builds in release mode with maximum optimizations
-    and no debug information. This is the recommended mode for production.
+            match (built, build) {
+                (None, _) => println!("   Build: not built yet ({}/ missing)", DEFAULT_OUTPUT_DIR),
+                (Some(built), BuildState::Stale) => {
+                    println!("   Build: {}  stale, sources changed since the last build ({})", style::prefix(Status::Warning), format_age(built));
+                }
+                (Some(built), _) => println!("   Build: {} up to date ({})", style::prefix(Status::Success), format_age(built)),
+            }
+        }
 
-USAGE:
-    stoffel build --release
-    stoffel build -r
+        Commands::Clean { all, cache_only, yes } => {
+            if !cache_only {
+                if !std::path::Path::new("Stoffel.toml").exists() {
+                    return Err("No Stoffel.toml found. Run this command from a Stoffel project root, or pass --cache-only.".to_string());
+                }
 
-RELEASE BUILD FEATURES:
-    ├─ Maximum Optimizations: All optimization passes enabled
-    ├─ No Debug Info: Smaller binary size, faster loading
-    ├─ Production Ready: Suitable for deployment
-    ├─ Security Hardening: Additional security measures
-    └─ Performance Tuned: Optimized for runtime performance
+                info!("🧹 Cleaning build artifacts...");
+                let mut cleaned = false;
+                for dir in [DEFAULT_OUTPUT_DIR, compile_cache::CACHE_DIR] {
+                    let path = std::path::Path::new(dir);
+                    if path.exists() {
+                        let reclaimed = cache::remove_dir(path)?;
+                        println!("   Removed {}/ ({})", dir, cache::format_size(reclaimed));
+                        cleaned = true;
+                    }
+                }
+                if !cleaned {
+                    println!("   Nothing to clean");
+                }
+            }
 
-DIFFERENCES FROM DEBUG:
-    Debug Build:
-    ├─ Fast compilation
-    ├─ Debug symbols included
-    ├─ Assertions enabled
-    ├─ Larger binary size
-    └─ Easier debugging
+            if all || cache_only {
+                clean_global_caches(yes)?;
+            }
+        }
 
-    Release Build:
-    ├─ Slower compilation
-    ├─ No debug symbols
-    ├─ Assertions disabled
-    ├─ Smaller binary size
-    └─ Maximum performance
+        Commands::Update { package, lock_flags } => {
+            let lock_policy = lock_flags.policy();
+            lock_policy.ensure_lockfile_writable("update dependencies")?;
 
-BUILD ARTIFACTS:
-    ├─ Optimized bytecode in target/release/
-    ├─ Deployment manifests
-    ├─ Production configuration templates
-    └─ Performance reports
+            if let Some(package) = &package {
+                info!("⬆️  Updating package: {}", package);
+            } else {
+                info!("⬆️  Updating all dependencies...");
+            }
+            lock_policy.print();
 
-EXAMPLES:
-    stoffel build -r                          # Standard release build
-    stoffel build --release --target wasm     # Release WebAssembly build
-    stoffel build --release --target tee      # Release TEE build
+            let mut dependencies = deps::Dependencies::load(std::path::Path::new("."))?;
+            let changes = dependencies.update(package.as_deref())?;
+            for change in &changes {
+                println!("   {}", change);
+            }
+            dependencies.save()?;
 
-DEPLOYMENT CHECKLIST:
-    ✅ Build with --release flag
-    ✅ Test on target environment
-    ✅ Verify performance requirements
-    ✅ Security audit if required
+            if changes.is_empty() {
+                println!("{} {} is up to date", style::prefix(Status::Success), lockfile::LOCKFILE_NAME);
+            } else {
+                println!("{} Updated {} ({} change(s))", style::prefix(Status::Success), lockfile::LOCKFILE_NAME, changes.len());
+            }
+        }
+    }
 
-For more help: stoffel build --help
-"#);
+    Ok(())
 }
 
-// Compile command help functions
-fn show_compile_output_help() {
-    println!(r#"
-HELP: stoffel compile --output (-o)
+/// Directory holding runnable project examples
+const EXAMPLES_DIR: &str = "examples";
+
+/// Program that `stoffel run` compiles and executes unless Stoffel.toml sets `package.entry`
+const ENTRY_POINT: &str = "src/main.stfl";
+
+/// The project's program: `package.entry` from Stoffel.toml, or src/main.stfl
+fn entry_point() -> Result<String, String> {
+    let manifest_path = std::path::Path::new(config::MANIFEST_NAME);
+    let configured = if manifest_path.exists() {
+        config::load(manifest_path)?.package.entry
+    } else {
+        None
+    };
+    resolve_entry_point(configured.as_deref())
+}
 
-DESCRIPTION:
-    The --output (-o) flag specifies the output file path for compiled bytecode.
-    If not provided, uses the input filename with appropriate extension.
+/// The configured entry point or the default, which must exist
+fn resolve_entry_point(configured: Option<&str>) -> Result<String, String> {
+    match configured {
+        Some(entry) if !std::path::Path::new(entry).is_file() => Err(format!(
+            "Entry point {} not found (set by package.entry in {})",
+            entry,
+            config::MANIFEST_NAME
+        )),
+        Some(entry) => Ok(entry.to_string()),
+        None if !std::path::Path::new(ENTRY_POINT).exists() => Err(format!(
+            "No {} found. Run this command from a Stoffel project root, or set package.entry in {}.",
+            ENTRY_POINT,
+            config::MANIFEST_NAME
+        )),
+        None => Ok(ENTRY_POINT.to_string()),
+    }
+}
 
-USAGE:
-    stoffel compile src/main.stfl --output compiled.bin
-    stoffel compile src/main.stfl -o output.bc
+/// Create a new example from the starter template
+fn add_example(name: &str) -> Result<std::path::PathBuf, String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!(
+            "Invalid example name '{}': use letters, digits, hyphens, and underscores",
+            name
+        ));
+    }
 
-OUTPUT FILE EXTENSIONS:
-    .bin    VM-compatible binary (use with --binary flag)
-    .bc     Bytecode format (default)
-    .stfl   Source file extension (input files)
+    let example_path = std::path::Path::new(EXAMPLES_DIR).join(format!("{}.stfl", name));
+    if example_path.exists() {
+        return Err(format!("Example '{}' already exists at {}", name, example_path.display()));
+    }
 
-FILE PATH RESOLUTION:
-    ├─ Absolute paths: /path/to/output.bin
-    ├─ Relative paths: ./output.bin, ../compiled/main.bc
-    ├─ Automatic extension: Adds .bc if no extension provided
-    └─ Directory creation: Creates parent directories if needed
+    std::fs::create_dir_all(EXAMPLES_DIR)
+        .map_err(|e| format!("Failed to create {} directory: {}", EXAMPLES_DIR, e))?;
 
-EXAMPLES:
-    stoffel compile main.stfl -o compiled.bin          # Specific output file
-    stoffel compile main.stfl --output release.bc     # Bytecode output
-    stoffel compile main.stfl -o /tmp/test.bin         # Absolute path
-    stoffel compile main.stfl                          # Auto: main.bc
+    let content = format!(r#"# Example: {}
+# Run with: stoffel example run {}
 
-INTEGRATION WITH OTHER FLAGS:
-    stoffel compile main.stfl -o app.bin --binary     # Binary format output
-    stoffel compile main.stfl -o debug.bc --print-ir  # Debug output with IR
-    stoffel compile main.stfl -o opt.bin -O3 --binary # Optimized binary
+proc main() =
+  # Example secret inputs (in real MPC, these would come from different parties)
+  let a: secret int64 = 3
+  let b: secret int64 = 4
 
-For more help: stoffel compile --help
-"#);
-}
+  let sum = a + b
+  discard sum
+"#,
+        name,
+        name
+    );
 
-fn show_compile_binary_help() {
-    println!(r#"
-HELP: stoffel compile --binary (-b)
+    std::fs::write(&example_path, content)
+        .map_err(|e| format!("Failed to write {}: {}", example_path.display(), e))?;
 
-DESCRIPTION:
-    The --binary (-b) flag generates VM-compatible binary format suitable
-    for execution on StoffelVM. This is the recommended format for production.
+    Ok(example_path)
+}
 
-USAGE:
-    stoffel compile src/main.stfl --binary
-    stoffel compile src/main.stfl -b
+/// List example names found under examples/
+fn list_examples() -> Result<Vec<String>, String> {
+    if !std::path::Path::new(EXAMPLES_DIR).exists() {
+        return Ok(Vec::new());
+    }
 
-BINARY FORMAT FEATURES:
-    ├─ VM Compatibility: Direct execution on StoffelVM
-    ├─ Optimized Loading: Faster startup times
-    ├─ Compact Size: Efficient binary representation
-    ├─ Production Ready: Suitable for deployment
-    └─ Platform Independent: Runs on any StoffelVM instance
+    let prefix = std::path::Path::new(EXAMPLES_DIR);
+    Ok(find_stfl_files(EXAMPLES_DIR)?
+        .iter()
+        .map(|file| {
+            let relative = std::path::Path::new(file).strip_prefix(prefix).unwrap_or(std::path::Path::new(file));
+            relative.with_extension("").to_string_lossy().to_string()
+        })
+        .collect())
+}
 
-BINARY VS BYTECODE:
-    Bytecode (.bc):
-    ├─ Human-readable representation
-    ├─ Debugging friendly
-    ├─ Larger file size
-    └─ Requires additional processing
+/// Find all .stfl files recursively in a directory
+///
+/// Paths matched by the project's .stoffelignore are skipped. Symlinked
+/// directories are followed, but each directory is searched only once.
+fn find_stfl_files(dir: &str) -> Result<Vec<String>, String> {
+    find_project_stfl_files(std::path::Path::new("."), std::path::Path::new(dir))
+}
 
-    Binary (.bin):
-    ├─ VM-optimized format
-    ├─ Faster execution
-    ├─ Smaller file size
-    └─ Production deployment
+/// `find_stfl_files` for the project at `root`, honoring its own .stoffelignore
+fn find_project_stfl_files(root: &std::path::Path, dir: &std::path::Path) -> Result<Vec<String>, String> {
+    let ignore = source_filter::IgnoreRules::load(root)?;
+    let mut stfl_files = Vec::new();
+    let mut visited = std::collections::HashSet::new();
+    find_stfl_files_recursive(root, dir, &ignore, &mut visited, &mut stfl_files)?;
+    stfl_files.sort(); // Sort for consistent ordering
+    Ok(stfl_files)
+}
 
-EXAMPLES:
-    stoffel compile main.stfl --binary                 # Generate binary
-    stoffel compile main.stfl -b -o release.bin        # Binary with custom name
-    stoffel compile main.stfl --binary -O3             # Optimized binary
+/// Recursively find .stfl files in a directory
+fn find_stfl_files_recursive(
+    root: &std::path::Path,
+    dir: &std::path::Path,
+    ignore: &source_filter::IgnoreRules,
+    visited: &mut std::collections::HashSet<std::path::PathBuf>,
+    files: &mut Vec<String>,
+) -> Result<(), String> {
+    // A symlink back to a directory already searched would otherwise recurse forever
+    let canonical = dir.canonicalize()
+        .map_err(|e| format!("Failed to resolve directory {}: {}", dir.display(), e))?;
+    if !visited.insert(canonical) {
+        debug!("Skipping {} (already searched)", dir.display());
+        return Ok(());
+    }
 
-DEPLOYMENT WORKFLOW:
-    1. Development: Compile without --binary for debugging
-    2. Testing: Use --binary for performance testing
-    3. Production: Always use --binary for deployment
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
 
-For more help: stoffel compile --help
-"#);
-}
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        let is_dir = path.is_dir();
+        if ignore.is_ignored(path.strip_prefix(root).unwrap_or(&path), is_dir) {
+            debug!("Skipping {} (matched {})", path.display(), source_filter::IGNORE_FILE);
+            continue;
+        }
 
-fn show_compile_disassemble_help() {
-    println!(r#"
-HELP: stoffel compile --disassemble
+        if is_dir {
+            // Recursively search subdirectories
+            find_stfl_files_recursive(root, &path, ignore, visited, files)?;
+        } else if let Some(extension) = path.extension() {
+            if extension == "stfl" {
+                files.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
 
-DESCRIPTION:
-    The --disassemble flag disassembles a compiled binary file to show
-    bytecode instructions. Useful for debugging and understanding compilation.
+    Ok(())
+}
 
-USAGE:
-    stoffel compile compiled.bin --disassemble
+/// Most recent modification time of any file below `dir`
+fn newest_modification(dir: &std::path::Path) -> Option<std::time::SystemTime> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                newest_modification(&path)
+            } else {
+                entry.metadata().and_then(|m| m.modified()).ok()
+            }
+        })
+        .max()
+}
 
-DISASSEMBLY FEATURES:
-    ├─ Bytecode Instructions: Shows VM opcodes and operands
-    ├─ Memory Layout: Displays data section and constants
-    ├─ Jump Targets: Shows labels and branch destinations
-    ├─ Debug Information: Includes source line mappings (if available)
-    └─ Human Readable: Formatted output for analysis
+/// Describe how long ago `time` was, e.g. "built 5m ago"
+fn format_age(time: std::time::SystemTime) -> String {
+    let seconds = time.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+    let age = match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3_599 => format!("{}m", seconds / 60),
+        3_600..=86_399 => format!("{}h", seconds / 3_600),
+        _ => format!("{}d", seconds / 86_400),
+    };
+    format!("built {} ago", age)
+}
 
-INPUT FILE TYPES:
-    .bin    VM-compatible binary files
-    .bc     Bytecode files (also supported)
+/// Default directory for reports and other side artifacts
+const DEFAULT_OUTPUT_DIR: &str = "target";
+
+/// Resolve the directory that receives reports and side artifacts
+///
+/// An explicitly requested directory is validated and created up front; the
+/// default is left to be created by whichever artifact is written first.
+fn prepare_output_dir(output_dir: Option<&str>) -> Result<std::path::PathBuf, String> {
+    let Some(output_dir) = output_dir else {
+        return Ok(std::path::PathBuf::from(DEFAULT_OUTPUT_DIR));
+    };
+
+    let dir = std::path::PathBuf::from(output_dir);
+    if dir.exists() && !dir.is_dir() {
+        return Err(format!("Output directory {} exists and is not a directory", dir.display()));
+    }
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create output directory {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
 
-DISASSEMBLY OUTPUT:
-    ├─ Instruction listing with addresses
-    ├─ Register usage and data flow
-    ├─ Function boundaries and call sites
-    └─ Constant pool and literal values
+/// Write the per-file timing report into the artifacts directory
+fn write_timings_report(artifacts_dir: &std::path::Path, file_timings: &[timings::FileTiming]) -> Result<(), String> {
+    let report_path = artifacts_dir.join(timings::TIMINGS_REPORT_NAME);
+    timings::write_html_report(&report_path, file_timings)?;
+    info!("⏱️  Timings report written to {}", report_path.display());
+    Ok(())
+}
 
-EXAMPLES:
-    stoffel compile app.bin --disassemble              # Disassemble binary
-    stoffel compile debug.bc --disassemble             # Disassemble bytecode
-    stoffel compile app.bin --disassemble > dump.txt   # Save to file
+/// Print files whose compilation exceeded the threshold, slowest first
+fn print_slow_files(file_timings: &[timings::FileTiming], threshold_ms: u64) {
+    let threshold = std::time::Duration::from_millis(threshold_ms);
+    let mut slow: Vec<&timings::FileTiming> = file_timings.iter()
+        .filter(|timing| timing.duration > threshold)
+        .collect();
 
-DEBUGGING WORKFLOW:
-    1. Compile with debug info: stoffel compile main.stfl --print-ir
-    2. Generate binary: stoffel compile main.stfl --binary -o app.bin
-    3. Disassemble: stoffel compile app.bin --disassemble
-    4. Analyze output for optimization opportunities
+    if slow.is_empty() {
+        return;
+    }
 
-COMMON USE CASES:
-    ✅ Debugging compilation issues
-    ✅ Understanding compiler optimizations
-    ✅ Reverse engineering binary files
-    ✅ Performance analysis and profiling
+    slow.sort_by_key(|timing| std::cmp::Reverse(timing.duration));
 
-For more help: stoffel compile --help
-"#);
+    println!();
+    println!("🐢 Slow files (> {} ms):", threshold_ms);
+    for timing in slow {
+        println!("   {:>8} ms  {}", timing.duration.as_millis(), timing.file);
+    }
 }
 
-fn show_compile_print_ir_help() {
-    println!(r#"
-HELP: stoffel compile --print-ir
+/// Parse a `KEY=VALUE` preprocessor define
+fn parse_define(define: &str) -> Result<(String, String), String> {
+    let (key, value) = define.split_once('=')
+        .ok_or_else(|| format!("invalid define '{}': expected KEY=VALUE", define))?;
+
+    let valid_key = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_key {
+        return Err(format!(
+            "invalid define key '{}': use letters, digits, and underscores, starting with a letter or underscore",
+            key
+        ));
+    }
 
-DESCRIPTION:
-    The --print-ir flag prints intermediate representations during compilation,
-    including tokens, AST, and other debug information.
+    Ok((key.to_string(), value.to_string()))
+}
 
-USAGE:
-    stoffel compile src/main.stfl --print-ir
+/// Reject defines that set the same key more than once
+fn ensure_unique_defines(defines: &[(String, String)]) -> Result<(), String> {
+    let mut seen = std::collections::HashSet::new();
+    for (key, _) in defines {
+        if !seen.insert(key) {
+            return Err(format!("Define '{}' is specified more than once", key));
+        }
+    }
+    Ok(())
+}
 
-INTERMEDIATE REPRESENTATIONS:
-    ├─ Tokens: Lexical analysis output (keywords, identifiers, literals)
-    ├─ Abstract Syntax Tree (AST): Parsed program structure
-    ├─ Symbol Table: Variable and function declarations
-    ├─ Type Information: Inferred and declared types
-    ├─ Semantic Analysis: Type checking and validation results
-    └─ Code Generation: Bytecode generation steps
+/// Compiler flags shared by every file in a compile invocation
+#[derive(Default)]
+struct CompileOptions {
+    /// Only parse and type-check; the compiler writes no output
+    check: bool,
+    binary: bool,
+    disassemble: bool,
+    print_ir: bool,
+    opt_level: u8,
+    std_path: Option<String>,
+    defines: Vec<(String, String)>,
+    /// Instrument procs and lines so the VM can report coverage
+    coverage: bool,
+}
 
-DEBUG OUTPUT SECTIONS:
-    1. LEXICAL ANALYSIS
-       ├─ Token stream with positions
-       ├─ Keyword recognition
-       └─ Literal parsing
+/// Result of compiling a batch of files
+struct BatchOutcome {
+    successful: usize,
+    failed: usize,
+    /// Files skipped because the compile cache had them
+    up_to_date: usize,
+    timings: Vec<timings::FileTiming>,
+    files: Vec<FileSummary>,
+    /// With `--fail-fast`, the failed file that stopped the batch
+    stopped_at: Option<String>,
+    /// Files never compiled because the batch stopped early
+    skipped: usize,
+}
 
-    2. SYNTAX ANALYSIS
-       ├─ Parse tree structure
-       ├─ Grammar rule applications
-       └─ Error recovery attempts
+/// Per-file result in a `--json` summary
+#[derive(serde::Serialize)]
+struct FileSummary {
+    path: String,
+    success: bool,
+    /// The compile cache had this file, so the compiler didn't run
+    up_to_date: bool,
+    /// Compiler output, which isn't printed in JSON mode
+    stdout: String,
+    stderr: String,
+}
 
-    3. SEMANTIC ANALYSIS
-       ├─ Type checking results
-       ├─ Symbol resolution
-       └─ Scope analysis
+/// `--json` result of compiling a batch of files
+#[derive(serde::Serialize)]
+struct BatchSummary<'a> {
+    successful: usize,
+    failed: usize,
+    up_to_date: usize,
+    total: usize,
+    /// With `--fail-fast`, the failed file that stopped the batch
+    stopped_at: Option<&'a str>,
+    skipped: usize,
+    files: &'a [FileSummary],
+}
 
-    4. CODE GENERATION
-       ├─ Bytecode instruction selection
-       ├─ Register allocation
-       └─ Optimization passes
+/// `--json` result of `stoffel build`
+#[derive(serde::Serialize)]
+struct BuildReport<'a> {
+    package: &'a str,
+    version: &'a str,
+    profile: &'a str,
+    opt_level: u8,
+    artifacts: std::borrow::Cow<'a, str>,
+    /// Build manifest path; `None` when files failed to compile
+    manifest: Option<std::borrow::Cow<'a, str>>,
+    #[serde(flatten)]
+    summary: BatchSummary<'a>,
+}
 
-EXAMPLES:
-    stoffel compile main.stfl --print-ir               # Full IR output
-    stoffel compile main.stfl --print-ir > debug.log   # Save to file
-    stoffel compile main.stfl --print-ir -O2           # IR with optimizations
+/// `--json` result of `stoffel bench`
+#[derive(serde::Serialize)]
+struct BenchReport<'a> {
+    file: &'a str,
+    protocol: &'a str,
+    parties: u8,
+    threshold: u8,
+    field: &'a str,
+    seed: u64,
+    warmup: u32,
+    iterations: u32,
+    min_ms: f64,
+    median_ms: f64,
+    max_ms: f64,
+    /// Per-party communication; `None` where the VM didn't report it
+    traffic: Vec<Option<sim::Traffic>>,
+}
 
-DEBUGGING WORKFLOW:
-    1. Basic compilation: Check for syntax errors
-    2. Add --print-ir: Examine parse tree and types
-    3. Fix issues: Use IR to identify problems
-    4. Optimize: Compare IR before/after optimization
+/// `--json` result of `stoffel status`
+#[derive(serde::Serialize)]
+struct StatusReport<'a> {
+    package: &'a str,
+    version: &'a str,
+    protocol: &'a str,
+    parties: u8,
+    /// `None` when it's calculated from the party count
+    threshold: Option<u8>,
+    field: &'a str,
+    /// Why the manifest's MPC settings can't be run, if they can't
+    config_error: Option<String>,
+    sources: usize,
+    build: BuildState,
+}
 
-WHEN TO USE:
-    ✅ Debugging compilation errors
-    ✅ Understanding compiler behavior
-    ✅ Learning StoffelLang internals
-    ✅ Contributing to compiler development
-    ⚠️  Produces verbose output (use redirection)
+/// Whether `target/` reflects the current sources
+#[derive(serde::Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum BuildState {
+    NotBuilt,
+    Stale,
+    UpToDate,
+}
 
-For more help: stoffel compile --help
-"#);
+/// `--json` output for a failed command
+#[derive(serde::Serialize)]
+struct ErrorReport<'a> {
+    error: &'a str,
 }
 
-fn show_compile_opt_level_help() {
-    println!(r#"
-HELP: stoffel compile --opt-level (-O)
+/// Print a `--json` result to stdout
+fn print_json(value: &impl serde::Serialize) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("{}  Failed to serialize JSON output: {}", style::prefix(Status::Warning), e),
+    }
+}
 
-DESCRIPTION:
-    The --opt-level (-O) flag sets the optimization level for compilation.
-    Higher levels improve performance but increase compilation time.
+impl BatchOutcome {
+    fn summary(&self) -> BatchSummary<'_> {
+        BatchSummary {
+            successful: self.successful,
+            failed: self.failed,
+            up_to_date: self.up_to_date,
+            total: self.successful + self.up_to_date + self.failed,
+            stopped_at: self.stopped_at.as_deref(),
+            skipped: self.skipped,
+            files: &self.files,
+        }
+    }
 
-USAGE:
-    stoffel compile src/main.stfl --opt-level 2
-    stoffel compile src/main.stfl -O3
+    fn print_summary(&self, title: &str) {
+        println!("📊 {}:", title);
+        println!("   {} Successful: {}", style::prefix(Status::Success), self.successful);
+        if self.up_to_date > 0 {
+            println!("   {}  Up to date: {}", style::prefix(Status::Skipped), self.up_to_date);
+        }
+        println!("   {} Failed: {}", style::prefix(Status::Failure), self.failed);
+        println!("   📁 Total: {}", self.successful + self.up_to_date + self.failed);
+        if let Some(file) = &self.stopped_at {
+            println!("   {}  Stopped at first failure: {} ({} file(s) not compiled)", style::prefix(Status::Stopped), file, self.skipped);
+        }
+    }
+}
 
-OPTIMIZATION LEVELS:
+/// Inputs besides the file itself that change what a compile produces
+///
+/// The compiler's path and version, defines, the stdlib's contents and every
+/// module under src/ are folded into each file's hash, since switching
+/// toolchains or editing an imported module changes the output too. Computed
+/// once per compile and passed to `cache_entry`.
+fn cache_inputs(compiler_path: &std::path::Path, options: &CompileOptions) -> Result<String, String> {
+    // A compiler that can't report its version is still keyed by its path
+    let version = toolchain::compiler_version(compiler_path).ok();
+    let std = options.std_path.as_deref()
+        .map(|std_path| compile_cache::tree_digest(std::path::Path::new(std_path)))
+        .transpose()?;
+    let sources = compile_cache::sources_digest(std::path::Path::new("src"))?;
+    Ok(format!("{}{:?}{:?}{:?}{}", compiler_path.display(), version, options.defines, std, sources))
+}
 
-  -O0 (default)
-    ├─ No optimization
-    ├─ Fastest compilation
-    ├─ Best for development and debugging
-    ├─ Preserves all debug information
-    └─ Larger bytecode size
+/// Cache key for compiling `file` with `options` and the shared `inputs` from `cache_inputs`
+///
+/// Files outside src/, such as examples, also fold in the modules next to them.
+fn cache_entry(file: &str, output: &Option<String>, options: &CompileOptions, inputs: &str) -> Result<compile_cache::CacheEntry, String> {
+    let siblings = match std::path::Path::new(file).parent() {
+        Some(dir) if dir.as_os_str().is_empty() => compile_cache::sources_digest(std::path::Path::new("."))?,
+        Some(dir) if !dir.starts_with("src") => compile_cache::sources_digest(dir)?,
+        _ => String::new(),
+    };
+    let output = output.clone().unwrap_or_else(|| {
+        let extension = if options.binary { "bin" } else { "bc" };
+        std::path::Path::new(file).with_extension(extension).to_string_lossy().to_string()
+    });
+    Ok(compile_cache::CacheEntry {
+        hash: compile_cache::fingerprint(std::path::Path::new(file), &format!("{}{}", inputs, siblings))?,
+        opt_level: options.opt_level,
+        binary: options.binary,
+        output,
+    })
+}
 
-  -O1
-    ├─ Basic optimizations
-    ├─ Dead code elimination
-    ├─ Constant folding
-    ├─ Fast compilation
-    └─ Good balance for development
+/// Compile each (source, output) pair, reporting per-file status
+///
+/// Files are compiled concurrently on up to one worker per CPU, but results
+/// are printed in job order so the output doesn't depend on scheduling.
+/// With a cache, unchanged files are skipped and successful compiles recorded.
+/// In JSON mode each file is reported as a single JSON line instead, and
+/// without a format nothing is printed; `BatchOutcome::files` has the results.
+/// With `fail_fast`, the batch stops at the first failure in job order and the
+/// remaining files are skipped.
+fn compile_batch(
+    compiler_path: &std::path::Path,
+    jobs: &[(String, Option<String>)],
+    options: &CompileOptions,
+    mut cache: Option<&mut compile_cache::CompileCache>,
+    format: Option<MessageFormat>,
+    fail_fast: bool,
+) -> Result<BatchOutcome, String> {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    let mut outcome = BatchOutcome {
+        successful: 0,
+        failed: 0,
+        up_to_date: 0,
+        timings: Vec::new(),
+        files: Vec::new(),
+        stopped_at: None,
+        skipped: 0,
+    };
+    let mut entries = Vec::new();
+    let mut fresh = Vec::new();
+    let inputs = cache.as_ref().map(|_| cache_inputs(compiler_path, options)).transpose()?;
+    for (file, output) in jobs {
+        let entry = match (&cache, &inputs) {
+            (Some(cache), Some(inputs)) => {
+                let entry = cache_entry(file, output, options, inputs)?;
+                fresh.push(cache.is_fresh(file, &entry));
+                Some(entry)
+            }
+            _ => {
+                fresh.push(false);
+                None
+            }
+        };
+        entries.push(entry);
+    }
 
-  -O2
-    ├─ Standard optimizations
-    ├─ Loop optimizations
-    ├─ Function inlining (small functions)
-    ├─ Register optimization
-    └─ Recommended for production
+    let batch_started = std::time::Instant::now();
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(jobs.len())
+        .max(1);
+    let next_job = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    std::thread::scope(|scope| -> Result<(), String> {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let next_job = &next_job;
+            let stop = &stop;
+            let fresh = &fresh;
+            scope.spawn(move || loop {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let index = next_job.fetch_add(1, Ordering::Relaxed);
+                let Some((file, output)) = jobs.get(index) else {
+                    break;
+                };
+                let started = std::time::Instant::now();
+                let result = if fresh[index] {
+                    Ok(None)
+                } else {
+                    run_compiler(compiler_path, file, output, options).map(Some)
+                };
+                if sender.send((index, started, started.elapsed(), result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Hold finished jobs until everything before them has been printed
+        let mut finished = std::collections::BTreeMap::new();
+        'results: for (index, started, duration, result) in receiver {
+            finished.insert(index, (started, duration, result));
+            while let Some((started, duration, result)) = finished.remove(&outcome.timings.len()) {
+                let index = outcome.timings.len();
+                let file = &jobs[index].0;
+                let Some(run) = result? else {
+                    outcome.timings.push(timings::FileTiming {
+                        file: file.clone(),
+                        start: started - batch_started,
+                        duration: std::time::Duration::ZERO,
+                        success: true,
+                    });
+                    outcome.up_to_date += 1;
+                    outcome.files.push(FileSummary {
+                        path: file.clone(),
+                        success: true,
+                        up_to_date: true,
+                        stdout: String::new(),
+                        stderr: String::new(),
+                    });
+                    if let Some(format) = format {
+                        report_up_to_date(file, format);
+                    }
+                    continue;
+                };
 
-  -O3
-    ├─ Aggressive optimizations
-    ├─ Advanced loop transformations
-    ├─ Extensive function inlining
-    ├─ Cross-function optimizations
-    └─ Maximum performance (slowest compilation)
+                if format == Some(MessageFormat::Human) {
+                    info!("🔧 Compiling: {}", file);
+                }
+                if let Some(format) = format {
+                    run.report(file, format);
+                }
+                outcome.files.push(FileSummary {
+                    path: file.clone(),
+                    success: run.success,
+                    up_to_date: false,
+                    stdout: String::from_utf8_lossy(&run.stdout).into_owned(),
+                    stderr: String::from_utf8_lossy(&run.stderr).into_owned(),
+                });
+                outcome.timings.push(timings::FileTiming {
+                    file: file.clone(),
+                    start: started - batch_started,
+                    duration,
+                    success: run.success,
+                });
+
+                if run.success {
+                    outcome.successful += 1;
+                    if let (Some(cache), Some(entry)) = (cache.as_deref_mut(), entries[index].take()) {
+                        cache.record(file, entry);
+                    }
+                } else {
+                    outcome.failed += 1;
+                    if fail_fast {
+                        // Dropping the receiver makes workers still compiling give up too
+                        stop.store(true, Ordering::Relaxed);
+                        outcome.stopped_at = Some(file.clone());
+                        outcome.skipped = jobs.len() - outcome.timings.len();
+                        break 'results;
+                    }
+                }
+            }
+        }
+        Ok(())
+    })?;
 
-OPTIMIZATION TECHNIQUES:
-    ├─ Dead Code Elimination: Removes unused code
-    ├─ Constant Folding: Pre-computes constant expressions
-    ├─ Loop Optimization: Reduces loop overhead
-    ├─ Function Inlining: Eliminates function call overhead
-    ├─ Register Allocation: Optimizes register usage
-    └─ MPC-Specific: Optimizes secure computation patterns
+    Ok(outcome)
+}
 
-PERFORMANCE IMPACT:
-    Level    Compile Time    Runtime Speed    Binary Size
-    -O0      Fastest        Slowest          Largest
-    -O1      Fast           Good             Medium
-    -O2      Medium         Better           Smaller
-    -O3      Slowest        Fastest          Smallest
+/// The seed to simulate with, announcing a randomly chosen one so it can be replayed
+fn resolve_seed(seed: Option<u64>) -> u64 {
+    match seed {
+        Some(seed) => {
+            info!("   Seed: {}", seed);
+            seed
+        }
+        None => {
+            let seed = sim::random_seed();
+            info!("   Seed: {} (random; replay with --seed {})", seed, seed);
+            seed
+        }
+    }
+}
 
-EXAMPLES:
-    stoffel compile main.stfl -O0                      # Debug build
-    stoffel compile main.stfl -O2                      # Production build
-    stoffel compile main.stfl -O3 --binary             # Maximum optimization
-    stoffel compile main.stfl --opt-level 1            # Explicit level 1
+/// Where `build_entry_point` writes the compiled program
+fn entry_point_artifact(entry: &str) -> std::path::PathBuf {
+    artifact_path(&std::path::Path::new(DEFAULT_OUTPUT_DIR).join("debug"), entry, "bin")
+}
 
-WHEN TO USE EACH LEVEL:
-    -O0: Development, debugging, rapid iteration
-    -O1: Testing builds, continuous integration
-    -O2: Production releases, performance testing
-    -O3: Performance-critical applications, benchmarking
+/// Compile the entry point for `stoffel run` into target/debug/
+///
+/// Goes through the compile cache, so an unchanged program isn't rebuilt.
+fn build_entry_point(entry: &str) -> Result<std::path::PathBuf, String> {
+    let program = entry_point_artifact(entry);
+    if let Some(parent) = program.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let output = Some(program.to_string_lossy().to_string());
+    let options = CompileOptions {
+        binary: true,
+        ..Default::default()
+    };
+
+    let compiler_path = toolchain::find_compiler()?;
+    let root = std::path::Path::new(".");
+    let mut cache = compile_cache::CompileCache::load(root);
+    let cached = cache_entry(entry, &output, &options, &cache_inputs(&compiler_path, &options)?)?;
+    if cache.is_fresh(entry, &cached) {
+        return Ok(program);
+    }
 
-For more help: stoffel compile --help
-"#);
+    info!("🔧 Compiling: {}", entry);
+    if !compile_single_file(&compiler_path, entry, &output, &options)? {
+        return Err(format!("Failed to compile {}", entry));
+    }
+    cache.record(entry, cached);
+    cache.save(root)?;
+    Ok(program)
 }
 
-// Placeholder functions for other commands to avoid compile errors
-fn show_test_test_help() { println!("Help for --test flag coming soon"); }
-fn show_test_parties_help() { println!("Help for --parties flag coming soon"); }
-fn show_test_protocol_help() { println!("Help for --protocol flag coming soon"); }
-fn show_test_threshold_help() { println!("Help for --threshold flag coming soon"); }
-fn show_test_field_help() { println!("Help for --field flag coming soon"); }
-fn show_test_integration_help() { println!("Help for --integration flag coming soon"); }
-fn show_run_parties_help() { println!("Help for --parties flag coming soon"); }
-fn show_run_protocol_help() { println!("Help for --protocol flag coming soon"); }
-fn show_run_threshold_help() { println!("Help for --threshold flag coming soon"); }
-fn show_run_field_help() { println!("Help for --field flag coming soon"); }
-fn show_run_vm_opt_help() { println!("Help for --vm-opt flag coming soon"); }
+/// Print what a simulated run wrote, or that every party finished quietly
+fn print_simulation_output(parties: u8, output: &str) {
+    if output.trim().is_empty() {
+        println!("{} All {} parties finished", style::prefix(Status::Success), parties);
+    } else {
+        println!("{} Result:", style::prefix(Status::Success));
+        for line in output.lines() {
+            println!("   {}", line);
+        }
+    }
+}
 
-fn display_honeybadger() {
-    println!(r#"
-    Stoffel is a honeybadger that helps you build MPC applications.
-    Honeybadgers are a fearless breed of animals that are known for their tenacity and resilience.
-    MPC is a powerful tool that allows you to build applications that are secure, scalable, and efficient. Just like Stoffel.
+/// Where the compiled form of a source file goes inside an output directory
+///
+/// Sources under src/ map to the directory root, other relative paths (e.g.
+/// examples/) keep their directory, and absolute paths keep only the file name.
+fn artifact_path(dir: &std::path::Path, source: &str, extension: &str) -> std::path::PathBuf {
+    let source = std::path::Path::new(source);
+    let relative = match source.strip_prefix("src") {
+        Ok(relative) => relative,
+        Err(_) if source.is_absolute() => source.file_name().map(std::path::Path::new).unwrap_or(source),
+        Err(_) => source,
+    };
+    dir.join(relative).with_extension(extension)
+}
 
-                                                                                                                                                  
-                                                   @    .                                           
-                                                @@@@@@@@@*@@                                        
-                                              @@+-@   --@@@@                                        
-                                          @@@@ --------------@@@                                    
-                                     @@@@   -----------------@@@@@                                  
-                                 @@@@  ---------------------------@@@@                              
-                              *@@@  :::::::::::::::::::-------------- @@@                           
-                            @@@  :::::::::::::::::::::::::::------------ @@                         
-                          @@@  :::::::::::::::::::::::::::::::::-*----%--- @@                       
-                         @@  :+=%%%%%%%%@%@@@:::::::::::::@::::=%%@%@-@%%%@- @@                     
-                       @@:%%%%%%%%%%%%#########%::::::::##%%%%%%%%%%%%%%%%%%%@ @@.                  
-                      @@-%%%%%%%%%################@:@#########################%@@@                  
-                     @:#%%#######################################################@@                 
-                   @@:#############@@#############################%@##############:@                
-                  @@:##################@#######################@###################@@@@##@@         
-           @@##@@@@:######################@#################@*######################@###@#@         
-          @@#@####:########################*@#############@++##########################%%@@@.       
-          @#%%%#############################+@###########@++##########################@%%%@@        
-          @#%%%%##########@@=====@@@@@######++%#####****%@+*#####@@@@@@====@@#########%%%%%@        
-         #@#%%%%@##########@=====@  @@@@@@###+%*********%@***#@% @@@@@=====@#########@#@%%%@        
-          @#%%@#############@....@%%%%%%@@#**@***********#***@@  %%%%@....@############@%%@@        
-          @:%%%@###########*#@....@%####.-*******##@@@##*****@.%%%@@@....@@*############%#@@        
-          @##%@##############*++@@..@@@...*****++++++++++****+  @@@..@@++*############%%%:@         
-           @#%%%#################++#####@.*********@@@******* @*****#+++##############%%:@%         
-           @@#%%########################****@%%%%%%%%%%%%%%@********###################:@@          
-            @@%:#########%%@@@%%%#######***@@@@   .        @@*******####%%@@%%########:@@           
-             @@:##############@##########***%%%@%%%%%%%%%%%%********##%#@@############@:@           
-             @@##%#############@##########***%%%#%%%%%%@%%%********####@=#############:@@@          
-             @:%%##############@=#############**@#%%%%#@*********#####@=##############:@            
-            .@%%@#%%############@=#######@#########@%################@=#############%##@+           
-            @@#@:#%%#############==@###############################%=@@#############%#:@            
-               @:%%%%#############@=.@%#######%%%%%%%%%%%#######%%@.=@#############%%%:@            
-               @@%%%%%%%###########=..@=.@@@@@@@@-....@@@@@@@@@==@..@##############:%:@@            
-               @@%@:%%%%%###########=..==......................=@.==###########%:#:@@:@             
-                @%@@:%%%%%%#########@..@=...@...=@....@=..@...==...###########%:%:@@@@              
-                @@  @:%%%%%%%%########..=..@..................=@.@#######%%#%:@@:@@                 
-                     @@:%@::%%%%#######@-=@.................=.=@#######%%%%%:@@@@                   
-                       @@:@@@::%%%%##########@@@@@@@@@@@@@%##@#######%%:%%:@@                       
-                          @@@@@@::%@:%#############################%::@@%@@                         
-                                 @@:@@@::######################%:%:@@ @:@                           
-                                    @@ @@@:%%%%############%%%:*@@                                  
-                                           @@::@:%%%%%%:%%:@%:@                                     
-                                             @@@@@:::@@+@@@@@                                       
-                                                    @ +                                             
-                                                                                                    
-                                                                                                    
-                                                                                                    
-                                                                                                    
-                                                                                                    
-                                                                                                    
+/// `artifact_path`, with its parent directories created
+fn prepare_artifact_path(dir: &std::path::Path, source: &str, extension: &str) -> Result<String, String> {
+    let artifact = artifact_path(dir, source, extension);
+    if let Some(parent) = artifact.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    Ok(artifact.to_string_lossy().to_string())
+}
 
+/// Disassemble a compiled program, writing the listing to `output` or stdout
+fn disassemble_file(file: &str, output: Option<&str>) -> Result<(), String> {
+    let path = std::path::Path::new(file);
+    if !path.exists() {
+        return Err(format!("File not found: {}", file));
+    }
+    if !matches!(path.extension().and_then(|extension| extension.to_str()), Some("bin" | "bc")) {
+        return Err(format!("{} is not a compiled program; expected a .bin or .bc file", file));
+    }
 
-"#);
+    let compiler_path = toolchain::find_compiler()?;
+    let options = CompileOptions {
+        disassemble: true,
+        ..Default::default()
+    };
+    let run = run_compiler(&compiler_path, file, &None, &options)?;
+    if !run.stderr.is_empty() {
+        eprint!("{}", String::from_utf8_lossy(&run.stderr));
+    }
+    if !run.success {
+        return Err(format!("Failed to disassemble {}", file));
+    }
+
+    match output {
+        Some(output) => {
+            std::fs::write(output, &run.stdout).map_err(|e| format!("Failed to write {}: {}", output, e))?;
+            info!("{} Disassembly written to {}", style::prefix(Status::Success), output);
+        }
+        None => print!("{}", String::from_utf8_lossy(&run.stdout)),
+    }
+    Ok(())
 }
 
-fn main() -> Result<(), String> {
-    // Handle special flag-specific help cases before clap parsing
-    let args: Vec<String> = std::env::args().collect();
+/// Compile a single StoffelLang file
+fn compile_single_file(
+    compiler_path: &std::path::Path,
+    file: &str,
+    output: &Option<String>,
+    options: &CompileOptions,
+) -> Result<bool, String> {
+    let run = run_compiler(compiler_path, file, output, options)?;
+    run.print();
+    Ok(run.success)
+}
 
-    // Check for flag-specific help patterns like "stoffel init -t -h" or "stoffel dev --parties --help"
-    if args.len() >= 4 {
-        let command = args.get(1).map(|s| s.as_str());
-        let flag = args.get(2).map(|s| s.as_str());
-        let help_flag = args.get(3).map(|s| s.as_str());
+/// Captured result of one compiler invocation
+struct CompilerRun {
+    success: bool,
+    /// Exit code; `None` when the compiler was killed by a signal
+    code: Option<i32>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
 
-        if help_flag == Some("-h") || help_flag == Some("--help") {
-            match (command, flag) {
-                // Init command flags
-                (Some("init"), Some("-t" | "--template")) => {
-                    show_init_template_help();
-                    return Ok(());
-                }
-                (Some("init"), Some("-i" | "--interactive")) => {
-                    show_init_interactive_help();
-                    return Ok(());
-                }
-                (Some("init"), Some("--lib")) => {
-                    show_init_lib_help();
-                    return Ok(());
-                }
-                (Some("init"), Some("--path")) => {
-                    show_init_path_help();
-                    return Ok(());
-                }
+impl CompilerRun {
+    /// Replay the compiler's output
+    fn print(&self) {
+        if !self.stdout.is_empty() {
+            print!("{}", String::from_utf8_lossy(&self.stdout));
+        }
 
-                // Dev command flags
-                (Some("dev"), Some("--parties")) => {
-                    show_dev_parties_help();
-                    return Ok(());
-                }
-                (Some("dev"), Some("-p" | "--port")) => {
-                    show_dev_port_help();
-                    return Ok(());
-                }
-                (Some("dev"), Some("--protocol")) => {
-                    show_dev_protocol_help();
-                    return Ok(());
-                }
-                (Some("dev"), Some("--threshold")) => {
-                    show_dev_threshold_help();
-                    return Ok(());
-                }
-                (Some("dev"), Some("--field")) => {
-                    show_dev_field_help();
-                    return Ok(());
-                }
+        if !self.stderr.is_empty() {
+            eprint!("{}", String::from_utf8_lossy(&self.stderr));
+        }
+    }
 
-                // Build command flags
-                (Some("build"), Some("--target")) => {
-                    show_build_target_help();
-                    return Ok(());
+    /// Report the result for `file` in the requested format
+    ///
+    /// Human output ends with the file's status line, followed by the
+    /// compiler's stderr indented beneath it on stdout, so a batch's
+    /// diagnostics stay with the file they belong to even when redirected.
+    fn report(&self, file: &str, format: MessageFormat) {
+        match format {
+            MessageFormat::Human => {
+                if !self.stdout.is_empty() {
+                    print!("{}", String::from_utf8_lossy(&self.stdout));
                 }
-                (Some("build"), Some("--optimize")) => {
-                    show_build_optimize_help();
-                    return Ok(());
+                if self.success {
+                    println!("{} {}", style::prefix(Status::Success), file);
+                } else {
+                    let exit = self.code.map_or_else(|| "killed by a signal".to_string(), |code| format!("exit code {}", code));
+                    println!("{} {} ({})", style::prefix(Status::Failure), file, exit);
                 }
-                (Some("build"), Some("-r" | "--release")) => {
-                    show_build_release_help();
-                    return Ok(());
+                for line in String::from_utf8_lossy(&self.stderr).lines() {
+                    println!("   {}", line);
                 }
+                println!();
+            }
+            MessageFormat::Json => print_file_message(&FileMessage {
+                path: file,
+                success: self.success,
+                up_to_date: false,
+                stdout: String::from_utf8_lossy(&self.stdout),
+                stderr: String::from_utf8_lossy(&self.stderr),
+            }),
+        }
+    }
+}
 
-                // Test command flags
-                (Some("test"), Some("--test")) => {
-                    show_test_test_help();
-                    return Ok(());
-                }
-                (Some("test"), Some("--parties")) => {
-                    show_test_parties_help();
-                    return Ok(());
-                }
-                (Some("test"), Some("--protocol")) => {
-                    show_test_protocol_help();
-                    return Ok(());
-                }
-                (Some("test"), Some("--threshold")) => {
-                    show_test_threshold_help();
-                    return Ok(());
-                }
-                (Some("test"), Some("--field")) => {
-                    show_test_field_help();
-                    return Ok(());
-                }
-                (Some("test"), Some("--integration")) => {
-                    show_test_integration_help();
-                    return Ok(());
-                }
+/// One line of `--message-format json` output
+#[derive(serde::Serialize)]
+struct FileMessage<'a> {
+    path: &'a str,
+    success: bool,
+    /// The compile cache had this file, so the compiler didn't run
+    up_to_date: bool,
+    stdout: std::borrow::Cow<'a, str>,
+    stderr: std::borrow::Cow<'a, str>,
+}
 
-                // Compile command flags
-                (Some("compile"), Some("-o" | "--output")) => {
-                    show_compile_output_help();
-                    return Ok(());
-                }
-                (Some("compile"), Some("-b" | "--binary")) => {
-                    show_compile_binary_help();
-                    return Ok(());
-                }
-                (Some("compile"), Some("--disassemble")) => {
-                    show_compile_disassemble_help();
-                    return Ok(());
-                }
-                (Some("compile"), Some("--print-ir")) => {
-                    show_compile_print_ir_help();
-                    return Ok(());
-                }
-                (Some("compile"), Some("-O" | "--opt-level")) => {
-                    show_compile_opt_level_help();
-                    return Ok(());
-                }
+fn print_file_message(message: &FileMessage) {
+    match serde_json::to_string(message) {
+        Ok(line) => println!("{}", line),
+        Err(e) => eprintln!("{}  Failed to serialize result for {}: {}", style::prefix(Status::Warning), message.path, e),
+    }
+}
 
-                // Run command flags
-                (Some("run"), Some("--parties")) => {
-                    show_run_parties_help();
-                    return Ok(());
-                }
-                (Some("run"), Some("--protocol")) => {
-                    show_run_protocol_help();
-                    return Ok(());
-                }
-                (Some("run"), Some("--threshold")) => {
-                    show_run_threshold_help();
-                    return Ok(());
-                }
-                (Some("run"), Some("--field")) => {
-                    show_run_field_help();
-                    return Ok(());
-                }
-                (Some("run"), Some("--vm-opt")) => {
-                    show_run_vm_opt_help();
-                    return Ok(());
-                }
+/// Report a file the compile cache let us skip
+fn report_up_to_date(file: &str, format: MessageFormat) {
+    match format {
+        MessageFormat::Human => {
+            println!("{} {} (up to date)", style::prefix(Status::Success), file);
+            println!();
+        }
+        MessageFormat::Json => print_file_message(&FileMessage {
+            path: file,
+            success: true,
+            up_to_date: true,
+            stdout: "".into(),
+            stderr: "".into(),
+        }),
+    }
+}
+
+/// Run the compiler on one file without printing anything
+fn run_compiler(
+    compiler_path: &std::path::Path,
+    file: &str,
+    output: &Option<String>,
+    options: &CompileOptions,
+) -> Result<CompilerRun, String> {
+    // Build arguments for the Stoffel-Lang compiler
+    let mut args = vec![file.to_string()];
 
-                _ => {}
-            }
-        }
+    if options.check {
+        args.push("--check".to_string());
     }
 
-    let cli = Cli::parse();
-
-    // If no subcommand is provided, show the honeybadger
-    if std::env::args().len() == 1 {
-        display_honeybadger();
-        return Ok(());
+    if let Some(output) = output {
+        args.push("-o".to_string());
+        args.push(output.clone());
     }
 
-    if cli.verbose {
-        println!("Running command: {:?}", cli.command);
+    if options.binary {
+        args.push("--binary".to_string());
     }
 
-    match cli.command {
-        Commands::Init { name, lib, path, interactive, template } => {
-            let init_options = init::InitOptions {
-                name,
-                lib,
-                path,
-                interactive,
-                template,
-            };
+    if options.disassemble {
+        args.push("--disassemble".to_string());
+    }
 
-            if let Err(e) = init::initialize_project(init_options) {
-                eprintln!("❌ Initialization failed: {}", e);
-                std::process::exit(1);
-            }
-        }
+    if options.print_ir {
+        args.push("--print-ir".to_string());
+    }
 
-        Commands::Compile { file, output, binary, disassemble, print_ir, opt_level } => {
-            // Validate optimization level
-            if opt_level > 3 {
-                eprintln!("❌ Invalid optimization level: {}. Must be 0-3.", opt_level);
-                std::process::exit(1);
-            }
+    if options.opt_level > 0 {
+        args.push(format!("-O{}", options.opt_level));
+    }
 
-            // Build the path to the Stoffel-Lang compiler
-            let exe_path = std::env::current_exe()
-                .map_err(|e| format!("Failed to get executable path: {}", e))?;
-            let exe_dir = exe_path.parent()
-                .ok_or("Failed to get executable directory")?;
+    if let Some(std_path) = &options.std_path {
+        args.push("--std".to_string());
+        args.push(std_path.clone());
+    }
 
-            // Navigate to parent directory to find Stoffel-Lang
-            let stoffel_lang_path = exe_dir.parent()
-                .and_then(|p| p.parent())
-                .and_then(|p| p.parent())
-                .map(|p| p.join("Stoffel-Lang"))
-                .ok_or("Could not locate Stoffel-Lang directory")?;
+    for (key, value) in &options.defines {
+        args.push("--define".to_string());
+        args.push(format!("{}={}", key, value));
+    }
 
-            let compiler_path = stoffel_lang_path.join("target").join("debug").join("stoffellang");
+    if options.coverage {
+        args.push("--coverage".to_string());
+    }
 
-            // Check if Stoffel-Lang compiler exists
-            if !compiler_path.exists() {
-                eprintln!("❌ Stoffel-Lang compiler not found at: {}", compiler_path.display());
-                eprintln!("   Please build Stoffel-Lang first:");
-                eprintln!("   cd {} && cargo build", stoffel_lang_path.display());
-                std::process::exit(1);
-            }
+    // Execute the Stoffel-Lang compiler
+    let output = std::process::Command::new(compiler_path)
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to execute compiler: {}", e))?;
 
-            match file {
-                Some(specific_file) => {
-                    // Compile specific file
-                    if disassemble {
-                        println!("🔧 Disassembling file: {}", specific_file);
-                    } else {
-                        println!("🔧 Compiling StoffelLang file: {}", specific_file);
-                    }
+    Ok(CompilerRun {
+        success: output.status.success(),
+        code: output.status.code(),
+        stdout: output.stdout,
+        stderr: output.stderr,
+    })
+}
 
-                    let success = compile_single_file(&compiler_path, &specific_file, &output, binary, disassemble, print_ir, opt_level)?;
-                    if !success {
-                        std::process::exit(1);
-                    }
-                }
-                None => {
-                    // Compile all files in src/ directory
-                    println!("🔧 Compiling all StoffelLang files in src/ directory...");
+/// Where an effective setting came from
+#[derive(Debug, Default, Clone)]
+enum ConfigSource {
+    /// A command-line flag
+    Flag,
+    /// A named `[mpc.profiles.<name>]` section in Stoffel.toml
+    Profile(String),
+    /// The base `[mpc]` section of Stoffel.toml
+    Manifest,
+    /// The user-global defaults file
+    UserGlobal(std::path::PathBuf),
+    #[default]
+    BuiltIn,
+}
 
-                    // Check if src/ directory exists
-                    if !std::path::Path::new("src").exists() {
-                        eprintln!("❌ No src/ directory found. Please run this command from a Stoffel project root,");
-                        eprintln!("   or specify a specific file to compile.");
-                        std::process::exit(1);
-                    }
+impl ConfigSource {
+    /// Exact location of a setting: the flag name, or the file and key it was read from
+    fn describe(&self, flag: &str, key: &str) -> String {
+        match self {
+            ConfigSource::Flag => format!("--{} flag", flag),
+            ConfigSource::Profile(name) => format!("Stoffel.toml [mpc.profiles.{}] {}", name, key),
+            ConfigSource::Manifest => format!("Stoffel.toml [mpc] {}", key),
+            ConfigSource::UserGlobal(path) => format!("{} {}", path.display(), key),
+            ConfigSource::BuiltIn => "built-in default".to_string(),
+        }
+    }
+}
 
-                    // Find all .stfl files in src/
-                    let stfl_files = find_stfl_files("src")?;
+/// One source of MPC settings; unset values defer to lower-precedence sources
+#[derive(Debug, Default, Clone)]
+struct MpcLayer {
+    protocol: Option<MpcProtocol>,
+    parties: Option<u8>,
+    threshold: Option<u8>,
+    field: Option<MpcField>,
+    source: ConfigSource,
+}
 
-                    if stfl_files.is_empty() {
-                        println!("ℹ️  No .stfl files found in src/ directory.");
-                        return Ok(());
-                    }
+/// Fully resolved MPC settings for a command
+#[derive(Debug, Clone)]
+struct MpcSettings {
+    protocol: MpcProtocol,
+    parties: u8,
+    threshold: u8,
+    field: MpcField,
+    origins: MpcOrigins,
+}
 
-                    println!("   Found {} StoffelLang file(s) to compile:", stfl_files.len());
-                    for file in &stfl_files {
-                        println!("     - {}", file);
-                    }
-                    println!();
-
-                    // Compile each file
-                    let mut successful = 0;
-                    let mut failed = 0;
-
-                    for stfl_file in &stfl_files {
-                        println!("🔧 Compiling: {}", stfl_file);
-
-                        // For batch compilation, don't use custom output names (they would conflict)
-                        let file_output = if output.is_some() && stfl_files.len() > 1 {
-                            eprintln!("⚠️  Custom output path ignored for batch compilation");
-                            None
-                        } else {
-                            output.clone()
-                        };
-
-                        let success = compile_single_file(&compiler_path, stfl_file, &file_output, binary, disassemble, print_ir, opt_level)?;
-
-                        if success {
-                            successful += 1;
-                            println!("✅ {}", stfl_file);
-                        } else {
-                            failed += 1;
-                            println!("❌ {}", stfl_file);
-                        }
-                        println!();
-                    }
+/// Human-readable provenance of each resolved MPC setting
+#[derive(Debug, Clone)]
+struct MpcOrigins {
+    protocol: String,
+    parties: String,
+    threshold: String,
+    field: String,
+}
 
-                    // Summary
-                    println!("📊 Compilation Summary:");
-                    println!("   ✅ Successful: {}", successful);
-                    println!("   ❌ Failed: {}", failed);
-                    println!("   📁 Total: {}", stfl_files.len());
+impl MpcSettings {
+    /// Print each setting with the location it was resolved from
+    fn explain(&self) {
+        println!("🔎 Effective configuration:");
+        print_explained("protocol", &value_name(&self.protocol), &self.origins.protocol);
+        print_explained("parties", &self.parties.to_string(), &self.origins.parties);
+        print_explained("threshold", &self.threshold.to_string(), &self.origins.threshold);
+        print_explained("field", &value_name(&self.field), &self.origins.field);
+    }
+}
 
-                    if failed > 0 {
-                        std::process::exit(1);
-                    } else {
-                        println!("🎉 All files compiled successfully!");
-                    }
-                }
-            }
-        }
+fn print_explained(name: &str, value: &str, origin: &str) {
+    println!("   {:<10} = {:<12} ({})", name, value, origin);
+}
 
-        Commands::Dev { parties, port, protocol, threshold, field } => {
-            println!("🔧 Starting development server...");
-            println!("   Parties: {}", parties);
-            println!("   Port: {}", port);
-            println!("   Protocol: {:?}", protocol);
-            println!("   Field: {:?}", field);
+/// Everything worth pasting into a bug report: CLI build, compiler and supported settings
+fn print_version_report() {
+    println!("stoffel {}", VERSION);
+    println!("compiler:  {}", describe_compiler());
+    println!("platform:  {}-{}", std::env::consts::OS, std::env::consts::ARCH);
+    println!("protocols: {}", possible_values::<MpcProtocol>());
+    println!("fields:    {}", possible_values::<MpcField>());
+}
 
-            let threshold = threshold.unwrap_or_else(|| calculate_threshold(parties, &protocol));
-            println!("   Threshold: {}", threshold);
+/// Version and path of the compiler commands would use
+fn describe_compiler() -> String {
+    match toolchain::find_compiler() {
+        Ok(compiler) => match toolchain::compiler_version(&compiler) {
+            Ok(version) => format!("{} ({})", version, compiler.display()),
+            Err(e) => format!("unknown version ({})", e),
+        },
+        Err(_) => "not found".to_string(),
+    }
+}
 
-            validate_mpc_params(parties, threshold, &protocol)?;
+/// `stoffel toolchain status`: the compiler in use and every installed toolchain
+fn print_toolchain_status() -> Result<(), String> {
+    let dir = toolchain::toolchains_dir().ok_or("Could not determine the Stoffel home directory")?;
+    let index = toolchain::ToolchainIndex::load(&dir)?;
+
+    println!("🧰 Toolchain status:");
+    println!("   CLI:      stoffel {}", VERSION);
+    println!("   Compiler: {}", describe_compiler());
+    println!("   Active:   {}", index.active.as_deref().unwrap_or("none"));
+    println!();
+    println!("{} Installed toolchains ({}):", style::prefix(Status::Package), dir.display());
+    if index.toolchains.is_empty() {
+        println!("   No toolchains installed");
+        println!("   Install one with `stoffel toolchain install <version> --path <compiler>`");
+    }
+    for installed in &index.toolchains {
+        let marker = if index.active.as_ref() == Some(&installed.version) { "*" } else { " " };
+        println!("   {} {} ({})", marker, installed.version, installed.source);
+    }
+    Ok(())
+}
 
-            println!("   [TODO: Initialize StoffelVM with {} parties]", parties);
-            println!("   [TODO: Setup {} protocol with threshold {}]", format!("{:?}", protocol).to_lowercase(), threshold);
-            println!("   [TODO: Start hot reloading server on port {}]", port);
-        }
+/// The command-line spelling of a value enum variant
+fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
 
-        Commands::Build { target, optimize, release } => {
-            println!("🔨 Building project...");
-            if release {
-                println!("   Mode: Release");
-            } else {
-                println!("   Mode: Debug");
-            }
-            if let Some(target) = target {
-                println!("   Target: {}", target);
-            }
-            if optimize {
-                println!("   Optimizations: Enabled");
-            }
-            println!("   [TODO: Implement build logic]");
-        }
+impl MpcLayer {
+    /// Parse the string values of a manifest [mpc] section or profile
+    fn from_manifest(
+        protocol: Option<&str>,
+        parties: Option<u8>,
+        threshold: Option<u8>,
+        field: Option<&str>,
+        source: ConfigSource,
+    ) -> Result<Self, String> {
+        let protocol = protocol
+            .map(|protocol| MpcProtocol::from_str(protocol, true).map_err(|_| format!(
+                "unsupported protocol '{}' (expected one of: {})",
+                protocol,
+                possible_values::<MpcProtocol>()
+            )))
+            .transpose()?;
+        let field = field
+            .map(|field| MpcField::from_str(field, true).map_err(|_| format!(
+                "unsupported field '{}' (expected one of: {})",
+                field,
+                possible_values::<MpcField>()
+            )))
+            .transpose()?;
+
+        Ok(MpcLayer { protocol, parties, threshold, field, source })
+    }
+}
 
-        Commands::Test { test, parties, protocol, threshold, field, integration } => {
-            println!("🧪 Running tests...");
-            println!("   Parties: {}", parties);
-            println!("   Protocol: {:?}", protocol);
-            println!("   Field: {:?}", field);
+/// Merge MPC setting layers, highest precedence first, over the built-in defaults
+///
+/// A threshold is only taken from the layer that supplied the party count or
+/// a higher-precedence one, so a threshold chosen for a different party count
+/// is never inherited; otherwise it is calculated from the protocol, which
+/// fails if there are too few parties to calculate one.
+fn merge_mpc_layers(layers: &[MpcLayer]) -> Result<MpcSettings, String> {
+    let built_in = ConfigSource::BuiltIn;
+    let (protocol, protocol_source) = layers.iter()
+        .find_map(|layer| layer.protocol.clone().map(|protocol| (protocol, &layer.source)))
+        .unwrap_or((MpcProtocol::Honeybadger, &built_in));
+    let (field, field_source) = layers.iter()
+        .find_map(|layer| layer.field.clone().map(|field| (field, &layer.source)))
+        .unwrap_or((MpcField::Bls12_381, &built_in));
+
+    let parties_layer = layers.iter().position(|layer| layer.parties.is_some());
+    let (parties, parties_source) = parties_layer
+        .and_then(|index| layers[index].parties.map(|parties| (parties, &layers[index].source)))
+        .unwrap_or((5, &built_in));
+    let threshold_layers = parties_layer.map_or(layers, |index| &layers[..=index]);
+    let (threshold, threshold_origin) = match threshold_layers.iter()
+        .find_map(|layer| layer.threshold.map(|threshold| (threshold, layer.source.describe("threshold", "threshold"))))
+    {
+        Some(configured) => configured,
+        None => (calculate_threshold(parties, &protocol)?, format!("calculated from {} parties", parties)),
+    };
+
+    let origins = MpcOrigins {
+        protocol: protocol_source.describe("protocol", "protocol"),
+        parties: parties_source.describe("parties", "parties"),
+        threshold: threshold_origin,
+        field: field_source.describe("field", "field"),
+    };
+
+    Ok(MpcSettings { protocol, parties, threshold, field, origins })
+}
 
-            let threshold = threshold.unwrap_or_else(|| calculate_threshold(parties, &protocol));
-            println!("   Threshold: {}", threshold);
+/// The user-global defaults file as a settings layer
+fn user_global_layer() -> Result<MpcLayer, String> {
+    let defaults = init::UserDefaults::load()?;
+    let Some(path) = init::UserDefaults::path() else {
+        return Ok(MpcLayer::default());
+    };
 
-            validate_mpc_params(parties, threshold, &protocol)?;
+    MpcLayer::from_manifest(None, None, None, defaults.field.as_deref(), ConfigSource::UserGlobal(path.clone()))
+        .map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
 
-            if let Some(test) = test {
-                println!("   Specific test: {}", test);
-            }
-            if integration {
-                println!("   Type: Integration tests");
+/// Look up a named MPC profile in the manifest as a settings layer
+fn manifest_profile_layer(manifest: &init::StoffelConfig, name: &str) -> Result<MpcLayer, String> {
+    let profile = manifest.mpc.profiles.as_ref()
+        .and_then(|profiles| profiles.get(name))
+        .ok_or_else(|| {
+            let mut available: Vec<&String> = manifest.mpc.profiles.iter().flatten().map(|(name, _)| name).collect();
+            available.sort();
+            if available.is_empty() {
+                format!("MPC profile '{}' not found: Stoffel.toml declares no [mpc.profiles]", name)
+            } else {
+                format!(
+                    "MPC profile '{}' not found. Available profiles: {}",
+                    name,
+                    available.iter().map(|name| name.as_str()).collect::<Vec<_>>().join(", ")
+                )
             }
-            println!("   [TODO: Initialize test environment with {} parties]", parties);
-            println!("   [TODO: Setup {} protocol for testing]", format!("{:?}", protocol).to_lowercase());
-        }
+        })?;
+
+    MpcLayer::from_manifest(
+        profile.protocol.as_deref(),
+        profile.parties,
+        profile.threshold,
+        profile.field.as_deref(),
+        ConfigSource::Profile(name.to_string()),
+    )
+        .map_err(|e| format!("Invalid MPC profile '{}': {}", name, e))
+}
 
-        Commands::Run { args, parties, protocol, threshold, field, vm_opt } => {
-            println!("▶️  Running project...");
-            println!("   Parties: {}", parties);
-            println!("   Protocol: {:?}", protocol);
-            println!("   Field: {:?}", field);
-            println!("   VM Optimization: {:?}", vm_opt);
+/// Remove the global dependency and template caches, reporting reclaimed space per cache
+fn clean_global_caches(skip_confirm: bool) -> Result<(), String> {
+    let root = cache::cache_root().ok_or("Could not determine the Stoffel home directory")?;
+    let caches: Vec<(&str, std::path::PathBuf)> = cache::GLOBAL_CACHES
+        .iter()
+        .map(|(description, dir)| (*description, root.join(dir)))
+        .filter(|(_, path)| path.exists())
+        .collect();
+
+    info!("🧹 Cleaning global caches in {}...", root.display());
+    if caches.is_empty() {
+        println!("   Nothing to clean");
+        return Ok(());
+    }
 
-            let threshold = threshold.unwrap_or_else(|| calculate_threshold(parties, &protocol));
-            println!("   Threshold: {}", threshold);
+    if !skip_confirm {
+        for (description, path) in &caches {
+            println!("   {} ({}): {}", description, cache::format_size(cache::dir_size(path)), path.display());
+        }
+        if !init::prompt_confirm("These caches are shared by all projects. Remove them?", false)? {
+            println!("Aborted. No caches were removed.");
+            return Ok(());
+        }
+    }
 
-            validate_mpc_params(parties, threshold, &protocol)?;
+    let mut total = 0;
+    for (description, path) in &caches {
+        let reclaimed = cache::remove_dir(path)?;
+        total += reclaimed;
+        println!("   Removed {} ({})", description, cache::format_size(reclaimed));
+    }
+    println!("{} Reclaimed {}", style::prefix(Status::Success), cache::format_size(total));
 
-            if !args.is_empty() {
-                println!("   Args: {:?}", args);
-            }
-            println!("   [TODO: Initialize StoffelVM with {:?} optimization]", vm_opt);
-            println!("   [TODO: Setup {} MPC network with {} parties]", format!("{:?}", protocol).to_lowercase(), parties);
-            println!("   [TODO: Execute program with args: {:?}]", args);
-        }
+    Ok(())
+}
 
-        Commands::Deploy { environment, tee, k8s } => {
-            println!("🚀 Deploying project...");
-            println!("   Environment: {}", environment);
-            if tee {
-                println!("   TEE deployment enabled");
-            }
-            if k8s {
-                println!("   Kubernetes deployment enabled");
-            }
-            println!("   [TODO: Implement deployment logic]");
-        }
+/// Resolve the MPC settings for dev/run/test
+///
+/// Precedence: CLI flags, then the selected profile, then the base [mpc]
+/// section, then the user-global defaults, then built-in defaults.
+/// Stoffel.toml is read and validated when present; selecting a profile
+/// requires it.
+fn resolve_mpc_settings(cli: MpcLayer, profile: Option<&str>) -> Result<MpcSettings, String> {
+    if profile.is_none() && !std::path::Path::new(config::MANIFEST_NAME).exists() {
+        return merge_mpc_layers(&[cli, user_global_layer()?]);
+    }
 
-        Commands::Add { package, version, dev } => {
-            println!("📦 Adding dependency: {}", package);
-            if let Some(version) = version {
-                println!("   Version: {}", version);
-            }
-            if dev {
-                println!("   Type: Development dependency");
-            }
-            println!("   [TODO: Implement package management]");
-        }
+    let manifest = config::load_project(std::path::Path::new("."))?;
+    manifest_mpc_settings(&manifest, cli, profile)
+}
 
-        Commands::Publish { dry_run } => {
-            println!("📤 Publishing package...");
-            if dry_run {
-                println!("   Mode: Dry run");
-            }
-            println!("   [TODO: Implement publishing logic]");
-        }
+/// Resolve MPC settings against an already loaded and validated Stoffel.toml
+fn manifest_mpc_settings(manifest: &init::StoffelConfig, cli: MpcLayer, profile: Option<&str>) -> Result<MpcSettings, String> {
+    let base = config::base_layer(manifest)?;
 
-        Commands::Plugin { action } => {
-            match action {
-                PluginCommands::Install { name } => {
-                    println!("🔌 Installing plugin: {}", name);
-                    println!("   [TODO: Implement plugin installation]");
-                }
-                PluginCommands::List => {
-                    println!("🔌 Installed plugins:");
-                    println!("   [TODO: List installed plugins]");
-                }
-                PluginCommands::Remove { name } => {
-                    println!("🔌 Removing plugin: {}", name);
-                    println!("   [TODO: Implement plugin removal]");
-                }
-            }
-        }
+    let mut layers = vec![cli];
+    if let Some(profile) = profile {
+        layers.push(manifest_profile_layer(manifest, profile)?);
+    }
+    layers.push(base);
+    layers.push(user_global_layer()?);
 
-        Commands::Status => {
-            println!("📊 Project Status:");
-            println!("   [TODO: Check project configuration, dependencies, build status]");
-        }
+    merge_mpc_layers(&layers)
+}
 
-        Commands::Clean => {
-            println!("🧹 Cleaning build artifacts...");
-            println!("   [TODO: Implement clean logic]");
+/// Refuse to deploy artifacts built for a different number of parties
+///
+/// A missing build manifest is refused too, since nothing was built to
+/// deploy. With `force` both only produce a warning.
+fn check_deployed_parties(build_dir: &std::path::Path, parties: u8, force: bool) -> Result<(), String> {
+    let path = build_manifest::manifest_path(build_dir);
+    let Some(manifest) = build_manifest::load(&path)? else {
+        let message = format!("No build manifest at {}; run `stoffel build` before deploying.", path.display());
+        if force {
+            eprintln!("{}  {} (continuing because of --force)", style::prefix(Status::Warning), message);
+            return Ok(());
         }
+        return Err(format!("{} Pass --force to deploy anyway.", message));
+    };
 
-        Commands::Update { package } => {
-            if let Some(package) = package {
-                println!("⬆️  Updating package: {}", package);
-            } else {
-                println!("⬆️  Updating all dependencies...");
-            }
-            println!("   [TODO: Implement dependency updates]");
-        }
+    if manifest.parties == parties {
+        return Ok(());
     }
 
-    Ok(())
+    let message = format!(
+        "Artifacts in {} were built for {} parties, but this deployment provisions {}. Rebuild with `stoffel build` before deploying.",
+        build_dir.display(),
+        manifest.parties,
+        parties
+    );
+    if force {
+        eprintln!("{}  {} (continuing because of --force)", style::prefix(Status::Warning), message);
+        Ok(())
+    } else {
+        Err(format!("{} Pass --force to deploy anyway.", message))
+    }
 }
 
-/// Find all .stfl files recursively in a directory
-fn find_stfl_files(dir: &str) -> Result<Vec<String>, String> {
-    let mut stfl_files = Vec::new();
-    find_stfl_files_recursive(std::path::Path::new(dir), &mut stfl_files)?;
-    stfl_files.sort(); // Sort for consistent ordering
-    Ok(stfl_files)
+/// Largest threshold the protocol tolerates with `parties` parties
+///
+/// Fails when the protocol can't run with that few parties at all, since no
+/// threshold would be valid then.
+fn calculate_threshold(parties: u8, protocol: &MpcProtocol) -> Result<u8, String> {
+    let spec = protocol.spec();
+    if parties < spec.min_parties {
+        return Err(format!(
+            "{} protocol requires at least {} parties, got {}",
+            spec.name, spec.min_parties, parties
+        ));
+    }
+    Ok(spec.max_threshold(parties))
 }
 
-/// Recursively find .stfl files in a directory
-fn find_stfl_files_recursive(dir: &std::path::Path, files: &mut Vec<String>) -> Result<(), String> {
-    let entries = std::fs::read_dir(dir)
-        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+/// Validate MPC parameters for the given protocol
+fn validate_mpc_params(parties: u8, threshold: u8, protocol: &MpcProtocol) -> Result<(), String> {
+    let spec = protocol.spec();
+    let max_threshold = calculate_threshold(parties, protocol)?;
+    if threshold > max_threshold {
+        return Err(format!(
+            "{} protocol requires threshold {}. For {} parties, max threshold is {} (got {})",
+            spec.name,
+            spec.bound(),
+            parties,
+            max_threshold,
+            threshold
+        ));
+    }
 
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
+    Ok(())
+}
 
-        if path.is_dir() {
-            // Recursively search subdirectories
-            find_stfl_files_recursive(&path, files)?;
-        } else if let Some(extension) = path.extension() {
-            if extension == "stfl" {
-                files.push(path.to_string_lossy().to_string());
-            }
+fn print_field_info(spec: &FieldSpec) {
+    println!("🔢 Field: {}", spec.name);
+    println!("   Description: {}", spec.description);
+    println!("   Modulus: {}", spec.modulus);
+    println!("   Bit length: {}", spec.bits);
+    match spec.security_bits {
+        Some(bits) => println!("   Security: ~{} bits", bits),
+        None => println!("   Security: none (testing only)"),
+    }
+}
+
+/// `stoffel explain threshold`: the calculated threshold and what it tolerates
+///
+/// Everything is derived from the protocol's `ProtocolSpec`, so the
+/// explanation always matches what `calculate_threshold` and validation do.
+fn explain_threshold(parties: u8, protocol: &MpcProtocol) -> Vec<String> {
+    let spec = protocol.spec();
+    let mut lines = vec![
+        format!("🧮 {} with {} parties", spec.name, parties),
+        format!("   Security bound: {}, where t is the number of corrupted parties", spec.bound()),
+    ];
+
+    let threshold = match calculate_threshold(parties, protocol) {
+        Ok(threshold) => threshold,
+        Err(e) => {
+            lines.push(format!("   {} {}", style::prefix(Status::Failure), e));
+            lines.push(format!(
+                "   With {} parties the threshold would be {}",
+                spec.min_parties,
+                spec.max_threshold(spec.min_parties)
+            ));
+            return lines;
         }
+    };
+    lines.push(format!(
+        "   Threshold: {}, the largest t with {} < {}/{} = {:.2}",
+        threshold,
+        threshold,
+        parties,
+        spec.threshold_divisor,
+        f64::from(parties) / f64::from(spec.threshold_divisor)
+    ));
+    lines.push(format!("   Tolerates: up to {} of {} parties corrupted ({})", threshold, parties, spec.adversary));
+    lines.push(format!(
+        "   The guarantees are lost once {} or more parties collude",
+        threshold + 1
+    ));
+    match (parties..=u8::MAX).find(|&more| spec.max_threshold(more) > threshold) {
+        Some(more) => lines.push(format!("   Tolerating {} corrupted parties needs at least {} parties", threshold + 1, more)),
+        None => lines.push("   No larger party count raises the threshold".to_string()),
     }
+    lines
+}
 
-    Ok(())
+/// `stoffel explain field`: what the modulus and security level mean in practice
+///
+/// The raw parameters are left to `stoffel info field`.
+fn explain_field(spec: &FieldSpec) -> Vec<String> {
+    let mut lines = vec![
+        format!("🔢 What the {} field means for a program", spec.name),
+        format!("   Secrets are integers modulo this {}-bit prime; sums and products wrap around at the modulus,", spec.bits),
+        format!("   so values stay exact while every intermediate result is below 2^{}", spec.bits - 1),
+    ];
+    match spec.security_bits {
+        Some(bits) => lines.push(format!(
+            "   Breaking it takes on the order of 2^{} operations, so it is fine for release builds and deploys",
+            bits
+        )),
+        None => lines.push(
+            "   It offers no cryptographic security: release builds and `stoffel deploy` refuse it unless --allow-insecure-field is given"
+                .to_string(),
+        ),
+    }
+    lines.push(format!("   Select it with --field {} or mpc.field = \"{}\" in {}", spec.name, spec.name, config::MANIFEST_NAME));
+    lines.push(format!("   Run `stoffel info field {}` for the modulus", spec.name));
+    lines
 }
 
-/// Compile a single StoffelLang file
-fn compile_single_file(
-    compiler_path: &std::path::Path,
-    file: &str,
-    output: &Option<String>,
-    binary: bool,
-    disassemble: bool,
-    print_ir: bool,
-    opt_level: u8,
-) -> Result<bool, String> {
-    // Build arguments for the Stoffel-Lang compiler
-    let mut args = vec![file.to_string()];
+/// How an MPC field is about to be used, which decides how an insecure one is treated
+#[derive(Clone, Copy)]
+enum FieldContext {
+    /// Debug builds and local runs: an insecure field only warns
+    Development,
+    /// Release builds and deployments: an insecure field is refused unless explicitly allowed
+    Production { command: &'static str, allow_insecure: bool },
+}
 
-    if let Some(output) = output {
-        args.push("-o".to_string());
-        args.push(output.clone());
+/// Warn about, or refuse, a field that is documented as testing-only
+fn check_field_security(field: &str, context: FieldContext) -> Result<(), String> {
+    let insecure = MpcField::from_str(field, true).is_ok_and(|field| field.spec().security_bits.is_none());
+    if !insecure {
+        return Ok(());
     }
 
-    if binary {
-        args.push("--binary".to_string());
+    match context {
+        FieldContext::Production { command, allow_insecure: false } => Err(format!(
+            "{} refuses the {} field: it is for testing only and not secure. Set mpc.field to a production field such as bls12-381, or pass --allow-insecure-field.",
+            command, field
+        )),
+        FieldContext::Production { allow_insecure: true, .. } => {
+            eprintln!("{}  Using the insecure {} field (allowed by --allow-insecure-field)", style::prefix(Status::Warning), field);
+            Ok(())
+        }
+        FieldContext::Development => {
+            eprintln!("{}  The {} field is for testing only and not secure; switch to a production field such as bls12-381 before deploying", style::prefix(Status::Warning), field);
+            Ok(())
+        }
     }
+}
 
-    if disassemble {
-        args.push("--disassemble".to_string());
-    }
+/// First port used when party addresses are assigned automatically
+const DEFAULT_PARTY_BASE_PORT: u16 = 9001;
+
+/// Resolve the address each simulated party binds to
+///
+/// Without a map, parties are assigned sequential localhost ports starting at
+/// `DEFAULT_PARTY_BASE_PORT`. A map must name every party index exactly once
+/// and may not assign the same address twice.
+fn resolve_party_addresses(parties: u8, parties_map: Option<&str>) -> Result<Vec<std::net::SocketAddr>, String> {
+    let Some(parties_map) = parties_map else {
+        return (0..parties as u16)
+            .map(|index| {
+                let port = DEFAULT_PARTY_BASE_PORT.checked_add(index)
+                    .ok_or_else(|| "Too many parties for automatic port assignment".to_string())?;
+                Ok(std::net::SocketAddr::from(([127, 0, 0, 1], port)))
+            })
+            .collect();
+    };
+
+    let mut addresses: Vec<Option<std::net::SocketAddr>> = vec![None; parties as usize];
+
+    for entry in parties_map.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (index, address) = entry.split_once('=')
+            .ok_or_else(|| format!("Invalid parties map entry '{}': expected INDEX=ADDRESS", entry))?;
+
+        let index: usize = index.trim().parse()
+            .map_err(|_| format!("Invalid party index '{}' in parties map", index.trim()))?;
+        if index >= parties as usize {
+            return Err(format!(
+                "Party index {} in parties map is out of range for {} parties (expected 0-{})",
+                index,
+                parties,
+                parties.saturating_sub(1)
+            ));
+        }
 
-    if print_ir {
-        args.push("--print-ir".to_string());
+        let address: std::net::SocketAddr = address.trim().parse()
+            .map_err(|e| format!("Invalid address '{}' for party {}: {}", address.trim(), index, e))?;
+
+        if addresses[index].is_some() {
+            return Err(format!("Party {} is assigned more than once in parties map", index));
+        }
+        if let Some(other) = addresses.iter().position(|existing| *existing == Some(address)) {
+            return Err(format!("Address {} is assigned to both party {} and party {}", address, other, index));
+        }
+
+        addresses[index] = Some(address);
     }
 
-    if opt_level > 0 {
-        args.push(format!("-O{}", opt_level));
+    let missing: Vec<String> = addresses.iter()
+        .enumerate()
+        .filter(|(_, address)| address.is_none())
+        .map(|(index, _)| index.to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Parties map is missing an address for party {}", missing.join(", ")));
     }
 
-    // Execute the Stoffel-Lang compiler
-    let output = std::process::Command::new(compiler_path)
-        .args(&args)
-        .output()
-        .map_err(|e| format!("Failed to execute compiler: {}", e))?;
+    Ok(addresses.into_iter().flatten().collect())
+}
+
+/// Outcome of a single project validation check
+struct ValidationCheck {
+    name: &'static str,
+    result: Result<String, String>,
+}
 
-    // Print compiler output
-    if !output.stdout.is_empty() {
-        print!("{}", String::from_utf8_lossy(&output.stdout));
+/// Run every manifest and layout check for the project in `dir`
+fn validate_project(dir: &std::path::Path) -> Vec<ValidationCheck> {
+    let mut checks = Vec::new();
+
+    let manifest = config::load(&dir.join(config::MANIFEST_NAME));
+    checks.push(ValidationCheck {
+        name: "Manifest",
+        result: manifest.as_ref().map(|_| "Stoffel.toml parsed".to_string()).map_err(|e| e.clone()),
+    });
+    let Ok(manifest) = manifest else {
+        return checks;
+    };
+
+    let protocol = MpcProtocol::from_str(&manifest.mpc.protocol, true);
+    checks.push(ValidationCheck {
+        name: "Protocol",
+        result: protocol.as_ref()
+            .map(|_| manifest.mpc.protocol.clone())
+            .map_err(|_| format!(
+                "unsupported protocol '{}' (expected one of: {})",
+                manifest.mpc.protocol,
+                possible_values::<MpcProtocol>()
+            )),
+    });
+
+    checks.push(ValidationCheck {
+        name: "Field",
+        result: MpcField::from_str(&manifest.mpc.field, true)
+            .map(|_| manifest.mpc.field.clone())
+            .map_err(|_| format!(
+                "unsupported field '{}' (expected one of: {})",
+                manifest.mpc.field,
+                possible_values::<MpcField>()
+            )),
+    });
+
+    if let Ok(protocol) = &protocol {
+        let threshold = match manifest.mpc.threshold {
+            Some(threshold) => Ok(threshold),
+            None => calculate_threshold(manifest.mpc.parties, protocol),
+        };
+        checks.push(ValidationCheck {
+            name: "MPC parameters",
+            result: threshold
+                .and_then(|threshold| validate_mpc_params(manifest.mpc.parties, threshold, protocol).map(|_| threshold))
+                .map(|threshold| format!("{} parties, threshold {}", manifest.mpc.parties, threshold)),
+        });
     }
 
-    if !output.stderr.is_empty() {
-        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+    if manifest.mpc.profiles.as_ref().is_some_and(|profiles| !profiles.is_empty()) {
+        checks.push(ValidationCheck {
+            name: "MPC profiles",
+            result: config::validate_profiles(&manifest)
+                .map(|_| format!("{} profile(s) valid", manifest.mpc.profiles.iter().flatten().count())),
+        });
     }
 
-    Ok(output.status.success())
+    let missing_paths: Vec<String> = manifest.dependencies.iter()
+        .chain(manifest.dev_dependencies.iter())
+        .flatten()
+        .filter(|(_, spec)| deps::is_path_dependency(spec) && !dir.join(spec).is_dir())
+        .map(|(name, spec)| format!("{} ({})", name, spec))
+        .collect();
+    checks.push(ValidationCheck {
+        name: "Dependency paths",
+        result: if missing_paths.is_empty() {
+            Ok("all local dependency paths exist".to_string())
+        } else {
+            Err(format!("missing: {}", missing_paths.join(", ")))
+        },
+    });
+
+    let src_dir = dir.join("src");
+    let sources = if src_dir.is_dir() {
+        find_project_stfl_files(dir, &src_dir).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let is_lib = src_dir.join("lib.stfl").is_file();
+    checks.push(ValidationCheck {
+        name: "Sources",
+        result: if sources.is_empty() {
+            Err("no .stfl files found in src/".to_string())
+        } else if is_lib {
+            Ok(format!("library with {} .stfl file(s)", sources.len()))
+        } else {
+            Ok(format!("application with {} .stfl file(s)", sources.len()))
+        },
+    });
+
+    checks
 }
 
-/// Calculate appropriate threshold based on number of parties and protocol
-fn calculate_threshold(parties: u8, protocol: &MpcProtocol) -> u8 {
-    match protocol {
-        MpcProtocol::Honeybadger => {
-            // HoneyBadger requires n >= 5 and t < n/3
-            if parties < 5 {
-                // Return a reasonable threshold anyway, validation will catch this
-                return 1;
-            }
-            (parties - 1) / 3
+/// One line of the `stoffel doctor` checklist
+struct DoctorCheck {
+    name: &'static str,
+    status: Status,
+    detail: String,
+}
+
+impl DoctorCheck {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        DoctorCheck { name, status, detail: detail.into() }
+    }
+}
+
+/// Check the toolchain, then the project in `dir` if there is one
+///
+/// A missing compiler or an invalid project fails; a missing git or running
+/// outside a project only warns, since plenty of commands work without them.
+fn diagnose(dir: &std::path::Path) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+
+    checks.push(match toolchain::find_compiler() {
+        Ok(compiler) => match toolchain::compiler_version(&compiler) {
+            Ok(version) => DoctorCheck::new("Compiler", Status::Success, format!("{} ({})", version, compiler.display())),
+            Err(e) => DoctorCheck::new("Compiler", Status::Warning, format!("found, but its version is unknown: {}", e)),
+        },
+        Err(e) => DoctorCheck::new("Compiler", Status::Failure, e),
+    });
+
+    checks.push(match std::process::Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            DoctorCheck::new("Git", Status::Success, String::from_utf8_lossy(&output.stdout).trim())
         }
+        _ => DoctorCheck::new("Git", Status::Warning, "not found; `stoffel init` won't create a repository"),
+    });
+
+    if !dir.join(config::MANIFEST_NAME).is_file() {
+        checks.push(DoctorCheck::new(
+            "Project",
+            Status::Warning,
+            format!("no {} in this directory, skipping project checks", config::MANIFEST_NAME),
+        ));
+        return checks;
     }
+
+    checks.extend(validate_project(dir).into_iter().map(|check| match check.result {
+        Ok(detail) => DoctorCheck::new(check.name, Status::Success, detail),
+        Err(problem) => DoctorCheck::new(check.name, Status::Failure, problem),
+    }));
+    checks
 }
 
-/// Validate MPC parameters for the given protocol
-fn validate_mpc_params(parties: u8, threshold: u8, protocol: &MpcProtocol) -> Result<(), String> {
-    match protocol {
-        MpcProtocol::Honeybadger => {
-            if parties < 5 {
-                return Err("HoneyBadger protocol requires at least 5 parties".to_string());
-            }
-            if threshold >= (parties + 2) / 3 {
-                return Err(format!(
-                    "HoneyBadger protocol requires threshold < n/3. For {} parties, max threshold is {}",
-                    parties,
-                    (parties + 2) / 3 - 1
-                ));
+/// Comma-separated list of the CLI names accepted for a value enum
+fn possible_values<T: ValueEnum>() -> String {
+    T::value_variants()
+        .iter()
+        .filter_map(|variant| variant.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Find every path from the root package to `package` in the dependency graph
+///
+/// Dependencies whose version spec names a local directory containing a
+/// Stoffel.toml are followed transitively. Where a directory has a
+/// Stoffel.lock, only the packages it locks are part of the build, and each
+/// step is labelled with its locked version.
+fn find_dependency_paths(
+    root_dir: &std::path::Path,
+    root: &init::StoffelConfig,
+    package: &str,
+) -> Result<Vec<Vec<String>>, String> {
+    let mut paths = Vec::new();
+    let mut current = vec![(root.package.name.clone(), None)];
+    collect_dependency_paths(root_dir, root, package, &mut current, &mut paths)?;
+    Ok(paths)
+}
+
+fn collect_dependency_paths(
+    dir: &std::path::Path,
+    manifest: &init::StoffelConfig,
+    package: &str,
+    current: &mut Vec<(String, Option<String>)>,
+    paths: &mut Vec<Vec<String>>,
+) -> Result<(), String> {
+    let lock_path = dir.join(lockfile::LOCKFILE_NAME);
+    let lock = if lock_path.is_file() {
+        Some(lockfile::Lockfile::load(&lock_path)?)
+    } else {
+        None
+    };
+
+    let mut dependencies: Vec<(&String, &String)> = manifest.dependencies.iter()
+        .chain(manifest.dev_dependencies.iter())
+        .flatten()
+        .collect();
+    dependencies.sort();
+
+    for (name, spec) in dependencies {
+        // Guard against dependency cycles
+        if current.iter().any(|(visited, _)| visited == name) {
+            continue;
+        }
+
+        let locked = match &lock {
+            Some(lock) => match lock.packages.iter().find(|locked| &locked.name == name) {
+                Some(locked) => Some(locked.version.clone()),
+                // Declared but never locked, so not part of the build
+                None => continue,
+            },
+            None => None,
+        };
+
+        current.push((name.clone(), locked));
+        if name == package {
+            paths.push(current.iter().map(|(name, version)| match version {
+                Some(version) => format!("{} {}", name, version),
+                None => name.clone(),
+            }).collect());
+        } else {
+            let dep_dir = dir.join(spec);
+            if dep_dir.join(config::MANIFEST_NAME).is_file() {
+                let dep_manifest = config::load(&dep_dir.join(config::MANIFEST_NAME))?;
+                collect_dependency_paths(&dep_dir, &dep_manifest, package, current, paths)?;
             }
         }
+        current.pop();
     }
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn source_discovery_survives_symlink_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir_all(src.join("lib")).unwrap();
+        std::fs::write(src.join("main.stfl"), "").unwrap();
+        std::fs::write(src.join("lib/math.stfl"), "").unwrap();
+        std::os::unix::fs::symlink(&src, src.join("lib/loop")).unwrap();
+
+        let files = find_stfl_files(&src.to_string_lossy()).unwrap();
+        let names: Vec<_> = files.iter().map(|file| std::path::Path::new(file).strip_prefix(&src).unwrap()).collect();
+        assert_eq!(names, [std::path::Path::new("lib/math.stfl"), std::path::Path::new("main.stfl")]);
+    }
+
+    #[test]
+    fn threshold_explanation_is_derived_from_the_protocol_rules() {
+        let lines = explain_threshold(7, &MpcProtocol::Honeybadger).join("\n");
+        assert!(lines.contains("t < n/3"), "{}", lines);
+        assert!(lines.contains("Threshold: 2, the largest t with 2 < 7/3 = 2.33"), "{}", lines);
+        assert!(lines.contains("Tolerating 3 corrupted parties needs at least 10 parties"), "{}", lines);
+
+        let lines = explain_threshold(3, &MpcProtocol::Shamir).join("\n");
+        assert!(lines.contains("Threshold: 1"), "{}", lines);
+        assert!(lines.contains("semi-honest"), "{}", lines);
+
+        let lines = explain_threshold(4, &MpcProtocol::Honeybadger).join("\n");
+        assert!(lines.contains("requires at least 5 parties, got 4"), "{}", lines);
+        assert!(!lines.contains("Threshold:"), "{}", lines);
+    }
+
+    #[test]
+    fn editing_a_module_next_to_the_source_makes_it_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main.stfl");
+        let util = dir.path().join("util.stfl");
+        std::fs::write(&main, "main() { util() }").unwrap();
+        std::fs::write(&util, "util() {}").unwrap();
+        std::fs::write(dir.path().join("main.bin"), "").unwrap();
+
+        let file = main.to_string_lossy().to_string();
+        let options = CompileOptions { binary: true, ..Default::default() };
+        let mut cache = compile_cache::CompileCache::default();
+        cache.record(&file, cache_entry(&file, &None, &options, "").unwrap());
+        assert!(cache.is_fresh(&file, &cache_entry(&file, &None, &options, "").unwrap()));
+
+        std::fs::write(&util, "util() { 1 }").unwrap();
+        assert!(!cache.is_fresh(&file, &cache_entry(&file, &None, &options, "").unwrap()));
+    }
+
+    #[test]
+    fn deploy_needs_a_matching_build_manifest_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let error = check_deployed_parties(dir.path(), 5, false).unwrap_err();
+        assert!(error.starts_with("No build manifest"), "{}", error);
+        assert!(check_deployed_parties(dir.path(), 5, true).is_ok());
+
+        let manifest = build_manifest::Manifest {
+            opt_level: 0,
+            protocol: "honeybadger".to_string(),
+            parties: 5,
+            threshold: 1,
+            field: "bls12-381".to_string(),
+            artifacts: Vec::new(),
+        };
+        build_manifest::save(&build_manifest::manifest_path(dir.path()), &manifest).unwrap();
+        assert!(check_deployed_parties(dir.path(), 5, false).is_ok());
+        let error = check_deployed_parties(dir.path(), 7, false).unwrap_err();
+        assert!(error.ends_with("Pass --force to deploy anyway."), "{}", error);
+        assert!(check_deployed_parties(dir.path(), 7, true).is_ok());
+    }
+
+    #[test]
+    fn validate_uses_the_validated_projects_ignore_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/generated")).unwrap();
+        std::fs::write(dir.path().join("Stoffel.toml"), "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n").unwrap();
+        std::fs::write(dir.path().join("src/main.stfl"), "").unwrap();
+        std::fs::write(dir.path().join("src/generated/out.stfl"), "").unwrap();
+        std::fs::write(dir.path().join(source_filter::IGNORE_FILE), "src/generated/\n").unwrap();
+
+        let checks = validate_project(dir.path());
+        let sources = checks.iter().find(|check| check.name == "Sources").unwrap();
+        assert_eq!(sources.result, Ok("application with 1 .stfl file(s)".to_string()));
+    }
+
+    #[test]
+    fn dry_run_init_accepts_no_git() {
+        assert!(Cli::try_parse_from(["stoffel", "init", "demo", "--dry-run", "--no-git"]).is_ok());
+        assert!(Cli::try_parse_from(["stoffel", "init", "demo", "--dry-run", "--print-only"]).is_err());
+    }
+
+    #[test]
+    fn why_follows_only_the_locked_dependency_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let project = |name: &str, dependencies: &str| {
+            format!(
+                "[package]\nname = \"{}\"\nversion = \"0.1.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n\n[dependencies]\n{}",
+                name, dependencies
+            )
+        };
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::create_dir_all(root.join("math")).unwrap();
+        std::fs::write(root.join("app").join(config::MANIFEST_NAME), project("app", "math = \"../math\"\nextra = \"1.0\"\n")).unwrap();
+        std::fs::write(root.join("math").join(config::MANIFEST_NAME), project("math", "extra = \"1.0\"\n")).unwrap();
+
+        let app = root.join("app");
+        let manifest = config::load(&app.join(config::MANIFEST_NAME)).unwrap();
+        let paths = find_dependency_paths(&app, &manifest, "extra").unwrap();
+        assert_eq!(paths, [vec!["app", "extra"], vec!["app", "math", "extra"]]);
+
+        // Only what Stoffel.lock resolved is built, at the locked version
+        let mut lock = lockfile::Lockfile::default();
+        lock.lock("math", "0.1.0");
+        lock.save(&app.join(lockfile::LOCKFILE_NAME)).unwrap();
+        let paths = find_dependency_paths(&app, &manifest, "extra").unwrap();
+        assert_eq!(paths, [vec!["app", "math 0.1.0", "extra"]]);
+    }
+}
@@ -0,0 +1,257 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::init::StoffelConfig;
+
+/// Directory packaged archives are written to
+pub const PACKAGE_DIR: &str = "target/package";
+
+/// Top-level files included in a package when present
+const EXTRA_FILES: &[&str] = &["README.md", "LICENSE"];
+
+/// Directories never included in a package, wherever they appear
+const SKIPPED_DIRS: &[&str] = &["target", ".git", ".hg", ".svn", ".stoffel-cache"];
+
+/// A package ready to be archived
+pub struct Package {
+    /// `<name>-<version>`, also the directory every archived file sits under
+    pub id: String,
+    /// Files to include, relative to the project root
+    pub files: Vec<PathBuf>,
+}
+
+impl Package {
+    /// Collect the files of the project at `root` described by `manifest`
+    pub fn collect(root: &Path, manifest: &StoffelConfig) -> Result<Self, String> {
+        let name = manifest.package.name.trim();
+        let version = manifest.package.version.trim();
+        if name.is_empty() || version.is_empty() {
+            return Err(format!(
+                "{} must set both package.name and package.version to be packaged",
+                config::MANIFEST_NAME
+            ));
+        }
+
+        let mut files = vec![PathBuf::from(config::MANIFEST_NAME)];
+        collect_dir(root, Path::new("src"), &mut files)?;
+        for extra in EXTRA_FILES {
+            if root.join(extra).is_file() {
+                files.push(PathBuf::from(extra));
+            }
+        }
+
+        Ok(Package { id: format!("{}-{}", name, version), files })
+    }
+
+    /// File name of the packaged archive
+    pub fn archive_name(&self) -> String {
+        format!("{}.tar.gz", self.id)
+    }
+
+    /// Build the gzipped tarball in memory
+    pub fn archive(&self, root: &Path) -> Result<Vec<u8>, String> {
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for file in &self.files {
+            archive
+                .append_path_with_name(root.join(file), Path::new(&self.id).join(file))
+                .map_err(|e| format!("Failed to archive {}: {}", file.display(), e))?;
+        }
+
+        archive
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(|e| format!("Failed to write archive: {}", e))
+    }
+}
+
+/// SPDX identifiers accepted for `package.license`
+const RECOGNIZED_LICENSES: &[&str] = &[
+    "MIT",
+    "Apache-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "0BSD",
+    "ISC",
+    "MPL-2.0",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "BSL-1.0",
+    "CC0-1.0",
+    "Unlicense",
+    "Zlib",
+];
+
+/// Check that the package metadata is complete enough to publish
+///
+/// Every problem is returned at once so they can all be fixed in one go.
+pub fn validate_for_publish(manifest: &StoffelConfig) -> Result<(), Vec<String>> {
+    let package = &manifest.package;
+    let mut problems = Vec::new();
+
+    if package.name.trim().is_empty() {
+        problems.push("package.name is empty".to_string());
+    }
+    if !is_semver(&package.version) {
+        problems.push(format!(
+            "package.version '{}' is not a valid semantic version (e.g. 1.2.3)",
+            package.version
+        ));
+    }
+    if package.description.as_deref().is_none_or(|description| description.trim().is_empty()) {
+        problems.push("package.description is missing".to_string());
+    }
+    if package
+        .authors
+        .as_deref()
+        .is_none_or(|authors| authors.iter().all(|author| author.trim().is_empty()))
+    {
+        problems.push("package.authors must list at least one author".to_string());
+    }
+    match package.license.as_deref() {
+        None => problems.push("package.license is missing".to_string()),
+        Some(license) if !is_recognized_license(license) => problems.push(format!(
+            "package.license '{}' is not a recognized SPDX license (e.g. MIT, Apache-2.0)",
+            license
+        )),
+        Some(_) => {}
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Whether a license, or every license in an `OR`/`AND` expression, is recognized
+fn is_recognized_license(license: &str) -> bool {
+    let terms: Vec<&str> = license
+        .split(['/', '(', ')'])
+        .flat_map(|part| part.split_whitespace())
+        .filter(|term| !matches!(*term, "OR" | "AND"))
+        .collect();
+    !terms.is_empty()
+        && terms
+            .iter()
+            .all(|term| RECOGNIZED_LICENSES.iter().any(|known| known.eq_ignore_ascii_case(term)))
+}
+
+/// Whether `version` is MAJOR.MINOR.PATCH with optional pre-release and build parts
+fn is_semver(version: &str) -> bool {
+    let (version, build) = match version.split_once('+') {
+        Some((version, build)) => (version, Some(build)),
+        None => (version, None),
+    };
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+
+    let numbers: Vec<&str> = core.split('.').collect();
+    let numeric = |part: &&str| {
+        !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) && (part.len() == 1 || !part.starts_with('0'))
+    };
+    let identifiers = |text: &str| {
+        text.split('.')
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'))
+    };
+
+    numbers.len() == 3
+        && numbers.iter().all(numeric)
+        && pre.is_none_or(identifiers)
+        && build.is_none_or(identifiers)
+}
+
+/// Recursively add the files under `dir` (relative to `root`) in a stable order
+fn collect_dir(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let full = root.join(dir);
+    if !full.is_dir() {
+        return Ok(());
+    }
+
+    let mut entries: Vec<_> = fs::read_dir(&full)
+        .map_err(|e| format!("Failed to read {}: {}", full.display(), e))?
+        .filter_map(|entry| entry.ok())
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let relative = dir.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .map_err(|e| format!("Failed to read {}: {}", relative.display(), e))?;
+        if file_type.is_dir() {
+            if !SKIPPED_DIRS.iter().any(|skipped| entry.file_name() == *skipped) {
+                collect_dir(root, &relative, files)?;
+            }
+        } else if file_type.is_file() {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_sources_and_metadata_but_not_build_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in ["Stoffel.toml", "README.md", "src/main.stfl", "src/lib/util.stfl", "src/.git/HEAD", "target/debug/main.bin"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "x").unwrap();
+        }
+        let manifest: StoffelConfig = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n",
+        )
+        .unwrap();
+
+        let package = Package::collect(root, &manifest).unwrap();
+        assert_eq!(package.archive_name(), "demo-0.1.0.tar.gz");
+        assert_eq!(
+            package.files,
+            ["Stoffel.toml", "src/lib/util.stfl", "src/main.stfl", "README.md"].map(PathBuf::from)
+        );
+        assert!(!package.archive(root).unwrap().is_empty());
+    }
+
+    #[test]
+    fn publish_validation_reports_every_problem() {
+        let manifest: StoffelConfig = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"1.0\"\nlicense = \"Proprietary\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n",
+        )
+        .unwrap();
+
+        let problems = validate_for_publish(&manifest).unwrap_err();
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert!(problems[0].contains("package.version '1.0'"));
+        assert!(problems[1].contains("description"));
+        assert!(problems[2].contains("authors"));
+        assert!(problems[3].contains("'Proprietary'"));
+    }
+
+    #[test]
+    fn complete_metadata_is_publishable() {
+        let manifest: StoffelConfig = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0-rc.1+build.5\"\ndescription = \"Demo\"\nauthors = [\"Ada\"]\nlicense = \"MIT OR Apache-2.0\"\n\n[mpc]\nprotocol = \"honeybadger\"\nparties = 5\nfield = \"bls12-381\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(validate_for_publish(&manifest), Ok(()));
+        assert!(!is_semver("01.2.3"));
+    }
+}
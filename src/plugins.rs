@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Manifest every plugin must ship at its root
+pub const PLUGIN_MANIFEST_NAME: &str = "stoffel-plugin.toml";
+
+/// Index of installed plugins inside the plugins directory
+pub const INDEX_NAME: &str = "plugins.toml";
+
+/// Directories left out when installing a plugin from a local path
+const SKIPPED_DIRS: &[&str] = &[".git", "target"];
+
+/// Directory installed plugins live in (`~/.stoffel/plugins`)
+pub fn plugins_dir() -> Option<PathBuf> {
+    crate::home::stoffel_home().map(|home| home.join("plugins"))
+}
+
+/// The `[plugin]` table of a plugin's manifest
+#[derive(Deserialize, Debug)]
+struct PluginManifest {
+    plugin: PluginMetadata,
+}
+
+#[derive(Deserialize, Debug)]
+struct PluginMetadata {
+    name: String,
+    version: String,
+}
+
+/// Installed plugins recorded in plugins.toml
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PluginIndex {
+    #[serde(default, rename = "plugin")]
+    pub plugins: Vec<InstalledPlugin>,
+}
+
+/// A single installed plugin
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstalledPlugin {
+    pub name: String,
+    pub version: String,
+    /// Local path or git URL the plugin was installed from
+    pub source: String,
+}
+
+impl PluginIndex {
+    /// Load the index of `dir`; a missing index means nothing is installed
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(INDEX_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(INDEX_NAME);
+        let content = toml::to_string(self)
+            .map_err(|e| format!("Failed to serialize plugin index: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Record a plugin, replacing any earlier install of the same name
+    fn record(&mut self, plugin: InstalledPlugin) {
+        self.plugins.retain(|installed| installed.name != plugin.name);
+        self.plugins.push(plugin);
+        self.plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+/// Whether a plugin source should be cloned with git rather than copied
+fn is_git_url(source: &str) -> bool {
+    ["https://", "http://", "ssh://", "git://", "git@"]
+        .iter()
+        .any(|prefix| source.starts_with(prefix))
+        || source.ends_with(".git")
+}
+
+/// Install a plugin from a local path or git URL into `<dir>/<name>/`
+///
+/// The plugin is fetched into a staging directory first, so a source without
+/// a valid manifest never touches an existing install.
+pub fn install(source: &str, dir: &Path) -> Result<InstalledPlugin, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let staging = dir.join(format!(".staging-{}", std::process::id()));
+    if staging.exists() {
+        fs::remove_dir_all(&staging).map_err(|e| format!("Failed to remove {}: {}", staging.display(), e))?;
+    }
+
+    let result = stage(source, &staging).and_then(|metadata| {
+        let target = dir.join(&metadata.name);
+        if target.exists() {
+            fs::remove_dir_all(&target).map_err(|e| format!("Failed to remove {}: {}", target.display(), e))?;
+        }
+        fs::rename(&staging, &target)
+            .map_err(|e| format!("Failed to move plugin into {}: {}", target.display(), e))?;
+        Ok(metadata)
+    });
+    if staging.exists() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    let metadata = result?;
+
+    // Record local sources absolutely so the index doesn't depend on where install ran
+    let source = match fs::canonicalize(source) {
+        Ok(path) if !is_git_url(source) => path.display().to_string(),
+        _ => source.to_string(),
+    };
+    let plugin = InstalledPlugin {
+        name: metadata.name,
+        version: metadata.version,
+        source,
+    };
+    let mut index = PluginIndex::load(dir)?;
+    index.record(plugin.clone());
+    index.save(dir)?;
+    Ok(plugin)
+}
+
+/// Uninstall a plugin, deleting its directory and index entry
+pub fn remove(name: &str, dir: &Path) -> Result<InstalledPlugin, String> {
+    let mut index = PluginIndex::load(dir)?;
+    let position = index
+        .plugins
+        .iter()
+        .position(|plugin| plugin.name == name)
+        .ok_or_else(|| format!("Plugin '{}' is not installed. Run `stoffel plugin list` to see installed plugins.", name))?;
+
+    let plugin_dir = dir.join(name);
+    if plugin_dir.exists() {
+        fs::remove_dir_all(&plugin_dir)
+            .map_err(|e| format!("Failed to remove {}: {}", plugin_dir.display(), e))?;
+    }
+    let plugin = index.plugins.remove(position);
+    index.save(dir)?;
+    Ok(plugin)
+}
+
+/// Fetch a plugin into `staging` and read its manifest
+fn stage(source: &str, staging: &Path) -> Result<PluginMetadata, String> {
+    if is_git_url(source) {
+        let output = Command::new("git")
+            .args(["clone", "--quiet", "--depth", "1", source])
+            .arg(staging)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "git clone of {} failed: {}",
+                source,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    } else {
+        let path = Path::new(source);
+        if !path.is_dir() {
+            return Err(format!("Plugin source {} is not a directory or git URL", source));
+        }
+        copy_dir(path, staging)?;
+    }
+
+    let manifest_path = staging.join(PLUGIN_MANIFEST_NAME);
+    let content = fs::read_to_string(&manifest_path)
+        .map_err(|_| format!("{} has no {}; it is not a Stoffel plugin", source, PLUGIN_MANIFEST_NAME))?;
+    let manifest: PluginManifest = toml::from_str(&content)
+        .map_err(|e| format!("Invalid {} in {}: {}", PLUGIN_MANIFEST_NAME, source, e))?;
+
+    let name = &manifest.plugin.name;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!(
+            "Invalid plugin name '{}' in {}: use letters, digits, '-' and '_'",
+            name, PLUGIN_MANIFEST_NAME
+        ));
+    }
+    Ok(manifest.plugin)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    let entries = fs::read_dir(from).map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let target = to.join(entry.file_name());
+        if path.is_dir() {
+            if !SKIPPED_DIRS.iter().any(|skipped| entry.file_name() == *skipped) {
+                copy_dir(&path, &target)?;
+            }
+        } else {
+            fs::copy(&path, &target).map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installs_a_local_plugin_and_records_it() {
+        let source = tempfile::tempdir().unwrap();
+        let plugins = tempfile::tempdir().unwrap();
+        fs::write(
+            source.path().join(PLUGIN_MANIFEST_NAME),
+            "[plugin]\nname = \"lint\"\nversion = \"0.2.0\"\n",
+        )
+        .unwrap();
+        fs::write(source.path().join("lint.sh"), "echo lint").unwrap();
+
+        let source_path = source.path().to_str().unwrap();
+        let plugin = install(source_path, plugins.path()).unwrap();
+        assert_eq!(plugin.name, "lint");
+        assert!(plugins.path().join("lint/lint.sh").exists());
+
+        // Reinstalling replaces the index entry instead of duplicating it
+        install(source_path, plugins.path()).unwrap();
+        assert_eq!(PluginIndex::load(plugins.path()).unwrap().plugins, vec![plugin]);
+    }
+
+    #[test]
+    fn removing_deletes_directory_and_index_entry() {
+        let plugins = tempfile::tempdir().unwrap();
+        assert!(remove("lint", plugins.path()).unwrap_err().contains("'lint' is not installed"));
+
+        fs::create_dir(plugins.path().join("lint")).unwrap();
+        let mut index = PluginIndex::default();
+        index.record(InstalledPlugin {
+            name: "lint".to_string(),
+            version: "0.2.0".to_string(),
+            source: "/src/lint".to_string(),
+        });
+        index.save(plugins.path()).unwrap();
+
+        assert_eq!(remove("lint", plugins.path()).unwrap().version, "0.2.0");
+        assert!(!plugins.path().join("lint").exists());
+        assert!(PluginIndex::load(plugins.path()).unwrap().plugins.is_empty());
+    }
+
+    #[test]
+    fn sources_without_a_manifest_are_rejected() {
+        let source = tempfile::tempdir().unwrap();
+        let plugins = tempfile::tempdir().unwrap();
+
+        let error = install(source.path().to_str().unwrap(), plugins.path()).unwrap_err();
+        assert!(error.contains(PLUGIN_MANIFEST_NAME));
+        assert_eq!(fs::read_dir(plugins.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,348 @@
+use std::fs::{self, File};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::Duration;
+
+use crate::{validate_mpc_params, value_name, MpcField, MpcProtocol, VmOptLevel};
+
+/// Directory under target/ holding per-party logs of the last simulation
+pub const LOG_DIR: &str = "sim";
+
+/// Conventional name for a run's coverage tracefile inside its log directory
+pub const COVERAGE_FILE: &str = "coverage.info";
+
+/// A fresh seed for runs that weren't given one
+pub fn random_seed() -> u64 {
+    use std::hash::BuildHasher;
+    std::collections::hash_map::RandomState::new().hash_one(std::time::SystemTime::now())
+}
+
+/// How often running parties are polled for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A local run of every party of an MPC program, one VM process per party
+pub struct Simulation<'a> {
+    pub vm_path: &'a Path,
+    pub program: &'a Path,
+    pub protocol: MpcProtocol,
+    pub field: MpcField,
+    pub threshold: u8,
+    pub vm_opt: VmOptLevel,
+    /// Listen address of each party, indexed by party
+    pub addresses: &'a [SocketAddr],
+    /// Inputs file and the index of the party that supplies it
+    pub inputs: Option<(&'a Path, u8)>,
+    /// Step through party 0 interactively, on the terminal instead of its logs
+    pub step: bool,
+    /// Seed for the VM's randomness, so a run can be replayed exactly
+    pub seed: u64,
+    pub args: &'a [String],
+    pub log_dir: PathBuf,
+    /// Where party 0 writes an lcov tracefile of the procs and lines it ran
+    ///
+    /// Every party runs the same program in lockstep, so one party's counts
+    /// stand for the whole run. The program must be compiled with coverage.
+    pub coverage: Option<PathBuf>,
+}
+
+/// Messages and bytes a party sent during a run
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct Traffic {
+    pub messages: u64,
+    pub bytes: u64,
+}
+
+struct Party {
+    index: usize,
+    child: Child,
+    status: Option<ExitStatus>,
+    stdout_log: PathBuf,
+    stderr_log: PathBuf,
+    /// Stepped on the terminal, so nothing of its output is in the logs
+    stepped: bool,
+}
+
+/// The party processes of a started simulation
+pub struct Network {
+    parties: Vec<Party>,
+}
+
+impl Simulation<'_> {
+    /// Launch every party, wait for all of them, and return their agreed output
+    ///
+    /// If any party fails the others are killed. Each party's stdout and
+    /// stderr go to log files under `log_dir`, which are echoed at debug level.
+    pub fn run(&self) -> Result<String, String> {
+        let mut network = self.start()?;
+        loop {
+            if let Some(result) = network.poll() {
+                return result;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Launch every party without waiting for them
+    pub fn start(&self) -> Result<Network, String> {
+        let parties = u8::try_from(self.addresses.len())
+            .map_err(|_| format!("Too many parties: {}", self.addresses.len()))?;
+        validate_mpc_params(parties, self.threshold, &self.protocol)?;
+
+        fs::create_dir_all(&self.log_dir)
+            .map_err(|e| format!("Failed to create {}: {}", self.log_dir.display(), e))?;
+        // A tracefile from an earlier run must not pass for this one's
+        if let Some(coverage) = &self.coverage {
+            if coverage.exists() {
+                fs::remove_file(coverage)
+                    .map_err(|e| format!("Failed to remove {}: {}", coverage.display(), e))?;
+            }
+        }
+
+        let mut running = Vec::new();
+        for index in 0..self.addresses.len() {
+            match self.spawn(index) {
+                Ok(party) => running.push(party),
+                Err(e) => {
+                    kill_all(&mut running);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(Network { parties: running })
+    }
+
+    /// Stderr of every party from the last run, each under a header
+    pub fn stderr_logs(&self) -> String {
+        let mut logs = String::new();
+        for index in 0..self.addresses.len() {
+            let path = self.log_dir.join(format!("party-{}.err", index));
+            if let Ok(content) = fs::read_to_string(&path) {
+                if !content.trim().is_empty() {
+                    logs.push_str(&format!("--- party {} ---\n{}", index, content));
+                }
+            }
+        }
+        logs
+    }
+
+    /// Traffic each party reported in the last run
+    ///
+    /// VMs that count communication print `messages_sent=<n>` and
+    /// `bytes_sent=<n>` on stderr; parties whose log has neither get `None`.
+    pub fn traffic(&self) -> Vec<Option<Traffic>> {
+        (0..self.addresses.len())
+            .map(|index| {
+                let path = self.log_dir.join(format!("party-{}.err", index));
+                fs::read_to_string(path).ok().and_then(|log| parse_traffic(&log))
+            })
+            .collect()
+    }
+
+    fn spawn(&self, index: usize) -> Result<Party, String> {
+        let stdout_log = self.log_dir.join(format!("party-{}.out", index));
+        let stderr_log = self.log_dir.join(format!("party-{}.err", index));
+        let create = |path: &Path| {
+            File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))
+        };
+
+        let mut command = Command::new(self.vm_path);
+        command
+            .arg(self.program)
+            .args(["--party-id", &index.to_string()])
+            .args(["--parties", &self.addresses.len().to_string()])
+            .args(["--threshold", &self.threshold.to_string()])
+            .args(["--protocol", &value_name(&self.protocol)])
+            .args(["--field", &value_name(&self.field)])
+            .args(["--opt-level", &value_name(&self.vm_opt)])
+            .args(["--seed", &self.seed.to_string()]);
+        for (peer, address) in self.addresses.iter().enumerate() {
+            command.arg("--party").arg(format!("{}={}", peer, address));
+        }
+        if let Some(coverage) = self.coverage.as_ref().filter(|_| index == 0) {
+            command.arg("--coverage-out").arg(coverage);
+        }
+        if let Some((inputs, owner)) = self.inputs {
+            if usize::from(owner) == index {
+                command.arg("--inputs").arg(inputs);
+            }
+        }
+
+        // Only party 0 can be stepped; the rest must not compete for the terminal
+        let stepped = self.step && index == 0;
+        if stepped {
+            // Logs from an earlier run must not pass for this one's
+            for log in [&stdout_log, &stderr_log] {
+                if log.exists() {
+                    fs::remove_file(log).map_err(|e| format!("Failed to remove {}: {}", log.display(), e))?;
+                }
+            }
+            command.arg("--step").stdin(Stdio::inherit()).stdout(Stdio::inherit()).stderr(Stdio::inherit());
+        } else {
+            command.stdin(Stdio::null()).stdout(create(&stdout_log)?).stderr(create(&stderr_log)?);
+        }
+        if !self.args.is_empty() {
+            command.arg("--").args(self.args);
+        }
+
+        let child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start party {} ({}): {}", index, self.vm_path.display(), e))?;
+
+        Ok(Party {
+            index,
+            child,
+            status: None,
+            stdout_log,
+            stderr_log,
+            stepped,
+        })
+    }
+}
+
+impl Network {
+    /// Check on the parties without blocking
+    ///
+    /// Returns `None` while any party is still running. Once all have exited
+    /// it returns their agreed output; as soon as one fails the rest are
+    /// killed and the failure returned. Logs are echoed at debug level.
+    pub fn poll(&mut self) -> Option<Result<String, String>> {
+        let result = match poll_all(&mut self.parties) {
+            Ok(true) => agreed_output(&self.parties),
+            Ok(false) => return None,
+            Err(e) => Err(e),
+        };
+        if log::log_enabled!(log::Level::Debug) {
+            for party in &self.parties {
+                print_logs(party);
+            }
+        }
+        Some(result)
+    }
+
+    /// Kill every party that is still running
+    pub fn stop(&mut self) {
+        kill_all(&mut self.parties);
+    }
+}
+
+impl Drop for Network {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Record parties that have exited, killing the rest as soon as one fails
+///
+/// Returns whether every party has finished.
+fn poll_all(parties: &mut [Party]) -> Result<bool, String> {
+    let mut pending = false;
+    for index in 0..parties.len() {
+        let party = &mut parties[index];
+        if party.status.is_some() {
+            continue;
+        }
+
+        match party.child.try_wait() {
+            Ok(Some(status)) => {
+                party.status = Some(status);
+                if !status.success() {
+                    let message = if party.stepped {
+                        format!("Party {} exited with {}", party.index, status)
+                    } else {
+                        format!(
+                            "Party {} exited with {} (log: {})",
+                            party.index,
+                            status,
+                            party.stderr_log.display()
+                        )
+                    };
+                    kill_all(parties);
+                    return Err(message);
+                }
+            }
+            Ok(None) => pending = true,
+            Err(e) => {
+                let message = format!("Failed to wait for party {}: {}", party.index, e);
+                kill_all(parties);
+                return Err(message);
+            }
+        }
+    }
+    Ok(!pending)
+}
+
+fn kill_all(parties: &mut [Party]) {
+    for party in parties.iter_mut().filter(|party| party.status.is_none()) {
+        let _ = party.child.kill();
+        party.status = party.child.wait().ok();
+    }
+}
+
+/// The output every party printed, which must be identical
+///
+/// A stepped party printed to the terminal, so only the others are compared.
+fn agreed_output(parties: &[Party]) -> Result<String, String> {
+    let mut outputs = Vec::new();
+    for party in parties.iter().filter(|party| !party.stepped) {
+        let output = fs::read_to_string(&party.stdout_log)
+            .map_err(|e| format!("Failed to read {}: {}", party.stdout_log.display(), e))?;
+        outputs.push((party.index, output));
+    }
+
+    let Some((first_index, first)) = outputs.first() else {
+        return Ok(String::new());
+    };
+    if let Some((index, output)) = outputs.iter().find(|(_, output)| output != first) {
+        return Err(format!(
+            "Parties disagree on the result: party {} printed {:?}, party {} printed {:?}",
+            first_index,
+            first.trim_end(),
+            index,
+            output.trim_end()
+        ));
+    }
+    Ok(first.clone())
+}
+
+/// The last `messages_sent=` and `bytes_sent=` counts in a party's stderr
+fn parse_traffic(log: &str) -> Option<Traffic> {
+    let (mut messages, mut bytes) = (None, None);
+    for (key, value) in log.split_whitespace().filter_map(|token| token.split_once('=')) {
+        match key {
+            "messages_sent" => messages = value.parse().ok().or(messages),
+            "bytes_sent" => bytes = value.parse().ok().or(bytes),
+            _ => {}
+        }
+    }
+    if messages.is_none() && bytes.is_none() {
+        return None;
+    }
+    Some(Traffic { messages: messages.unwrap_or(0), bytes: bytes.unwrap_or(0) })
+}
+
+fn print_logs(party: &Party) {
+    let status = party
+        .status
+        .map_or_else(|| "killed".to_string(), |status| status.to_string());
+    log::debug!("📜 Party {} ({}):", party.index, status);
+    for log in [&party.stdout_log, &party.stderr_log] {
+        if let Ok(content) = fs::read_to_string(log) {
+            for line in content.lines() {
+                log::debug!("   {}", line);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traffic_is_read_from_the_last_reported_counts() {
+        let log = "args: --party-id 0\nmessages_sent=12 bytes_sent=512\nmessages_sent=40 bytes_sent=2048\n";
+        assert_eq!(parse_traffic(log), Some(Traffic { messages: 40, bytes: 2048 }));
+        assert_eq!(parse_traffic("args: --seed=7\n"), None);
+    }
+}
@@ -0,0 +1,170 @@
+use glob::{MatchOptions, Pattern};
+use std::path::Path;
+
+/// Gitignore-style list of paths that source discovery skips
+pub const IGNORE_FILE: &str = ".stoffelignore";
+
+/// Include/exclude globs that narrow the set of discovered source files
+///
+/// Patterns are matched against a file's path relative to the directory it
+/// was found in (`lib/math.stfl`) as well as the path as listed
+/// (`src/lib/math.stfl`), so either spelling works.
+#[derive(Debug, Default)]
+pub struct SourceFilter {
+    root: String,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl SourceFilter {
+    pub fn new(root: &str, include: Vec<Pattern>, exclude: Vec<Pattern>) -> Self {
+        SourceFilter {
+            root: root.to_string(),
+            include,
+            exclude,
+        }
+    }
+
+    /// Whether `file` should be compiled
+    pub fn matches(&self, file: &str) -> bool {
+        let path = Path::new(file);
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let hit = |pattern: &Pattern| pattern.matches_path(path) || pattern.matches_path(relative);
+
+        (self.include.is_empty() || self.include.iter().any(hit)) && !self.exclude.iter().any(hit)
+    }
+
+    /// Keep only the files that match, preserving order
+    pub fn apply(&self, files: Vec<String>) -> Vec<String> {
+        files.into_iter().filter(|file| self.matches(file)).collect()
+    }
+}
+
+/// The rules of a `.stoffelignore`
+///
+/// Supports the common gitignore syntax: `#` comments, `!` to re-include, a
+/// trailing `/` to match only directories, and a leading or inner `/` to
+/// anchor a pattern to the project root. Unanchored patterns match a file or
+/// directory name at any depth. Later rules override earlier ones.
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug)]
+struct IgnoreRule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl IgnoreRules {
+    /// Read the `.stoffelignore` in `root`; without one nothing is ignored
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let path = root.join(IGNORE_FILE);
+        if !path.is_file() {
+            return Ok(IgnoreRules::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&content)
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let anchored = line.contains('/');
+            let pattern = Pattern::new(line.trim_start_matches('/'))
+                .map_err(|e| format!("{} line {}: invalid pattern '{}': {}", IGNORE_FILE, number + 1, line, e))?;
+            rules.push(IgnoreRule { pattern, negated, dir_only, anchored });
+        }
+        Ok(IgnoreRules { rules })
+    }
+
+    /// Whether `path`, relative to the project root, is ignored
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let options = MatchOptions { require_literal_separator: true, ..Default::default() };
+        let name = path.file_name().map(Path::new).unwrap_or(path);
+
+        let mut ignored = false;
+        for rule in self.rules.iter().filter(|rule| is_dir || !rule.dir_only) {
+            let candidate = if rule.anchored { path } else { name };
+            if rule.pattern.matches_path_with(candidate, options) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Parse a `--include`/`--exclude` glob
+pub fn parse_glob(pattern: &str) -> Result<Pattern, String> {
+    Pattern::new(pattern).map_err(|e| format!("invalid glob '{}': {}", pattern, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<String> {
+        ["src/main.stfl", "src/lib/math.stfl", "src/gen/out.stfl", "src/gen/nested/more.stfl"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn globs(patterns: &[&str]) -> Vec<Pattern> {
+        patterns.iter().map(|p| parse_glob(p).unwrap()).collect()
+    }
+
+    #[test]
+    fn excluded_files_are_dropped() {
+        let filter = SourceFilter::new("src", Vec::new(), globs(&["gen/**"]));
+        assert_eq!(filter.apply(files()), ["src/main.stfl", "src/lib/math.stfl"]);
+
+        let filter = SourceFilter::new("src", Vec::new(), globs(&["src/lib/*.stfl", "**/more.stfl"]));
+        assert_eq!(filter.apply(files()), ["src/main.stfl", "src/gen/out.stfl"]);
+    }
+
+    #[test]
+    fn include_restricts_before_exclude() {
+        let filter = SourceFilter::new("src", globs(&["gen/**"]), globs(&["**/nested/*"]));
+        assert_eq!(filter.apply(files()), ["src/gen/out.stfl"]);
+    }
+
+    #[test]
+    fn ignore_file_follows_gitignore_rules() {
+        let rules = IgnoreRules::parse("# generated code\ngen/\n/src/vendor\n*.draft.stfl\n!keep.draft.stfl\nsrc/lib/*.stfl\n").unwrap();
+        let ignored = |path: &str, is_dir| rules.is_ignored(Path::new(path), is_dir);
+
+        assert!(ignored("src/gen", true));
+        assert!(ignored("src/nested/gen", true));
+        assert!(!ignored("src/gen", false));
+        assert!(ignored("src/vendor", true));
+        assert!(!ignored("lib/src/vendor", true));
+        assert!(ignored("src/x.draft.stfl", false));
+        assert!(!ignored("src/keep.draft.stfl", false));
+        assert!(ignored("src/lib/math.stfl", false));
+        assert!(!ignored("src/lib/deep/math.stfl", false));
+        assert!(!ignored("src/main.stfl", false));
+
+        assert!(IgnoreRules::parse("ok\nsrc/[\n").unwrap_err().contains("line 2"));
+    }
+
+    #[test]
+    fn invalid_glob_is_rejected() {
+        assert!(parse_glob("src/[").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use clap::ValueEnum;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// When to color terminal output
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR isn't set
+    Auto,
+    /// Always color, even when piped
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Markers that start status lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Success,
+    Failure,
+    Warning,
+    /// Nothing to do, or a note that isn't a problem
+    Info,
+    /// A whole command finished cleanly
+    Done,
+    /// Work left out on purpose, e.g. up-to-date or kept files
+    Skipped,
+    /// A batch cut short before every item ran
+    Stopped,
+    /// Packages, artifacts and other things the project ships
+    Package,
+}
+
+/// Decide once whether output is styled; call before printing anything
+pub fn init(choice: ColorChoice) {
+    let terminal = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    ENABLED.store(should_style(choice, terminal), Ordering::Relaxed);
+}
+
+fn should_style(choice: ColorChoice, terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Auto => terminal,
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    }
+}
+
+/// Marker for a status line, colored only when styling is enabled
+pub fn prefix(status: Status) -> String {
+    render(status, ENABLED.load(Ordering::Relaxed))
+}
+
+fn render(status: Status, styled: bool) -> String {
+    let (marker, color) = match status {
+        Status::Success => ("✅", "32"),
+        Status::Failure => ("❌", "31"),
+        Status::Warning => ("⚠️", "33"),
+        Status::Info => ("ℹ️", "34"),
+        Status::Done => ("🎉", "32"),
+        Status::Skipped => ("⏭️", "36"),
+        Status::Stopped => ("⏹️", "31"),
+        Status::Package => ("📦", "35"),
+    };
+    if styled {
+        format!("\x1b[{}m{}\x1b[0m", color, marker)
+    } else {
+        marker.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_markers_carry_no_escape_codes() {
+        assert!(!should_style(ColorChoice::Auto, false));
+        assert!(should_style(ColorChoice::Always, false));
+        assert!(!should_style(ColorChoice::Never, true));
+
+        assert_eq!(render(Status::Failure, false), "❌");
+        assert_eq!(render(Status::Success, true), "\x1b[32m✅\x1b[0m");
+        assert_eq!(render(Status::Info, false), "ℹ️");
+    }
+
+    #[test]
+    fn console_output_has_no_mis_encoded_emoji() {
+        // What UTF-8 emoji such as 🚀 and ✅ turn into when re-saved as Windows-1252
+        let mangled = ["ðŸ", "âœ", "âš", "â\u{9d}"];
+        for (name, source) in [("init.rs", include_str!("init.rs")), ("main.rs", include_str!("main.rs"))] {
+            for pattern in mangled {
+                assert!(!source.contains(pattern), "{} contains mis-encoded text {:?}", name, pattern);
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+// {{package_name}} - {{package_description}}
+// Generated by Stoffel CLI
+//
+// Go client integration with the Stoffel MPC network
+// Protocol: {{mpc_protocol}}, Parties: {{mpc_parties}}, Field: {{mpc_field}}
+package main
+
+import (
+	"fmt"
+	"os"
+)
+
+// MPCConfig mirrors the [mpc] section of Stoffel.toml
+type MPCConfig struct {
+	Protocol  string
+	Parties   int
+	Threshold int
+	Field     string
+}
+
+var config = MPCConfig{
+	Protocol:  "{{mpc_protocol}}",
+	Parties:   {{mpc_parties}},
+	Threshold: {{mpc_threshold}},
+	Field:     "{{mpc_field}}",
+}
+
+// program is the StoffelLang source compiled with `stoffel compile`
+const program = "src/secure_computation.stfl"
+
+func main() {
+	fmt.Println("=== Stoffel Go MPC Demo ===")
+	fmt.Printf("Protocol: %s\n", config.Protocol)
+	fmt.Printf("Parties: %d (threshold %d)\n", config.Parties, config.Threshold)
+	fmt.Printf("Field: %s\n", config.Field)
+
+	if _, err := os.Stat(program); err != nil {
+		fmt.Fprintf(os.Stderr, "missing %s: %v\n", program, err)
+		os.Exit(1)
+	}
+
+	// TODO: Connect to the MPC network once the Stoffel Go SDK is available
+	fmt.Println("Go client integration with StoffelVM coming soon!")
+}
@@ -0,0 +1,36 @@
+/*
+ * {{package_name}} - {{package_description}}
+ * Generated by Stoffel CLI
+ *
+ * Java client integration with the Stoffel MPC network
+ * Protocol: {{mpc_protocol}}, Parties: {{mpc_parties}}, Field: {{mpc_field}}
+ */
+package {{java_package}};
+
+import java.nio.file.Files;
+import java.nio.file.Path;
+
+public class Main {
+    /** Mirrors the [mpc] section of Stoffel.toml */
+    record MpcConfig(String protocol, int parties, int threshold, String field) {}
+
+    static final MpcConfig CONFIG = new MpcConfig("{{mpc_protocol}}", {{mpc_parties}}, {{mpc_threshold}}, "{{mpc_field}}");
+
+    /** StoffelLang source compiled with `stoffel compile` */
+    static final Path PROGRAM = Path.of("src/secure_computation.stfl");
+
+    public static void main(String[] args) {
+        System.out.println("=== Stoffel Java MPC Demo ===");
+        System.out.println("Protocol: " + CONFIG.protocol());
+        System.out.println("Parties: " + CONFIG.parties() + " (threshold " + CONFIG.threshold() + ")");
+        System.out.println("Field: " + CONFIG.field());
+
+        if (!Files.exists(PROGRAM)) {
+            System.err.println("missing " + PROGRAM);
+            System.exit(1);
+        }
+
+        // TODO: Connect to the MPC network once the Stoffel Java SDK is available
+        System.out.println("Java client integration with StoffelVM coming soon!");
+    }
+}
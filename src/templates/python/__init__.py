@@ -0,0 +1,20 @@
+"""
+{{package_name}} - {{package_description}}
+Generated by Stoffel CLI
+
+Python package wrapping the {{package_name}} StoffelLang library
+Protocol: {{mpc_protocol}}, Parties: {{mpc_parties}}, Field: {{mpc_field}}
+"""
+
+from pathlib import Path
+
+# StoffelLang source of the library's MPC procedures
+SOURCE_PATH = Path(__file__).resolve().parent.parent / "lib.stfl"
+
+# MPC settings the library was generated for
+PARTIES = {{mpc_parties}}
+THRESHOLD = {{mpc_threshold}}
+
+# TODO: Compile SOURCE_PATH with StoffelProgram once the SDK exposes libraries
+
+__all__ = ["SOURCE_PATH", "PARTIES", "THRESHOLD"]
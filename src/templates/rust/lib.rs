@@ -0,0 +1,39 @@
+//! {{package_name}} - {{package_description}}
+//! Generated by Stoffel CLI
+//!
+//! Rust bindings for the {{package_name}} StoffelLang library
+//! Protocol: {{mpc_protocol}}, Parties: {{mpc_parties}}, Field: {{mpc_field}}
+
+/// StoffelLang source of the library's MPC procedures
+pub const SOURCE: &str = include_str!("lib.stfl");
+
+/// Number of parties the library is configured for
+pub const PARTIES: u32 = {{mpc_parties}};
+
+/// Maximum number of corrupted parties tolerated
+pub const THRESHOLD: u32 = {{mpc_threshold}};
+
+/// Number of parties, for callers loading the library over FFI
+#[no_mangle]
+pub extern "C" fn {{package_name_underscore}}_parties() -> u32 {
+    PARTIES
+}
+
+/// Maximum number of corrupted parties, for callers loading the library over FFI
+#[no_mangle]
+pub extern "C" fn {{package_name_underscore}}_threshold() -> u32 {
+    THRESHOLD
+}
+
+// TODO: Expose secure_add through StoffelVM once its crates are available
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_is_embedded() {
+        assert!(SOURCE.contains("secure_add"));
+        assert!(THRESHOLD < PARTIES);
+    }
+}
@@ -0,0 +1,35 @@
+//! {{package_name}} - {{package_description}}
+//! Generated by Stoffel CLI
+//!
+//! Rust FFI integration with StoffelVM for MPC computation
+//! Protocol: {{mpc_protocol}}, Parties: {{mpc_parties}}, Field: {{mpc_field}}
+
+// TODO: Uncomment when StoffelVM crates are available
+// use stoffel_vm::core_vm::VirtualMachine;
+// use stoffel_vm::functions::VMFunction;
+// use stoffel_vm::instructions::Instruction;
+// use stoffel_vm::core_types::Value;
+use std::collections::HashMap;
+
+/// Main MPC computation using Rust FFI to StoffelVM
+fn main() -> Result<(), String> {
+    println!("=== Stoffel Rust MPC Demo ===");
+    println!("Protocol: {{mpc_protocol}}");
+    println!("Parties: {{mpc_parties}} (threshold {{mpc_threshold}})");
+    println!("Field: {{mpc_field}}");
+
+    // TODO: Implement StoffelVM integration
+    println!("Rust FFI integration with StoffelVM coming soon!");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        assert!(main().is_ok());
+    }
+}
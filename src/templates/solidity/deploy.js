@@ -0,0 +1,25 @@
+// Deploy script for Stoffel MPC contracts
+const hre = require("hardhat");
+
+async function main() {
+  console.log("Deploying Stoffel MPC contracts...");
+
+  const StoffelMPC = await hre.ethers.getContractFactory("StoffelMPC");
+  const stoffelMPC = await StoffelMPC.deploy();
+
+  await stoffelMPC.deployed();
+  console.log("StoffelMPC deployed to:", stoffelMPC.address);
+
+  const PrivateAuction = await hre.ethers.getContractFactory("PrivateAuction");
+  const privateAuction = await PrivateAuction.deploy();
+
+  await privateAuction.deployed();
+  console.log("PrivateAuction deployed to:", privateAuction.address);
+}
+
+main()
+  .then(() => process.exit(0))
+  .catch((error) => {
+    console.error(error);
+    process.exit(1);
+  });
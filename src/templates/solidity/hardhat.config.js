@@ -0,0 +1,14 @@
+require("@nomicfoundation/hardhat-toolbox");
+
+/** @type import('hardhat/config').HardhatUserConfig */
+module.exports = {
+  solidity: "0.8.20",
+  networks: {
+    hardhat: {},
+    // Add Stoffel MPC network configuration here
+    stoffel: {
+      url: "http://localhost:8545",
+      accounts: []
+    }
+  }
+};
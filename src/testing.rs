@@ -0,0 +1,77 @@
+use std::path::Path;
+
+/// Directory holding a project's .stfl tests
+pub const TESTS_DIR: &str = "tests";
+
+/// Whether a test file is an integration test
+///
+/// Integration tests are files named `integration*.stfl` or anything under
+/// an `integration/` directory.
+pub fn is_integration(file: &str) -> bool {
+    let path = Path::new(file);
+    let named = path
+        .file_stem()
+        .is_some_and(|stem| stem.to_string_lossy().starts_with("integration"));
+    let nested = path
+        .parent()
+        .is_some_and(|parent| parent.components().any(|c| c.as_os_str() == "integration"));
+    named || nested
+}
+
+/// Name of a test: its path below tests/ without the extension
+pub fn test_name(file: &str) -> String {
+    let path = Path::new(file);
+    let relative = path.strip_prefix(TESTS_DIR).unwrap_or(path);
+    relative.with_extension("").to_string_lossy().to_string()
+}
+
+/// Narrow discovered test files to the requested ones
+///
+/// `name` matches either a test's full name (`math/add`) or its file stem
+/// (`add`), with or without the `.stfl` extension.
+pub fn select(files: Vec<String>, name: Option<&str>, integration: bool) -> Vec<String> {
+    let name = name.map(|name| name.strip_suffix(".stfl").unwrap_or(name));
+    files
+        .into_iter()
+        .filter(|file| !integration || is_integration(file))
+        .filter(|file| {
+            name.is_none_or(|name| {
+                let full = test_name(file);
+                full == name || full.rsplit('/').next() == Some(name)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<String> {
+        [
+            "tests/integration.stfl",
+            "tests/integration/payments.stfl",
+            "tests/math/add.stfl",
+            "tests/unit.stfl",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    #[test]
+    fn selects_by_name_or_stem() {
+        assert_eq!(select(files(), Some("add"), false), ["tests/math/add.stfl"]);
+        assert_eq!(select(files(), Some("math/add.stfl"), false), ["tests/math/add.stfl"]);
+        assert!(select(files(), Some("missing"), false).is_empty());
+        assert_eq!(select(files(), None, false).len(), 4);
+    }
+
+    #[test]
+    fn integration_restricts_to_integration_tests() {
+        assert_eq!(
+            select(files(), None, true),
+            ["tests/integration.stfl", "tests/integration/payments.stfl"]
+        );
+        assert!(select(files(), Some("unit"), true).is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Wall-clock timing for a single compiled file
+pub struct FileTiming {
+    pub file: String,
+    /// Offset from the start of the batch when compilation began
+    pub start: Duration,
+    pub duration: Duration,
+    pub success: bool,
+}
+
+/// Fastest, median and slowest of a set of samples
+#[derive(Debug, PartialEq)]
+pub struct Spread {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+/// Spread of `samples`; `None` when there are none
+pub fn spread(samples: &[Duration]) -> Option<Spread> {
+    let mut sorted = samples.to_vec();
+    sorted.sort();
+    let (min, max) = (*sorted.first()?, *sorted.last()?);
+    // The two middle samples are the same one when the count is odd
+    let median = (sorted[(sorted.len() - 1) / 2] + sorted[sorted.len() / 2]) / 2;
+    Some(Spread { min, median, max })
+}
+
+/// Default location of the timings report
+pub const TIMINGS_REPORT_NAME: &str = "stoffel-timings.html";
+
+/// Write an HTML timeline of per-file compilation times
+pub fn write_html_report(path: &Path, timings: &[FileTiming]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    fs::write(path, render_html(timings))
+        .map_err(|e| format!("Failed to write timings report {}: {}", path.display(), e))
+}
+
+fn render_html(timings: &[FileTiming]) -> String {
+    let total = timings
+        .iter()
+        .map(|t| t.start + t.duration)
+        .max()
+        .unwrap_or_default();
+    let total_ms = total.as_secs_f64() * 1000.0;
+
+    let mut rows = String::new();
+    for timing in timings {
+        let start_ms = timing.start.as_secs_f64() * 1000.0;
+        let duration_ms = timing.duration.as_secs_f64() * 1000.0;
+        let (offset, width) = if total_ms > 0.0 {
+            (start_ms / total_ms * 100.0, (duration_ms / total_ms * 100.0).max(0.5))
+        } else {
+            (0.0, 100.0)
+        };
+
+        rows.push_str(&format!(
+            r#"    <tr>
+      <td>{}</td>
+      <td class="num">{:.1}</td>
+      <td class="num">{:.1}</td>
+      <td>{}</td>
+      <td class="timeline"><div class="bar {}" style="margin-left: {:.2}%; width: {:.2}%"></div></td>
+    </tr>
+"#,
+            escape_html(&timing.file),
+            start_ms,
+            duration_ms,
+            if timing.success { "ok" } else { "failed" },
+            if timing.success { "ok" } else { "failed" },
+            offset,
+            width
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>Stoffel Build Timings</title>
+  <style>
+    body {{ font-family: sans-serif; margin: 2em; }}
+    table {{ border-collapse: collapse; width: 100%; }}
+    th, td {{ border-bottom: 1px solid #ddd; padding: 4px 8px; text-align: left; }}
+    td.num {{ text-align: right; }}
+    td.timeline {{ width: 50%; }}
+    .bar {{ height: 14px; }}
+    .bar.ok {{ background: #4a90d9; }}
+    .bar.failed {{ background: #d9534f; }}
+  </style>
+</head>
+<body>
+  <h1>Stoffel Build Timings</h1>
+  <p>{} file(s), {:.1} ms total</p>
+  <table>
+    <tr><th>File</th><th>Start (ms)</th><th>Duration (ms)</th><th>Result</th><th>Timeline</th></tr>
+{}  </table>
+</body>
+</html>
+"#,
+        timings.len(),
+        total_ms,
+        rows
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_of_an_even_count_averages_the_middle_pair() {
+        let ms = Duration::from_millis;
+        assert_eq!(spread(&[ms(30), ms(10), ms(20)]), Some(Spread { min: ms(10), median: ms(20), max: ms(30) }));
+        assert_eq!(spread(&[ms(40), ms(10), ms(20), ms(30)]).unwrap().median, ms(25));
+        assert_eq!(spread(&[]), None);
+    }
+}
@@ -0,0 +1,476 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config;
+
+/// An external executable the CLI shells out to
+struct Tool {
+    /// Human-readable name used in messages
+    label: &'static str,
+    /// Executable file name
+    binary: &'static str,
+    /// Environment variable pointing at the binary or a checkout of its repository
+    env: &'static str,
+    /// Directory name of a checkout beside this repository
+    checkout: &'static str,
+    /// Key in a `[tools]` table that pins the tool, if it can be pinned
+    config_key: Option<&'static str>,
+    /// Whether `stoffel toolchain install` provides the tool
+    managed: bool,
+}
+
+/// A tool path set in a config file rather than found by searching
+struct Pinned {
+    path: PathBuf,
+    /// Where the path was set, for messages
+    origin: String,
+}
+
+/// The Stoffel-Lang compiler
+const COMPILER: Tool = Tool {
+    label: "Stoffel-Lang compiler",
+    binary: "stoffellang",
+    env: "STOFFEL_LANG",
+    checkout: "Stoffel-Lang",
+    config_key: Some("compiler"),
+    managed: true,
+};
+
+/// The StoffelVM runner
+const VM: Tool = Tool {
+    label: "StoffelVM",
+    binary: "stoffel-vm",
+    env: "STOFFEL_VM",
+    checkout: "StoffelVM",
+    config_key: None,
+    managed: false,
+};
+
+/// Locate the Stoffel-Lang compiler
+pub fn find_compiler() -> Result<PathBuf, String> {
+    find(&COMPILER, pinned_compiler()?, active_toolchain()?)
+}
+
+/// Locate the StoffelVM binary
+pub fn find_vm() -> Result<PathBuf, String> {
+    find(&VM, None, None)
+}
+
+/// `[tools] compiler` from the project's Stoffel.toml, its workspace root's, or ~/.stoffel/config.toml
+fn pinned_compiler() -> Result<Option<Pinned>, String> {
+    if let Some(compiler) = config::project_tools(Path::new("."))?.and_then(|tools| tools.compiler) {
+        return Ok(Some(Pinned {
+            path: PathBuf::from(compiler),
+            origin: format!("[tools] compiler in {}", config::MANIFEST_NAME),
+        }));
+    }
+
+    let workspace = config::workspace_tools(Path::new("."))?;
+    if let Some((root, compiler)) = workspace.and_then(|(root, tools)| tools.compiler.map(|compiler| (root, compiler))) {
+        let manifest = root.join(config::MANIFEST_NAME);
+        return Ok(Some(Pinned {
+            path: root.join(compiler),
+            origin: format!("[tools] compiler in {}", manifest.display()),
+        }));
+    }
+
+    let compiler = crate::init::UserDefaults::load()?.tools.and_then(|tools| tools.compiler);
+    let (Some(compiler), Some(path)) = (compiler, crate::init::UserDefaults::path()) else {
+        return Ok(None);
+    };
+    Ok(Some(Pinned {
+        path: path.parent().unwrap_or(Path::new(".")).join(compiler),
+        origin: format!("[tools] compiler in {}", path.display()),
+    }))
+}
+
+/// The compiler of the toolchain `stoffel toolchain install` last activated
+fn active_toolchain() -> Result<Option<Pinned>, String> {
+    let Some(dir) = toolchains_dir() else {
+        return Ok(None);
+    };
+    let Some(version) = ToolchainIndex::load(&dir)?.active else {
+        return Ok(None);
+    };
+    Ok(Some(Pinned {
+        path: dir.join(&version).join(COMPILER.binary),
+        origin: format!("active toolchain {}", version),
+    }))
+}
+
+/// Index of installed toolchains inside the toolchains directory
+pub const TOOLCHAIN_INDEX_NAME: &str = "toolchains.toml";
+
+/// Directory installed toolchains live in (`~/.stoffel/toolchains`)
+pub fn toolchains_dir() -> Option<PathBuf> {
+    crate::home::stoffel_home().map(|home| home.join("toolchains"))
+}
+
+/// Installed toolchains recorded in toolchains.toml
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ToolchainIndex {
+    /// Version whose compiler `find_compiler` uses
+    pub active: Option<String>,
+    #[serde(default, rename = "toolchain")]
+    pub toolchains: Vec<InstalledToolchain>,
+}
+
+/// A single installed toolchain
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstalledToolchain {
+    pub version: String,
+    /// Compiler binary or Stoffel-Lang checkout it was installed from
+    pub source: String,
+}
+
+impl ToolchainIndex {
+    /// Load the index of `dir`; a missing index means nothing is installed
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(TOOLCHAIN_INDEX_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(TOOLCHAIN_INDEX_NAME);
+        let content = toml::to_string(self)
+            .map_err(|e| format!("Failed to serialize toolchain index: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Record a toolchain, replacing any earlier install of the same version
+    fn record(&mut self, toolchain: InstalledToolchain) {
+        self.toolchains.retain(|installed| installed.version != toolchain.version);
+        self.toolchains.push(toolchain);
+        self.toolchains.sort_by(|a, b| a.version.cmp(&b.version));
+    }
+}
+
+/// Install the compiler at `source` into `<dir>/<version>/` and make it the active toolchain
+///
+/// `source` is a compiler binary or a Stoffel-Lang checkout that has been
+/// built. The binary is copied in under a temporary name and renamed, so a
+/// compiler that is still running is never overwritten in place.
+pub fn install(version: &str, source: &Path, dir: &Path) -> Result<InstalledToolchain, String> {
+    if version.is_empty()
+        || version.starts_with('.')
+        || !version.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'))
+    {
+        return Err(format!(
+            "Invalid toolchain version '{}': use letters, digits, '.', '-', '_' and '+'",
+            version
+        ));
+    }
+    let compiler = from_setting(&COMPILER, source, &source.display().to_string())?;
+
+    let toolchain_dir = dir.join(version);
+    fs::create_dir_all(&toolchain_dir)
+        .map_err(|e| format!("Failed to create {}: {}", toolchain_dir.display(), e))?;
+    let installed = toolchain_dir.join(COMPILER.binary);
+    let staging = toolchain_dir.join(format!(".{}.{}", COMPILER.binary, std::process::id()));
+    fs::copy(&compiler, &staging)
+        .and_then(|_| fs::rename(&staging, &installed))
+        .map_err(|e| {
+            let _ = fs::remove_file(&staging);
+            format!("Failed to install {} into {}: {}", compiler.display(), toolchain_dir.display(), e)
+        })?;
+
+    let toolchain = InstalledToolchain {
+        version: version.to_string(),
+        source: fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf()).display().to_string(),
+    };
+    let mut index = ToolchainIndex::load(dir)?;
+    index.record(toolchain.clone());
+    index.active = Some(toolchain.version.clone());
+    index.save(dir)?;
+    Ok(toolchain)
+}
+
+/// The version the Stoffel-Lang compiler reports for `--version`
+pub fn compiler_version(compiler: &Path) -> Result<String, String> {
+    let output = Command::new(compiler)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", compiler.display(), e))?;
+    if !output.status.success() {
+        return Err(format!("{} --version failed with {}", compiler.display(), output.status));
+    }
+    version_line(&String::from_utf8_lossy(&output.stdout))
+        .map(str::to_string)
+        .ok_or_else(|| format!("{} --version printed nothing", compiler.display()))
+}
+
+/// First non-blank line of a `--version` banner
+fn version_line(stdout: &str) -> Option<&str> {
+    stdout.lines().map(str::trim).find(|line| !line.is_empty())
+}
+
+/// Locate a tool
+///
+/// A path pinned in config wins; otherwise checks the tool's environment
+/// variable, then the active toolchain, then its binary on `PATH`, and
+/// finally a checkout next to the directory this CLI was built in.
+fn find(tool: &Tool, pinned: Option<Pinned>, toolchain: Option<Pinned>) -> Result<PathBuf, String> {
+    let exe_path = std::env::current_exe()
+        .map_err(|e| format!("Failed to get executable path: {}", e))?;
+    let tool_path = locate(
+        tool,
+        pinned.as_ref(),
+        std::env::var_os(tool.env).as_deref(),
+        toolchain.as_ref(),
+        std::env::var_os("PATH").as_deref(),
+        &exe_path,
+    )?;
+
+    log::debug!("🔧 Using {}: {}", tool.label, tool_path.display());
+    Ok(tool_path)
+}
+
+fn locate(
+    tool: &Tool,
+    pinned: Option<&Pinned>,
+    env_override: Option<&OsStr>,
+    toolchain: Option<&Pinned>,
+    path_var: Option<&OsStr>,
+    exe_path: &Path,
+) -> Result<PathBuf, String> {
+    if let Some(pinned) = pinned {
+        return from_setting(tool, &pinned.path, &pinned.origin);
+    }
+    if let Some(value) = env_override.filter(|value| !value.is_empty()) {
+        let value = Path::new(value);
+        return from_setting(tool, value, &format!("{}={}", tool.env, value.display()));
+    }
+    if let Some(toolchain) = toolchain {
+        return from_setting(tool, &toolchain.path, &toolchain.origin);
+    }
+
+    if let Some(tool_path) = path_var.and_then(|path_var| search_path(tool, path_var)) {
+        return Ok(tool_path);
+    }
+
+    relative_to_exe(tool, exe_path)
+}
+
+/// Resolve a configured path, which may name the binary itself or a checkout
+fn from_setting(tool: &Tool, value: &Path, origin: &str) -> Result<PathBuf, String> {
+    let tool_path = if value.is_dir() {
+        checkout_build(tool, value)
+    } else {
+        value.to_path_buf()
+    };
+
+    if !tool_path.is_file() {
+        return Err(format!("{} not found at: {} (from {})", tool.label, tool_path.display(), origin));
+    }
+    Ok(tool_path)
+}
+
+fn search_path(tool: &Tool, path_var: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(path_var)
+        .map(|dir| dir.join(tool.binary))
+        .find(|candidate| candidate.is_file())
+}
+
+/// The tool built in a checkout of its repository
+///
+/// Uses whichever of the debug and release builds exists, preferring the
+/// newer one when both do. If neither exists the debug path is returned so
+/// the caller can report where it looked.
+fn checkout_build(tool: &Tool, checkout: &Path) -> PathBuf {
+    let debug = checkout.join("target").join("debug").join(tool.binary);
+    let release = checkout.join("target").join("release").join(tool.binary);
+    let modified = |path: &Path| path.metadata().and_then(|m| m.modified()).ok();
+
+    match (modified(&debug), modified(&release)) {
+        (Some(debug_time), Some(release_time)) if release_time > debug_time => release,
+        (None, Some(_)) => release,
+        _ => debug,
+    }
+}
+
+/// The historical layout: the tool's repository checked out beside this one
+fn relative_to_exe(tool: &Tool, exe_path: &Path) -> Result<PathBuf, String> {
+    let checkout_path = exe_path
+        .ancestors()
+        .nth(4)
+        .map(|p| p.join(tool.checkout))
+        .ok_or_else(|| format!("Could not locate {} directory", tool.checkout))?;
+
+    let tool_path = checkout_build(tool, &checkout_path);
+
+    if !tool_path.exists() {
+        return Err(format!(
+            "{} not found. Looked in these places, in order:\n{}\n   Build {} with `cd {} && cargo build`, or point one of the above at it",
+            tool.label,
+            search_order(tool, &tool_path),
+            tool.checkout,
+            checkout_path.display()
+        ));
+    }
+
+    Ok(tool_path)
+}
+
+/// The places `locate` checks, numbered in the order it checks them
+fn search_order(tool: &Tool, checkout_build: &Path) -> String {
+    let mut places = Vec::new();
+    if let Some(key) = tool.config_key {
+        places.push(format!("[tools] {} in {}", key, config::MANIFEST_NAME));
+        places.push(format!("[tools] {} in ~/.stoffel/config.toml", key));
+    }
+    places.push(format!("the {} environment variable", tool.env));
+    if tool.managed {
+        places.push("the active toolchain (`stoffel toolchain install`)".to_string());
+    }
+    places.push(format!("{} on your PATH", tool.binary));
+    places.push(format!("a {} checkout beside this CLI: {}", tool.checkout, checkout_build.display()));
+
+    places
+        .iter()
+        .enumerate()
+        .map(|(index, place)| format!("   {}. {}", index + 1, place))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn fake_compiler(dir: &Path) -> PathBuf {
+        let path = dir.join(COMPILER.binary);
+        fs::create_dir_all(dir).unwrap();
+        fs::write(&path, "").unwrap();
+        path
+    }
+
+    #[test]
+    fn env_var_naming_the_binary_wins() {
+        let env_dir = tempfile::tempdir().unwrap();
+        let path_dir = tempfile::tempdir().unwrap();
+        let expected = fake_compiler(env_dir.path());
+        fake_compiler(path_dir.path());
+
+        let found = locate(&COMPILER, None, Some(expected.as_os_str()), None, Some(path_dir.path().as_os_str()), Path::new("/")).unwrap();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn env_var_naming_a_checkout_uses_its_debug_build() {
+        let checkout = tempfile::tempdir().unwrap();
+        let expected = fake_compiler(&checkout.path().join("target").join("debug"));
+
+        let found = locate(&COMPILER, None, Some(checkout.path().as_os_str()), None, None, Path::new("/")).unwrap();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn checkout_falls_back_to_release_build() {
+        let checkout = tempfile::tempdir().unwrap();
+        let release = fake_compiler(&checkout.path().join("target").join("release"));
+        assert_eq!(checkout_build(&COMPILER, checkout.path()), release);
+    }
+
+    #[test]
+    fn checkout_prefers_newer_build() {
+        let checkout = tempfile::tempdir().unwrap();
+        let debug = fake_compiler(&checkout.path().join("target").join("debug"));
+        let release = fake_compiler(&checkout.path().join("target").join("release"));
+        let now = std::time::SystemTime::now();
+        let hour = std::time::Duration::from_secs(3600);
+
+        fs::File::options().write(true).open(&debug).unwrap().set_modified(now - hour).unwrap();
+        assert_eq!(checkout_build(&COMPILER, checkout.path()), release);
+
+        fs::File::options().write(true).open(&release).unwrap().set_modified(now - hour * 2).unwrap();
+        assert_eq!(checkout_build(&COMPILER, checkout.path()), debug);
+    }
+
+    #[test]
+    fn version_banner_is_reduced_to_its_first_line() {
+        assert_eq!(version_line("\n  stoffellang 0.3.1  \nbuilt 2025-01-01\n"), Some("stoffellang 0.3.1"));
+        assert_eq!(version_line(" \n"), None);
+    }
+
+    #[test]
+    fn env_var_pointing_nowhere_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path_dir = tempfile::tempdir().unwrap();
+        fake_compiler(path_dir.path());
+        let missing = dir.path().join("missing");
+
+        let err = locate(&COMPILER, None, Some(missing.as_os_str()), None, Some(path_dir.path().as_os_str()), Path::new("/")).unwrap_err();
+        assert!(err.contains(COMPILER.env), "{}", err);
+    }
+
+    #[test]
+    fn pinned_path_wins_over_the_environment() {
+        let pinned_dir = tempfile::tempdir().unwrap();
+        let env_dir = tempfile::tempdir().unwrap();
+        let expected = fake_compiler(pinned_dir.path());
+        let from_env = fake_compiler(env_dir.path());
+        let pinned = Pinned { path: expected.clone(), origin: "[tools] compiler in Stoffel.toml".to_string() };
+
+        let found = locate(&COMPILER, Some(&pinned), Some(from_env.as_os_str()), None, None, Path::new("/")).unwrap();
+        assert_eq!(found, expected);
+
+        let missing = Pinned { path: pinned_dir.path().join("missing"), ..pinned };
+        let err = locate(&COMPILER, Some(&missing), Some(from_env.as_os_str()), None, None, Path::new("/")).unwrap_err();
+        assert!(err.contains("[tools] compiler in Stoffel.toml"), "{}", err);
+    }
+
+    #[test]
+    fn not_found_lists_the_search_order() {
+        let exe = Path::new("/opt/stoffel/target/debug/deps/stoffel");
+        let err = locate(&COMPILER, None, None, None, None, exe).unwrap_err();
+        let order = ["[tools] compiler in Stoffel.toml", "~/.stoffel/config.toml", "STOFFEL_LANG", "active toolchain", "on your PATH", "checkout"];
+        let positions: Vec<usize> = order.iter().map(|place| err.find(place).unwrap_or_else(|| panic!("{}", err))).collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{}", err);
+    }
+
+    #[test]
+    fn installed_toolchain_becomes_active_and_is_found() {
+        let checkout = tempfile::tempdir().unwrap();
+        let toolchains = tempfile::tempdir().unwrap();
+        let path_dir = tempfile::tempdir().unwrap();
+        fake_compiler(&checkout.path().join("target").join("release"));
+        fake_compiler(path_dir.path());
+
+        install("0.3.0", checkout.path(), toolchains.path()).unwrap();
+        let toolchain = install("0.4.0-rc.1", checkout.path(), toolchains.path()).unwrap();
+        let index = ToolchainIndex::load(toolchains.path()).unwrap();
+        assert_eq!(index.active.as_deref(), Some("0.4.0-rc.1"));
+        assert_eq!(index.toolchains.len(), 2);
+        assert_eq!(index.toolchains[1], toolchain);
+
+        // The active toolchain is used ahead of PATH
+        let active = Pinned { path: toolchains.path().join("0.4.0-rc.1").join(COMPILER.binary), origin: "active toolchain 0.4.0-rc.1".to_string() };
+        let found = locate(&COMPILER, None, None, Some(&active), Some(path_dir.path().as_os_str()), Path::new("/")).unwrap();
+        assert_eq!(found, active.path);
+
+        assert!(install("../escape", checkout.path(), toolchains.path()).unwrap_err().contains("Invalid toolchain version"));
+        let missing = toolchains.path().join("missing");
+        assert!(install("0.5.0", &missing, toolchains.path()).is_err());
+        assert!(!toolchains.path().join("0.5.0").exists());
+    }
+
+    #[test]
+    fn falls_back_to_path() {
+        let path_dir = tempfile::tempdir().unwrap();
+        let expected = fake_compiler(path_dir.path());
+        let path_var = std::env::join_paths([Path::new("/nonexistent"), path_dir.path()]).unwrap();
+
+        let found = locate(&COMPILER, None, None, None, Some(&path_var), Path::new("/")).unwrap();
+        assert_eq!(found, expected);
+    }
+}
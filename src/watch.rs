@@ -0,0 +1,150 @@
+use notify::{RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::style::{self, Status};
+
+/// How long the tree must be quiet before changed files are rebuilt
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+const STOPPED: &str = "File watcher stopped unexpectedly";
+
+/// Watch `dir` and call `on_change` once per changed .stfl file
+///
+/// Runs until the process is interrupted.
+pub fn watch_sources(dir: &Path, mut on_change: impl FnMut(&Path)) -> Result<(), String> {
+    let watcher = SourceWatcher::new(dir)?;
+    loop {
+        for path in watcher.changes(None)? {
+            on_change(&path);
+        }
+    }
+}
+
+/// A recursive watch on a source directory
+pub struct SourceWatcher {
+    watcher: notify::RecommendedWatcher,
+    receiver: mpsc::Receiver<notify::Result<notify::Event>>,
+    cwd: PathBuf,
+    /// Files added with `watch_file`, reported even though they aren't .stfl
+    files: Vec<PathBuf>,
+}
+
+impl SourceWatcher {
+    pub fn new(dir: &Path) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+        let cwd = std::env::current_dir()
+            .map_err(|e| format!("Failed to get current directory: {}", e))?;
+
+        Ok(SourceWatcher {
+            watcher,
+            receiver,
+            cwd,
+            files: Vec::new(),
+        })
+    }
+
+    /// Also watch the .stfl files in `dir`, and in its subdirectories if `recursive`
+    pub fn watch_dir(&mut self, dir: &Path, recursive: bool) -> Result<(), String> {
+        let mode = if recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        self.watcher
+            .watch(dir, mode)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))
+    }
+
+    /// Also watch one file, such as Stoffel.toml, and report its changes
+    pub fn watch_file(&mut self, file: &Path) -> Result<(), String> {
+        self.watcher
+            .watch(file, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", file.display(), e))?;
+        self.files.push(file.to_path_buf());
+        Ok(())
+    }
+
+    /// Wait for the next burst of changes, or up to `timeout` if given
+    ///
+    /// Editors often write a file several times per save, so events are
+    /// collected until nothing has changed for a short while and each file
+    /// is reported once, relative to the current directory. An empty list
+    /// means the timeout passed, or the burst touched no .stfl files.
+    pub fn changes(&self, timeout: Option<Duration>) -> Result<Vec<PathBuf>, String> {
+        let first = match timeout {
+            Some(timeout) => match self.receiver.recv_timeout(timeout) {
+                Ok(event) => event,
+                Err(mpsc::RecvTimeoutError::Timeout) => return Ok(Vec::new()),
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Err(STOPPED.to_string()),
+            },
+            None => self.receiver.recv().map_err(|_| STOPPED.to_string())?,
+        };
+
+        let mut changed = BTreeSet::new();
+        collect(first, &self.files, &mut changed);
+        while let Ok(event) = self.receiver.recv_timeout(DEBOUNCE) {
+            collect(event, &self.files, &mut changed);
+        }
+
+        Ok(changed
+            .into_iter()
+            .filter(|path| path.is_file())
+            .map(|path| path.strip_prefix(&self.cwd).map(Path::to_path_buf).unwrap_or(path))
+            .collect())
+    }
+}
+
+fn collect(event: notify::Result<notify::Event>, files: &[PathBuf], changed: &mut BTreeSet<PathBuf>) {
+    match event {
+        Ok(event) if event.kind.is_create() || event.kind.is_modify() => {
+            changed.extend(
+                event.paths.into_iter()
+                    .filter(|path| is_source(path) || files.iter().any(|file| path.ends_with(file))),
+            );
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("{}  Watch error: {}", style::prefix(Status::Warning), e),
+    }
+}
+
+fn is_source(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "stfl")
+}
+
+/// Current UTC time of day as HH:MM:SS, for rebuild log lines
+pub fn timestamp() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+        % 86_400;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, EventKind};
+    use notify::Event;
+
+    #[test]
+    fn collects_changed_sources_once() {
+        let mut changed = BTreeSet::new();
+        let event = |kind, path: &str| Ok(Event::new(kind).add_path(PathBuf::from(path)));
+
+        collect(event(EventKind::Create(CreateKind::File), "src/a.stfl"), &[], &mut changed);
+        collect(event(EventKind::Create(CreateKind::File), "src/a.stfl"), &[], &mut changed);
+        collect(event(EventKind::Create(CreateKind::File), "src/notes.md"), &[], &mut changed);
+        collect(event(EventKind::Access(AccessKind::Any), "src/b.stfl"), &[], &mut changed);
+        collect(event(EventKind::Create(CreateKind::File), "/demo/Stoffel.toml"), &[], &mut changed);
+
+        assert_eq!(changed.iter().collect::<Vec<_>>(), [&PathBuf::from("src/a.stfl")]);
+
+        collect(event(EventKind::Create(CreateKind::File), "/demo/Stoffel.toml"), &[PathBuf::from("Stoffel.toml")], &mut changed);
+        assert!(changed.contains(Path::new("/demo/Stoffel.toml")));
+    }
+}